@@ -0,0 +1,234 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::{TypePath, TypeUuid},
+    utils::{BoxedFuture, HashMap},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    assets::{AddAssetCollection, AssetCollection},
+    consts,
+    events::{BreakAsteroid, SpaceshipDamaged, SpaceshipDashed, WaveEnded},
+    is_playing,
+    player::ChargedShot,
+    save, GameState, Stats,
+};
+
+pub struct AchievementsPlugin;
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<AchievementList>()
+            .init_asset_loader::<AchievementListLoader>()
+            .add_asset_collection::<AchievementsHandle>(GameState::LoadingMenu)
+            .add_event::<AchievementUnlocked>()
+            .init_resource::<AchievementTracker>()
+            .insert_resource(
+                save::load::<AchievementSave>(consts::ACHIEVEMENTS_SAVE).unwrap_or_default(),
+            )
+            .add_systems(OnEnter(GameState::Gameplay), reset_achievement_tracker)
+            .add_systems(
+                Update,
+                (track_achievement_progress, unlock_achievements)
+                    .chain()
+                    .run_if(is_playing),
+            )
+            .add_systems(OnExit(GameState::Gameplay), store_achievements);
+    }
+}
+
+// ===
+
+#[derive(Deserialize, Clone, Debug)]
+pub enum AchievementCondition {
+    // Score reached in a single run
+    Score(u32),
+    // Wave cleared without any spaceship taking damage in the run
+    WaveWithoutDamage(u32),
+    // Asteroids destroyed by a single charged shot over its whole lifetime
+    ChargedShotKills(u32),
+    // Dashes across all runs
+    Dashes(u32),
+    // Asteroids destroyed across all runs
+    AsteroidsDestroyed(u32),
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct Achievement {
+    pub id: String,
+    // Keys of the name and the description in the string tables
+    pub name: String,
+    pub description: String,
+    pub condition: AchievementCondition,
+}
+
+// Achievement definitions, loaded from the assets folder
+#[derive(Deserialize, TypeUuid, TypePath, Debug)]
+#[uuid = "8e3b52d1-7c4f-4a90-b6e2-3f1d9a5c7e08"]
+pub struct AchievementList {
+    pub achievements: Vec<Achievement>,
+}
+
+#[derive(Resource)]
+pub struct AchievementsHandle(pub Handle<AchievementList>);
+
+impl AssetCollection for AchievementsHandle {
+    fn load(asset_server: &AssetServer) -> Self {
+        AchievementsHandle(asset_server.load(consts::ACHIEVEMENTS))
+    }
+
+    fn handles(&self) -> Vec<HandleUntyped> {
+        vec![self.0.clone_weak_untyped()]
+    }
+}
+
+#[derive(Default)]
+struct AchievementListLoader;
+
+impl AssetLoader for AchievementListLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let achievement_list = ron::de::from_bytes::<AchievementList>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(achievement_list));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["achievements.ron"]
+    }
+}
+
+// Unlocked achievements and the counters that carry over between runs
+#[derive(Resource, Serialize, Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct AchievementSave {
+    pub unlocked: Vec<String>,
+    pub dashes: u32,
+    pub asteroids_destroyed: u32,
+}
+
+impl AchievementSave {
+    pub fn is_unlocked(&self, id: &str) -> bool {
+        self.unlocked.iter().any(|unlocked| unlocked == id)
+    }
+}
+
+// Progress of the current run, saved with it
+#[derive(Resource, Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(default)]
+pub struct AchievementTracker {
+    damage_taken: bool,
+    // Asteroids destroyed by the charged shots still in flight,
+    // their kills aren't saved with the run
+    #[serde(skip)]
+    charged_shot_kills: HashMap<Entity, u32>,
+    best_charged_shot_kills: u32,
+    waves_cleared: u32,
+}
+
+// Name and description are keys in the string tables
+#[derive(Event)]
+pub struct AchievementUnlocked {
+    pub name: String,
+    pub description: String,
+}
+
+// ===
+
+fn reset_achievement_tracker(mut tracker: ResMut<AchievementTracker>) {
+    *tracker = AchievementTracker::default();
+}
+
+fn track_achievement_progress(
+    mut ev_break_asteroid: EventReader<BreakAsteroid>,
+    mut ev_damaged: EventReader<SpaceshipDamaged>,
+    mut ev_dashed: EventReader<SpaceshipDashed>,
+    mut ev_wave_ended: EventReader<WaveEnded>,
+    mut removed_charged_shots: RemovedComponents<ChargedShot>,
+    mut tracker: ResMut<AchievementTracker>,
+    mut achievement_save: ResMut<AchievementSave>,
+) {
+    let shot_down = ev_break_asteroid
+        .iter()
+        .filter(|break_ev| break_ev.is_shot_down());
+    for break_ev in BreakAsteroid::unique(shot_down) {
+        achievement_save.asteroids_destroyed += 1;
+
+        // Charged shots count for as long as they last, like a beam sweeping across the screen
+        if let (Some(shot), true) = (break_ev.source, break_ev.charged) {
+            let kills = tracker.charged_shot_kills.entry(shot).or_default();
+            *kills += 1;
+            let kills = *kills;
+            tracker.best_charged_shot_kills = tracker.best_charged_shot_kills.max(kills);
+        }
+    }
+    for shot in removed_charged_shots.iter() {
+        tracker.charged_shot_kills.remove(&shot);
+    }
+    achievement_save.dashes += ev_dashed.iter().count() as u32;
+
+    if ev_damaged.iter().count() > 0 {
+        tracker.damage_taken = true;
+    }
+
+    for wave_ended_ev in ev_wave_ended.iter() {
+        tracker.waves_cleared = wave_ended_ev.0;
+    }
+}
+
+fn unlock_achievements(
+    mut ev_unlocked: EventWriter<AchievementUnlocked>,
+    mut achievement_save: ResMut<AchievementSave>,
+    tracker: Res<AchievementTracker>,
+    stats: Res<Stats>,
+    achievements_handle: Res<AchievementsHandle>,
+    achievement_lists: Res<Assets<AchievementList>>,
+) {
+    if let Some(achievement_list) = achievement_lists.get(&achievements_handle.0) {
+        let mut unlocked_any = false;
+
+        for achievement in achievement_list.achievements.iter() {
+            if achievement_save.is_unlocked(&achievement.id) {
+                continue;
+            }
+
+            let unlocked = match achievement.condition {
+                AchievementCondition::Score(score) => stats.score >= score,
+                AchievementCondition::WaveWithoutDamage(wave) => {
+                    !tracker.damage_taken && tracker.waves_cleared >= wave
+                }
+                AchievementCondition::ChargedShotKills(kills) => {
+                    tracker.best_charged_shot_kills >= kills
+                }
+                AchievementCondition::Dashes(dashes) => achievement_save.dashes >= dashes,
+                AchievementCondition::AsteroidsDestroyed(asteroids) => {
+                    achievement_save.asteroids_destroyed >= asteroids
+                }
+            };
+
+            if unlocked {
+                achievement_save.unlocked.push(achievement.id.clone());
+                ev_unlocked.send(AchievementUnlocked {
+                    name: achievement.name.clone(),
+                    description: achievement.description.clone(),
+                });
+                unlocked_any = true;
+            }
+        }
+
+        if unlocked_any {
+            save::store(consts::ACHIEVEMENTS_SAVE, &*achievement_save);
+        }
+    }
+}
+
+// Counters change all the time, so they are only written out once the run is over
+fn store_achievements(achievement_save: Res<AchievementSave>) {
+    save::store(consts::ACHIEVEMENTS_SAVE, &*achievement_save);
+}
@@ -0,0 +1,137 @@
+use bevy::{asset::LoadState, prelude::*};
+
+use crate::{transition::ChangeState, GameState};
+
+pub struct AssetsPlugin;
+
+impl Plugin for AssetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LoadingAssets>()
+            .init_resource::<FailedAssets>()
+            .add_systems(
+                Update,
+                check_loading_progress.run_if(
+                    in_state(GameState::LoadingMenu).or_else(in_state(GameState::LoadingGame)),
+                ),
+            )
+            .add_systems(OnExit(GameState::LoadingMenu), clear_loading_assets)
+            .add_systems(OnExit(GameState::LoadingGame), clear_loading_assets);
+    }
+}
+
+// ===
+
+// Group of asset handles that is stored as a resource once loaded
+pub trait AssetCollection: Resource + Sized {
+    fn load(asset_server: &AssetServer) -> Self;
+
+    fn handles(&self) -> Vec<HandleUntyped>;
+}
+
+pub trait AddAssetCollection {
+    // Starts loading the collection when entering the loading state,
+    // the state is left only after every collection has finished loading
+    fn add_asset_collection<T: AssetCollection>(&mut self, loading_state: GameState) -> &mut Self;
+}
+
+impl AddAssetCollection for App {
+    fn add_asset_collection<T: AssetCollection>(&mut self, loading_state: GameState) -> &mut Self {
+        self.add_systems(OnEnter(loading_state), load_asset_collection::<T>)
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct LoadingAssets {
+    handles: Vec<HandleUntyped>,
+    pub loaded: usize,
+}
+
+// Paths of the assets that couldn't be loaded, kept apart from the loading progress
+// so they're still around for the error screen after the loading state is left
+#[derive(Resource, Debug, Default)]
+pub struct FailedAssets(pub Vec<String>);
+
+impl LoadingAssets {
+    pub fn total(&self) -> usize {
+        self.handles.len()
+    }
+
+    pub fn progress(&self) -> f32 {
+        if self.handles.is_empty() {
+            1.0
+        } else {
+            self.loaded as f32 / self.total() as f32
+        }
+    }
+
+    // Tracks handles that aren't part of an asset collection,
+    // handles that are already tracked are skipped
+    pub fn add_handles(&mut self, handles: impl IntoIterator<Item = HandleUntyped>) {
+        for handle in handles {
+            if !self.handles.contains(&handle) {
+                self.handles.push(handle);
+            }
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.loaded == self.total()
+    }
+}
+
+// ===
+
+fn load_asset_collection<T: AssetCollection>(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut loading_assets: ResMut<LoadingAssets>,
+) {
+    let collection = T::load(&asset_server);
+    loading_assets.add_handles(collection.handles());
+
+    commands.insert_resource(collection);
+}
+
+pub fn check_loading_progress(
+    mut ev_change: EventWriter<ChangeState>,
+    asset_server: Res<AssetServer>,
+    state: Res<State<GameState>>,
+    mut loading_assets: ResMut<LoadingAssets>,
+    mut failed_assets: ResMut<FailedAssets>,
+) {
+    let mut loaded = 0;
+    let mut failed = vec![];
+
+    for handle in loading_assets.handles.iter() {
+        match asset_server.get_load_state(handle.id()) {
+            LoadState::Loaded => loaded += 1,
+            LoadState::Failed => {
+                let path = match asset_server.get_handle_path(handle.id()) {
+                    Some(asset_path) => asset_path.path().display().to_string(),
+                    None => format!("{:?}", handle.id()),
+                };
+                failed.push(path);
+            }
+            _ => (),
+        }
+    }
+
+    loading_assets.loaded = loaded;
+
+    if !failed.is_empty() {
+        error!("Failed to load assets: {:?}", failed);
+        failed_assets.0 = failed;
+        ev_change.send(ChangeState::to(GameState::LoadingError));
+    } else if loading_assets.is_finished() {
+        let next_state = match state.get() {
+            GameState::LoadingMenu => GameState::MainMenu,
+            _ => GameState::Gameplay,
+        };
+        ev_change.send(ChangeState::to(next_state));
+    }
+}
+
+fn clear_loading_assets(mut loading_assets: ResMut<LoadingAssets>) {
+    loading_assets.handles.clear();
+    loading_assets.loaded = 0;
+}
@@ -0,0 +1,365 @@
+use std::f32::consts::TAU;
+
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    consts, enemy::GameplayStage, events::SpaceshipDashed, is_gameplay, GameState, GameplayState,
+    WinSize,
+};
+
+pub struct BackgroundPlugin;
+
+impl Plugin for BackgroundPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(StarfieldSeed(consts::MENU_STARFIELD_SEED))
+            .init_resource::<ScrollSpeed>()
+            .add_systems(Startup, create_background_images)
+            .add_systems(OnEnter(GameState::MainMenu), reset_menu_starfield)
+            .add_systems(
+                Update,
+                (
+                    build_starfield.run_if(resource_changed::<StarfieldSeed>()),
+                    update_scroll_speed.run_if(is_gameplay),
+                    (scroll_background, twinkle_stars, spawn_background_objects)
+                        .after(build_starfield),
+                )
+                    .run_if(not(in_state(GameplayState::Paused))),
+            );
+    }
+}
+
+// ===
+
+// Seed of the starfield, every run gets its own one so the background
+// is the same whenever the run is played with the same seed
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct StarfieldSeed(pub u64);
+
+// Separate from the gameplay generator, so the background never changes how a run plays out
+#[derive(Resource)]
+struct StarfieldRng(ChaCha8Rng);
+
+#[derive(Resource)]
+struct ScrollSpeed {
+    multiplier: f32,
+    dash_boost: f32,
+}
+
+impl Default for ScrollSpeed {
+    fn default() -> Self {
+        Self {
+            multiplier: 1.0,
+            dash_boost: 0.0,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct BackgroundImages {
+    nebula: Handle<Image>,
+    planet: Handle<Image>,
+}
+
+// Time until the next nebula or planet drifts in
+#[derive(Resource)]
+struct NextBackgroundObject(Timer);
+
+#[derive(Component)]
+struct Background;
+
+#[derive(Component)]
+struct Scrolling {
+    // Share of the scroll speed, further layers move slower
+    depth: f32,
+}
+
+#[derive(Component)]
+struct Star {
+    alpha: f32,
+    twinkle: f32,
+    frequency: f32,
+    phase: f32,
+}
+
+// Nebulas and planets are despawned once they leave the screen, stars wrap around
+#[derive(Component)]
+struct BackgroundObject;
+
+struct StarLayer {
+    depth: f32,
+    size: f32,
+    alpha: f32,
+    share: f32,
+}
+
+const STAR_LAYERS: [StarLayer; 3] = [
+    StarLayer {
+        depth: 0.2,
+        size: 1.0,
+        alpha: 0.4,
+        share: 0.5,
+    },
+    StarLayer {
+        depth: 0.5,
+        size: 2.0,
+        alpha: 0.7,
+        share: 0.35,
+    },
+    StarLayer {
+        depth: 1.0,
+        size: 3.0,
+        alpha: 1.0,
+        share: 0.15,
+    },
+];
+
+pub fn new_image(size: u32, pixel: impl Fn(f32, f32) -> [u8; 4]) -> Image {
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            // Position relative to the center, from -1 to 1
+            let u = (x as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+            let v = (y as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+            data.extend_from_slice(&pixel(u, v));
+        }
+    }
+
+    Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+// ===
+
+// Nebula is a soft blob and planet a lit sphere, both white so sprites can tint them
+fn create_background_images(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let nebula = new_image(64, |u, v| {
+        let falloff = (1.0 - (u * u + v * v).sqrt()).max(0.0);
+        [255, 255, 255, (falloff.powi(2) * 255.0) as u8]
+    });
+
+    let planet = new_image(64, |u, v| {
+        let distance = u * u + v * v;
+        if distance > 1.0 {
+            return [0, 0, 0, 0];
+        }
+
+        // Light comes from the top left
+        let normal = Vec3::new(u, -v, (1.0 - distance).sqrt());
+        let light = normal.dot(Vec3::new(-0.5, 0.5, 0.7).normalize()).max(0.0);
+        let shade = ((0.15 + 0.85 * light) * 255.0) as u8;
+        [shade, shade, shade, 255]
+    });
+
+    commands.insert_resource(BackgroundImages {
+        nebula: images.add(nebula),
+        planet: images.add(planet),
+    });
+}
+
+fn build_starfield(
+    mut commands: Commands,
+    seed: Res<StarfieldSeed>,
+    win_size: Res<WinSize>,
+    background_query: Query<Entity, With<Background>>,
+) {
+    for entity in background_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let mut rng = ChaCha8Rng::seed_from_u64(seed.0);
+
+    for (idx, layer) in STAR_LAYERS.iter().enumerate() {
+        let count = (consts::STARFIELD_STARS as f32 * layer.share) as usize;
+
+        for _ in 0..count {
+            let x = rng.gen_range(-win_size.w / 2.0..win_size.w / 2.0);
+            let y = rng.gen_range(-win_size.h / 2.0..win_size.h / 2.0);
+
+            // Only some of the stars twinkle
+            let twinkle = match rng.gen_bool(0.3) {
+                true => rng.gen_range(0.3..0.7),
+                false => 0.0,
+            };
+
+            commands.spawn((
+                Background,
+                Scrolling { depth: layer.depth },
+                Star {
+                    alpha: layer.alpha,
+                    twinkle,
+                    frequency: rng.gen_range(1.0..4.0),
+                    phase: rng.gen_range(0.0..TAU),
+                },
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::rgba(1.0, 1.0, 1.0, layer.alpha),
+                        custom_size: Some(Vec2::splat(layer.size)),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(x, y, consts::STARFIELD_Z + idx as f32 * 0.1),
+                    ..default()
+                },
+            ));
+        }
+    }
+
+    let first_object = rng.gen_range(0.0..consts::STARFIELD_OBJECT_INTERVAL);
+    commands.insert_resource(NextBackgroundObject(Timer::from_seconds(
+        first_object,
+        TimerMode::Once,
+    )));
+    commands.insert_resource(StarfieldRng(rng));
+}
+
+// Menus always show the same starfield, it's only rebuilt when coming back from a run
+fn reset_menu_starfield(mut seed: ResMut<StarfieldSeed>, mut scroll_speed: ResMut<ScrollSpeed>) {
+    if seed.0 != consts::MENU_STARFIELD_SEED {
+        seed.0 = consts::MENU_STARFIELD_SEED;
+    }
+    *scroll_speed = ScrollSpeed::default();
+}
+
+// Scrolling speeds up for a moment after every dash and stays fast between waves
+fn update_scroll_speed(
+    mut ev_dashed: EventReader<SpaceshipDashed>,
+    mut scroll_speed: ResMut<ScrollSpeed>,
+    stage_query: Query<&GameplayStage>,
+    time: Res<Time>,
+) {
+    if ev_dashed.iter().count() > 0 {
+        scroll_speed.dash_boost = consts::STARFIELD_DASH_BOOST;
+    }
+
+    let between_waves = stage_query
+        .get_single()
+        .is_ok_and(GameplayStage::is_between_waves);
+    let target = match between_waves {
+        true => consts::STARFIELD_WAVE_BOOST,
+        false => 1.0,
+    };
+
+    // Eases towards the target instead of jumping
+    let ease = 1.0 - (-consts::STARFIELD_SPEED_EASING * time.delta_seconds()).exp();
+    scroll_speed.multiplier += (target - scroll_speed.multiplier) * ease;
+    scroll_speed.dash_boost -= scroll_speed.dash_boost * ease;
+}
+
+fn scroll_background(
+    mut commands: Commands,
+    mut query: Query<(
+        Entity,
+        &mut Transform,
+        &Scrolling,
+        Option<&BackgroundObject>,
+    )>,
+    scroll_speed: Res<ScrollSpeed>,
+    win_size: Res<WinSize>,
+    time: Res<Time>,
+) {
+    let speed = consts::STARFIELD_SCROLL_SPEED
+        * (scroll_speed.multiplier + scroll_speed.dash_boost)
+        * time.delta_seconds();
+    let h_bound = win_size.h / 2.0;
+
+    for (entity, mut tf, scrolling, object) in query.iter_mut() {
+        tf.translation.y -= speed * scrolling.depth;
+
+        match object {
+            Some(_) if tf.translation.y < -h_bound - consts::DESPAWN_MARGIN * 2.0 => {
+                commands.entity(entity).despawn();
+            }
+            None if tf.translation.y < -h_bound => {
+                tf.translation.y += win_size.h;
+            }
+            _ => (),
+        }
+    }
+}
+
+fn twinkle_stars(mut query: Query<(&mut Sprite, &Star)>, time: Res<Time>) {
+    let elapsed = time.elapsed_seconds();
+
+    for (mut sprite, star) in query.iter_mut() {
+        if star.twinkle > 0.0 {
+            let wave = (elapsed * star.frequency + star.phase).sin() * 0.5 + 0.5;
+            sprite.color.set_a(star.alpha * (1.0 - star.twinkle * wave));
+        }
+    }
+}
+
+fn spawn_background_objects(
+    mut commands: Commands,
+    mut next_object: Option<ResMut<NextBackgroundObject>>,
+    mut rng: Option<ResMut<StarfieldRng>>,
+    images: Res<BackgroundImages>,
+    win_size: Res<WinSize>,
+    time: Res<Time>,
+) {
+    let (Some(next_object), Some(rng)) = (next_object.as_mut(), rng.as_mut()) else {
+        return;
+    };
+
+    next_object.0.tick(time.delta());
+    if !next_object.0.finished() {
+        return;
+    }
+
+    let rng = &mut rng.0;
+    let is_planet = rng.gen_bool(0.3);
+    let (texture, size, depth, color) = match is_planet {
+        true => (
+            images.planet.clone(),
+            rng.gen_range(40.0..120.0),
+            rng.gen_range(0.3..0.5),
+            Color::hsl(rng.gen_range(0.0..360.0), 0.4, 0.6),
+        ),
+        false => (
+            images.nebula.clone(),
+            rng.gen_range(300.0..600.0),
+            0.1,
+            Color::hsla(rng.gen_range(0.0..360.0), 0.6, 0.5, 0.25),
+        ),
+    };
+    let x = rng.gen_range(-win_size.w / 2.0..win_size.w / 2.0);
+    let y = win_size.h / 2.0 + size / 2.0;
+    // Nebulas stay behind the stars, planets in front of the far layers
+    let z = match is_planet {
+        true => consts::STARFIELD_Z + 0.15,
+        false => consts::STARFIELD_Z - 0.1,
+    };
+
+    commands.spawn((
+        Background,
+        BackgroundObject,
+        Scrolling { depth },
+        SpriteBundle {
+            texture,
+            sprite: Sprite {
+                color,
+                custom_size: Some(Vec2::splat(size)),
+                ..default()
+            },
+            transform: Transform::from_xyz(x, y, z),
+            ..default()
+        },
+    ));
+
+    let interval = rng.gen_range(
+        consts::STARFIELD_OBJECT_INTERVAL * 0.5..consts::STARFIELD_OBJECT_INTERVAL * 1.5,
+    );
+    next_object.0 = Timer::from_seconds(interval, TimerMode::Once);
+}
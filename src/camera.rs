@@ -1,3 +1,5 @@
+#[cfg(feature = "dev")]
+use bevy::{ecs::system::SystemParam, window::PrimaryWindow};
 use bevy::{prelude::*, render::camera::ScalingMode};
 
 use crate::consts;
@@ -13,6 +15,24 @@ impl Plugin for CameraPlugin {
 #[derive(Component)]
 struct GameCamera;
 
+// Cursor of the primary window, seen through the gameplay camera
+#[cfg(feature = "dev")]
+#[derive(SystemParam)]
+pub struct WorldCursor<'w, 's> {
+    window_query: Query<'w, 's, &'static Window, With<PrimaryWindow>>,
+    camera_query: Query<'w, 's, (&'static Camera, &'static GlobalTransform), With<GameCamera>>,
+}
+
+#[cfg(feature = "dev")]
+impl WorldCursor<'_, '_> {
+    pub fn position(&self) -> Option<Vec2> {
+        let cursor_position = self.window_query.get_single().ok()?.cursor_position()?;
+        let (camera, camera_tf) = self.camera_query.get_single().ok()?;
+
+        camera.viewport_to_world_2d(camera_tf, cursor_position)
+    }
+}
+
 fn spawn_camera(mut commands: Commands) {
     commands.spawn((
         GameCamera,
@@ -1,75 +1,210 @@
-use bevy::{math::Vec3Swizzles, prelude::*, sprite::collide_aabb::collide, utils::HashSet};
+use std::f32::consts::TAU;
+
+use bevy::{
+    ecs::system::SystemParam, math::Vec3Swizzles, prelude::*, sprite::collide_aabb::collide,
+    utils::HashSet,
+};
+use bevy_mod_aseprite::{Aseprite, AsepriteAnimation, AsepriteBundle};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    enemy::EnemyBundle,
-    events::{AddScore, AddScoreType, DespawnEntity, EventSet, SplitAsteroid},
+    assets::{AddAssetCollection, AssetCollection},
+    config::GameConfig,
+    consts,
+    enemy::{Enemy, EnemyBundle, EnemyHealth},
+    events::{DespawnEntity, EnemyIsHit, EventSet},
     is_playing,
-    movement::{Movable, Velocity},
-    player::{Invulnerability, PlayerAssetDimensions},
+    movement::{AngularVelocity, Movable, MovementSet, PatternMovement, Velocity},
+    player::{ChargedShot, Invulnerability, PlayerId},
+    rng::GameRng,
+    GameState,
 };
 
 pub struct CommonPlugin;
 
 impl Plugin for CommonPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            PreUpdate,
-            projectile_hit_detection
-                .in_set(EventSet::CreateEv)
-                .run_if(is_playing),
-        );
+        // Loaded with the menu, asteroids also drift across the title screen
+        app.add_asset_collection::<AsteroidHandles>(GameState::LoadingMenu)
+            .add_systems(
+                PreUpdate,
+                projectile_hit_detection
+                    .in_set(EventSet::CreateEv)
+                    .run_if(is_playing),
+            )
+            .add_systems(
+                Update,
+                (
+                    asteroid_break_animation,
+                    asteroid_collisions
+                        .after(MovementSet::UpdateVelocity)
+                        .before(MovementSet::ApplyVelocity),
+                )
+                    .run_if(is_playing),
+            );
     }
 }
 
 // ===
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
 pub enum AsteroidType {
     Small,
     Medium,
     Large,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
 pub struct Asteroid {
     pub asteroid_type: AsteroidType,
 }
 
 impl Asteroid {
+    // Picks a random sprite variant for the asteroid size,
+    // returns None when the picked aseprite hasn't been loaded
     pub fn construct_asteroid_bundle(
         &self,
         entity_type: EntityType,
         initial_velocity: Velocity,
         spawn_point: Vec3,
-    ) -> EnemyBundle {
-        let sprite = Sprite {
-            color: Color::rgb(0.5, 0.5, 0.5),
-            custom_size: match self.asteroid_type {
-                AsteroidType::Small => Some(Vec2::new(20.0, 20.0)),
-                AsteroidType::Medium => Some(Vec2::new(40.0, 40.0)),
-                AsteroidType::Large => Some(Vec2::new(70.0, 70.0)),
+        asteroid_assets: &AsteroidAssets,
+        rng: &mut GameRng,
+    ) -> Option<EnemyBundle> {
+        let variants = asteroid_assets.handles.variants(self.asteroid_type);
+        let variant = rng.gen_range(0..variants.len());
+        let aseprite_handle = &variants[variant];
+        let aseprite = asteroid_assets.aseprites.get(aseprite_handle)?;
+        let asteroid_config = asteroid_assets.config.asteroids.get(self.asteroid_type);
+        // Variants missing from the config fall back to the size of their canvas
+        let collider_size = asteroid_config
+            .collider(variant)
+            .unwrap_or_else(|| aseprite_size(aseprite));
+        let animation = AsepriteAnimation::new(aseprite.info(), "idle");
+
+        let rotation = Quat::from_rotation_z(rng.gen_range(0.0..TAU));
+        let spin =
+            AngularVelocity(rng.gen_range(-consts::ASTEROID_MAX_SPIN..consts::ASTEROID_MAX_SPIN));
+
+        Some(EnemyBundle::new(
+            entity_type,
+            initial_velocity,
+            spin,
+            EnemyHealth(asteroid_config.health),
+            Collider::new(collider_size),
+            AsepriteBundle {
+                texture_atlas: aseprite.atlas().clone_weak(),
+                sprite: TextureAtlasSprite::new(animation.current_frame()),
+                aseprite: aseprite_handle.clone_weak(),
+                animation,
+                transform: Transform::from_translation(spawn_point).with_rotation(rotation),
+                ..default()
             },
-            ..default()
+        ))
+    }
+}
+
+// Size of the aseprite canvas, which every frame shares
+pub fn aseprite_size(aseprite: &Aseprite) -> Vec2 {
+    let (width, height) = aseprite.info().dimensions;
+    Vec2::new(width as f32, height as f32)
+}
+
+// Sprites of the asteroid variants with their balance numbers from the config
+#[derive(SystemParam)]
+pub struct AsteroidAssets<'w> {
+    pub handles: Res<'w, AsteroidHandles>,
+    pub aseprites: Res<'w, Assets<Aseprite>>,
+    pub config: Res<'w, GameConfig>,
+}
+
+#[derive(Resource)]
+pub struct AsteroidHandles {
+    pub small: Vec<Handle<Aseprite>>,
+    pub medium: Vec<Handle<Aseprite>>,
+    pub large: Vec<Handle<Aseprite>>,
+}
+
+impl AsteroidHandles {
+    pub fn variants(&self, asteroid_type: AsteroidType) -> &[Handle<Aseprite>] {
+        match asteroid_type {
+            AsteroidType::Small => &self.small,
+            AsteroidType::Medium => &self.medium,
+            AsteroidType::Large => &self.large,
+        }
+    }
+}
+
+impl AssetCollection for AsteroidHandles {
+    fn load(asset_server: &AssetServer) -> Self {
+        let load_variants = |paths: &[&str]| -> Vec<Handle<Aseprite>> {
+            paths.iter().map(|path| asset_server.load(*path)).collect()
         };
 
-        EnemyBundle::new(entity_type, initial_velocity, sprite, spawn_point)
+        Self {
+            small: load_variants(&consts::ASTEROID_ASEPRITES_SMALL),
+            medium: load_variants(&consts::ASTEROID_ASEPRITES_MEDIUM),
+            large: load_variants(&consts::ASTEROID_ASEPRITES_LARGE),
+        }
+    }
+
+    fn handles(&self) -> Vec<HandleUntyped> {
+        self.small
+            .iter()
+            .chain(self.medium.iter())
+            .chain(self.large.iter())
+            .map(|handle| handle.clone_weak_untyped())
+            .collect()
     }
 }
 
+// Asteroid that has been destroyed and is playing its break apart animation
+#[derive(Component, Debug)]
+#[component(storage = "SparseSet")]
+pub struct AsteroidBreaking(Timer);
+
+impl AsteroidBreaking {
+    pub fn new() -> Self {
+        Self(Timer::from_seconds(
+            consts::ASTEROID_BREAK_ANIMATION_TIME,
+            TimerMode::Once,
+        ))
+    }
+}
+
+// Axis aligned hitbox used for all collision checks
 #[derive(Component, Clone, Copy, Debug)]
+pub struct Collider {
+    pub size: Vec2,
+}
+
+impl Collider {
+    pub fn new(size: Vec2) -> Self {
+        Self { size }
+    }
+
+    pub fn scaled_size(&self, tf: &Transform) -> Vec2 {
+        self.size * tf.scale.xy()
+    }
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Copy, Debug)]
 pub enum EntityType {
     Spaceship,
     Projectile,
-    ChargedShot,
     Asteroid(Asteroid),
 }
 
 #[derive(Component, Debug)]
 pub struct Projectile;
 
-#[derive(Component, Debug)]
+// Health taken from an enemy by a single hit
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Damage(pub u32);
+
+#[derive(Component, Serialize, Deserialize, Clone, Copy, Debug)]
 pub enum ProjectileSource {
-    FromSpaceship,
+    FromSpaceship(PlayerId),
     // FromEnemy,
 }
 
@@ -80,6 +215,8 @@ pub struct ProjectileBundle {
     velocity: Velocity,
     movable: Movable,
     source: ProjectileSource,
+    damage: Damage,
+    collider: Collider,
     #[bundle()]
     sprite: SpriteBundle,
 }
@@ -90,7 +227,9 @@ impl ProjectileBundle {
         velocity: Velocity,
         spawn_point: Vec3,
         texture: Handle<Image>,
+        collider: Collider,
         source: ProjectileSource,
+        damage: Damage,
     ) -> Self {
         Self {
             projectile: Projectile,
@@ -98,6 +237,8 @@ impl ProjectileBundle {
             velocity,
             movable: Movable::new(true),
             source,
+            damage,
+            collider,
             sprite: SpriteBundle {
                 texture,
                 transform: Transform::from_translation(spawn_point),
@@ -105,82 +246,183 @@ impl ProjectileBundle {
             },
         }
     }
+
+    // Tinted, turned towards where it flies and scaled, used by the weapons
+    pub fn with_look(mut self, color: Color, rotation: Quat, scale: f32) -> Self {
+        self.sprite.sprite.color = color;
+        self.sprite.transform.rotation = rotation;
+        self.sprite.transform.scale = Vec3::splat(scale);
+        self
+    }
 }
 
 // ===
 
+fn asteroid_break_animation(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut AsteroidBreaking)>,
+) {
+    for (entity, mut breaking) in query.iter_mut() {
+        breaking.0.tick(time.delta());
+
+        if breaking.0.finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+// Asteroids bounce off each other in elastic collisions, heavier ones barely budge
+fn asteroid_collisions(
+    mut commands: Commands,
+    mut query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Velocity,
+            &Collider,
+            &EntityType,
+            Option<&PatternMovement>,
+        ),
+        With<Enemy>,
+    >,
+    config: Res<GameConfig>,
+) {
+    let mut combinations = query.iter_combinations_mut();
+
+    while let Some([first, second]) = combinations.fetch_next() {
+        let (entity_a, mut tf_a, mut velocity_a, collider_a, type_a, movement_a) = first;
+        let (entity_b, mut tf_b, mut velocity_b, collider_b, type_b, movement_b) = second;
+
+        let (mass_a, mass_b) = match (type_a, type_b) {
+            (EntityType::Asteroid(asteroid_a), EntityType::Asteroid(asteroid_b)) => (
+                config.asteroids.get(asteroid_a.asteroid_type).mass,
+                config.asteroids.get(asteroid_b.asteroid_type).mass,
+            ),
+            _ => continue,
+        };
+
+        // Escorts don't bump into the enemy they circle or into each other
+        let anchor_a = movement_a.and_then(PatternMovement::anchor);
+        let anchor_b = movement_b.and_then(PatternMovement::anchor);
+        if anchor_a == Some(entity_b)
+            || anchor_b == Some(entity_a)
+            || (anchor_a.is_some() && anchor_a == anchor_b)
+        {
+            continue;
+        }
+
+        let radius_a = collider_a.scaled_size(&tf_a).min_element() / 2.0;
+        let radius_b = collider_b.scaled_size(&tf_b).min_element() / 2.0;
+        let offset = (tf_b.translation - tf_a.translation).truncate();
+        let overlap = radius_a + radius_b - offset.length();
+
+        if overlap <= 0.0 {
+            continue;
+        }
+
+        let normal = offset.try_normalize().unwrap_or(Vec2::X);
+        let total_mass = mass_a + mass_b;
+
+        // Pushed apart so they don't get stuck in each other
+        tf_a.translation -= (normal * overlap * mass_b / total_mass).extend(0.0);
+        tf_b.translation += (normal * overlap * mass_a / total_mass).extend(0.0);
+
+        let v_a = Vec2::new(velocity_a.x, velocity_a.y);
+        let v_b = Vec2::new(velocity_b.x, velocity_b.y);
+        let approach_speed = (v_a - v_b).dot(normal);
+
+        if approach_speed <= 0.0 {
+            continue;
+        }
+
+        let impulse = 2.0 * approach_speed / total_mass;
+        let v_a = v_a - normal * impulse * mass_b;
+        let v_b = v_b + normal * impulse * mass_a;
+
+        *velocity_a = Velocity::new(v_a.x, v_a.y);
+        *velocity_b = Velocity::new(v_b.x, v_b.y);
+
+        // Knocked off asteroids drift freely from now on
+        for (entity, movement) in [(entity_a, movement_a), (entity_b, movement_b)] {
+            if movement.is_some() {
+                commands.entity(entity).remove::<PatternMovement>();
+            }
+        }
+    }
+}
+
 fn projectile_hit_detection(
     mut ev_despawn: EventWriter<DespawnEntity>,
-    mut ev_add_score: EventWriter<AddScore>,
-    mut ev_split_asteroid: EventWriter<SplitAsteroid>,
+    mut ev_enemy_hit: EventWriter<EnemyIsHit>,
     entity_query: Query<
-        (Entity, &Transform, &Sprite, &EntityType, &Velocity),
+        (Entity, &Transform, &Collider, &EntityType),
         (
             With<EntityType>,
             Without<Projectile>,
             Without<Invulnerability>,
         ),
     >,
-    projectile_query: Query<(Entity, &Transform, &ProjectileSource, &EntityType), With<Projectile>>,
-    player_asset_dimensions: Res<PlayerAssetDimensions>,
+    projectile_query: Query<
+        (
+            Entity,
+            &Transform,
+            &Collider,
+            &ProjectileSource,
+            &EntityType,
+            &Damage,
+            &Velocity,
+            Option<&ChargedShot>,
+        ),
+        With<Projectile>,
+    >,
 ) {
-    let mut processed_entities: HashSet<Entity> = HashSet::new();
+    let mut processed_projectiles: HashSet<Entity> = HashSet::new();
 
-    for (entity, entity_tf, entity_sprite, entity_type, velocity) in entity_query.iter() {
-        if processed_entities.contains(&entity) {
-            continue;
-        }
-
-        for (projectile, projectile_tf, projectile_source, projectile_type) in
-            projectile_query.iter()
+    for (entity, entity_tf, entity_collider, entity_type) in entity_query.iter() {
+        for (
+            projectile,
+            projectile_tf,
+            projectile_collider,
+            projectile_source,
+            projectile_type,
+            damage,
+            projectile_velocity,
+            charged,
+        ) in projectile_query.iter()
         {
             if matches!(entity_type, EntityType::Spaceship)
-                && matches!(projectile_source, ProjectileSource::FromSpaceship)
+                && matches!(projectile_source, ProjectileSource::FromSpaceship(_))
             {
                 continue;
             }
 
-            if processed_entities.contains(&entity) || processed_entities.contains(&projectile) {
+            if processed_projectiles.contains(&projectile) {
                 continue;
             }
 
-            let entity_size = {
-                match entity_sprite.custom_size {
-                    Some(size) => size * projectile_tf.scale.xy(),
-                    None => player_asset_dimensions.spaceship,
-                }
-            };
-
             let collision = collide(
                 projectile_tf.translation,
-                player_asset_dimensions.projectile,
+                projectile_collider.scaled_size(projectile_tf),
                 entity_tf.translation,
-                entity_size,
+                entity_collider.scaled_size(entity_tf),
             );
 
             if collision.is_some() {
-                processed_entities.insert(entity);
-                ev_despawn.send(DespawnEntity {
+                // Health of the enemy is handled together with every other weapon hit
+                let ProjectileSource::FromSpaceship(player) = projectile_source;
+                ev_enemy_hit.send(EnemyIsHit {
                     entity,
-                    entity_type: *entity_type,
+                    damage: damage.0,
+                    player: *player,
+                    source: projectile,
+                    charged: charged.is_some(),
+                    impact: projectile_tf.translation,
+                    direction: Vec2::new(projectile_velocity.x, projectile_velocity.y)
+                        .normalize_or_zero(),
                 });
 
-                if matches!(projectile_source, ProjectileSource::FromSpaceship) {
-                    ev_add_score.send(AddScore(AddScoreType::EnemyDestroyed(*entity_type)));
-                }
-
-                if let EntityType::Asteroid(asteroid) = entity_type {
-                    if !matches!(asteroid.asteroid_type, AsteroidType::Small) {
-                        ev_split_asteroid.send(SplitAsteroid::new(
-                            entity_tf.translation,
-                            entity_size,
-                            *velocity,
-                            *asteroid,
-                        ));
-                    }
-                }
-
-                processed_entities.insert(projectile);
+                processed_projectiles.insert(projectile);
                 ev_despawn.send(DespawnEntity {
                     entity: projectile,
                     entity_type: *projectile_type,
@@ -0,0 +1,451 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    assets::{AddAssetCollection, AssetCollection},
+    common::{AsteroidType, EntityType},
+    consts,
+    events::WaveEnded,
+    movement::{MovementPattern, Velocity},
+    player::{Modifier, Stat, StatModifier},
+    GameState,
+};
+
+pub struct ConfigPlugin;
+
+impl Plugin for ConfigPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<GameConfig>()
+            .init_asset_loader::<GameConfigLoader>()
+            .register_type::<GameConfig>()
+            .init_resource::<GameConfig>()
+            .add_asset_collection::<GameConfigHandle>(GameState::LoadingMenu)
+            .add_systems(
+                PostUpdate,
+                (update_game_config, apply_pending_game_config).chain(),
+            );
+    }
+}
+
+// ===
+
+// Balance values that designers can tweak while the game runs,
+// defaults are used for anything missing from the config file
+#[derive(Resource, Reflect, Deserialize, TypeUuid, Clone, Debug, Default)]
+#[uuid = "1f6a3c9e-5d2b-4b8e-9c61-7a0e4f2d8b53"]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct GameConfig {
+    pub player: PlayerConfig,
+    pub lives: LivesConfig,
+    pub score: ScoreConfig,
+    pub stage: StageConfig,
+    pub shop: ShopConfig,
+    pub profile: ProfileConfig,
+    pub asteroids: AsteroidsConfig,
+}
+
+#[derive(Reflect, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct PlayerConfig {
+    pub invulnerability_time: f32,
+    pub invulnerability_animation_time: f32,
+    pub revive_time: f32,
+    pub revive_distance: f32,
+    // Seconds a downed spaceship waits for a revive before it loses a life
+    pub bleed_out_time: f32,
+}
+
+impl Default for PlayerConfig {
+    fn default() -> Self {
+        Self {
+            invulnerability_time: 3.0,
+            invulnerability_animation_time: 0.2,
+            revive_time: 3.0,
+            revive_distance: 50.0,
+            bleed_out_time: 8.0,
+        }
+    }
+}
+
+#[derive(Reflect, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct LivesConfig {
+    // Lives of every player, including the one being played
+    pub lives: u32,
+    // Score needed for each extra life, in ascending order
+    pub extra_life_scores: Vec<u32>,
+    pub respawn_time: f32,
+    pub continue_time: f32,
+}
+
+impl Default for LivesConfig {
+    fn default() -> Self {
+        Self {
+            lives: 3,
+            extra_life_scores: vec![100, 250, 500, 1000],
+            respawn_time: 1.5,
+            continue_time: 10.0,
+        }
+    }
+}
+
+#[derive(Reflect, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ScoreConfig {
+    pub asteroid_small: u32,
+    pub asteroid_medium: u32,
+    pub asteroid_large: u32,
+    pub combo_window: f32,
+    pub combo_step: u32,
+    pub max_multiplier: u32,
+    pub multi_kill_bonus: u32,
+    // Points for every second a wave is cleared before its par time
+    pub wave_time_bonus: u32,
+    // Seconds after the wave length to clear the remaining enemies
+    pub wave_par_time: f32,
+    // Points for a wave with perfect accuracy, less accurate waves get a share of them
+    pub wave_accuracy_bonus: u32,
+    pub wave_no_damage_bonus: u32,
+}
+
+impl Default for ScoreConfig {
+    fn default() -> Self {
+        Self {
+            asteroid_small: 3,
+            asteroid_medium: 2,
+            asteroid_large: 1,
+            combo_window: 2.0,
+            combo_step: 5,
+            max_multiplier: 5,
+            multi_kill_bonus: 2,
+            wave_time_bonus: 1,
+            wave_par_time: 10.0,
+            wave_accuracy_bonus: 15,
+            wave_no_damage_bonus: 20,
+        }
+    }
+}
+
+impl ScoreConfig {
+    // Base points for destroying an entity, before the combo multiplier,
+    // new enemy types get their own point values here
+    pub fn points(&self, entity_type: &EntityType) -> u32 {
+        match entity_type {
+            EntityType::Asteroid(asteroid) => match asteroid.asteroid_type {
+                AsteroidType::Small => self.asteroid_small,
+                AsteroidType::Medium => self.asteroid_medium,
+                AsteroidType::Large => self.asteroid_large,
+            },
+            EntityType::Spaceship | EntityType::Projectile => 0,
+        }
+    }
+}
+
+#[derive(Reflect, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct AsteroidConfig {
+    // Relative mass, used when asteroids bump into each other or split
+    pub mass: f32,
+    pub health: u32,
+    // Downward speed the asteroid spawns with
+    pub speed: f32,
+    // Hitbox of every sprite variant, in the order of the variants
+    pub colliders: Vec<[f32; 2]>,
+}
+
+impl AsteroidConfig {
+    pub fn collider(&self, variant: usize) -> Option<Vec2> {
+        self.colliders.get(variant).copied().map(Vec2::from_array)
+    }
+
+    pub fn initial_velocity(&self) -> Velocity {
+        Velocity::new(0.0, -self.speed)
+    }
+}
+
+#[derive(Reflect, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct AsteroidsConfig {
+    pub small: AsteroidConfig,
+    pub medium: AsteroidConfig,
+    pub large: AsteroidConfig,
+}
+
+impl Default for AsteroidsConfig {
+    fn default() -> Self {
+        let asteroid =
+            |mass: f32, health: u32, speed: f32, colliders: [[f32; 2]; 3]| AsteroidConfig {
+                mass,
+                health,
+                speed,
+                colliders: colliders.to_vec(),
+            };
+
+        Self {
+            small: asteroid(1.0, 1, 300.0, [[14.0, 16.0], [15.0, 15.0], [16.0, 14.0]]),
+            medium: asteroid(2.0, 1, 200.0, [[35.0, 29.0], [33.0, 34.0], [35.0, 36.0]]),
+            large: asteroid(4.0, 2, 100.0, [[57.0, 57.0], [58.0, 61.0], [58.0, 56.0]]),
+        }
+    }
+}
+
+impl AsteroidsConfig {
+    pub fn get(&self, asteroid_type: AsteroidType) -> &AsteroidConfig {
+        match asteroid_type {
+            AsteroidType::Small => &self.small,
+            AsteroidType::Medium => &self.medium,
+            AsteroidType::Large => &self.large,
+        }
+    }
+}
+
+#[derive(Reflect, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct StageConfig {
+    pub init_cooldown: f32,
+    pub cooldown: f32,
+    pub length: f32,
+    pub patterns: Vec<WavePattern>,
+}
+
+impl Default for StageConfig {
+    fn default() -> Self {
+        Self {
+            init_cooldown: 3.0,
+            cooldown: 5.0,
+            length: 30.0,
+            patterns: vec![],
+        }
+    }
+}
+
+// Small asteroids circling the spawned enemy
+#[derive(Reflect, Serialize, Deserialize, Clone, Debug)]
+pub struct Escort {
+    pub count: u32,
+    pub radius: f32,
+    pub angular_speed: f32,
+}
+
+// Movement pattern given to a share of the enemies, starting with the wave
+#[derive(Reflect, Serialize, Deserialize, Clone, Debug)]
+pub struct WavePattern {
+    pub from_wave: u32,
+    pub chance: f64,
+    pub pattern: Option<MovementPattern>,
+    pub escort: Option<Escort>,
+}
+
+// Upgrade that can be offered in the shop between waves
+#[derive(Reflect, Deserialize, Clone, Debug)]
+pub struct UpgradeConfig {
+    pub id: String,
+    // Keys of the name and the description in the string tables
+    pub name: String,
+    pub description: String,
+    pub price: u32,
+    // Price rises by this much every time the upgrade is bought
+    pub price_step: u32,
+    pub modifiers: Vec<StatModifier>,
+}
+
+impl UpgradeConfig {
+    fn new(id: &str, name: &str, description: &str, price: u32, modifier: StatModifier) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            description: description.to_string(),
+            price,
+            price_step: price / 2,
+            modifiers: vec![modifier],
+        }
+    }
+}
+
+#[derive(Reflect, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ShopConfig {
+    // Upgrades offered after every wave, picked at random
+    pub offers: usize,
+    pub upgrades: Vec<UpgradeConfig>,
+}
+
+impl Default for ShopConfig {
+    fn default() -> Self {
+        Self {
+            offers: 3,
+            upgrades: vec![
+                UpgradeConfig::new(
+                    "max_health",
+                    "upgrade-max-health",
+                    "upgrade-max-health-description",
+                    30,
+                    StatModifier {
+                        stat: Stat::MaxHealth,
+                        modifier: Modifier::Add(1.0),
+                    },
+                ),
+                UpgradeConfig::new(
+                    "fire_rate",
+                    "upgrade-fire-rate",
+                    "upgrade-fire-rate-description",
+                    20,
+                    StatModifier {
+                        stat: Stat::FireCooldown,
+                        modifier: Modifier::Multiply(0.85),
+                    },
+                ),
+                UpgradeConfig::new(
+                    "dash_cooldown",
+                    "upgrade-dash-cooldown",
+                    "upgrade-dash-cooldown-description",
+                    15,
+                    StatModifier {
+                        stat: Stat::DashCooldown,
+                        modifier: Modifier::Multiply(0.8),
+                    },
+                ),
+                UpgradeConfig::new(
+                    "charge_size",
+                    "upgrade-charge-size",
+                    "upgrade-charge-size-description",
+                    20,
+                    StatModifier {
+                        stat: Stat::ChargeSize,
+                        modifier: Modifier::Multiply(1.25),
+                    },
+                ),
+            ],
+        }
+    }
+}
+
+// Shop upgrade bought in the hangar, every run starts with it
+#[derive(Reflect, Deserialize, Clone, Debug)]
+pub struct StartingUpgrade {
+    // Id of the upgrade in the shop config
+    pub upgrade: String,
+    pub price: u32,
+}
+
+#[derive(Reflect, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ProfileConfig {
+    // Credits earned at the end of a run
+    pub credits_per_score: f32,
+    pub credits_per_wave: u32,
+    pub starting_upgrades: Vec<StartingUpgrade>,
+}
+
+impl Default for ProfileConfig {
+    fn default() -> Self {
+        let starting_upgrade = |upgrade: &str, price: u32| StartingUpgrade {
+            upgrade: upgrade.to_string(),
+            price,
+        };
+
+        Self {
+            credits_per_score: 0.1,
+            credits_per_wave: 10,
+            starting_upgrades: vec![
+                starting_upgrade("max_health", 200),
+                starting_upgrade("fire_rate", 150),
+                starting_upgrade("dash_cooldown", 100),
+                starting_upgrade("charge_size", 120),
+            ],
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct GameConfigHandle(Handle<GameConfig>);
+
+impl AssetCollection for GameConfigHandle {
+    fn load(asset_server: &AssetServer) -> Self {
+        GameConfigHandle(asset_server.load(consts::GAME_CONFIG))
+    }
+
+    fn handles(&self) -> Vec<HandleUntyped> {
+        vec![self.0.clone_weak_untyped()]
+    }
+}
+
+// Config that was reloaded mid run, waits for the current wave to end
+#[derive(Resource)]
+struct PendingGameConfig(GameConfig);
+
+#[derive(Default)]
+struct GameConfigLoader;
+
+impl AssetLoader for GameConfigLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let config = ron::de::from_bytes::<GameConfig>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(config));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["config.ron"]
+    }
+}
+
+// ===
+
+fn update_game_config(
+    mut commands: Commands,
+    mut ev_config: EventReader<AssetEvent<GameConfig>>,
+    configs: Res<Assets<GameConfig>>,
+    config_handle: Option<Res<GameConfigHandle>>,
+    state: Res<State<GameState>>,
+) {
+    if let Some(config_handle) = config_handle {
+        for config_ev in ev_config.iter() {
+            match config_ev {
+                AssetEvent::Created { handle } if *handle == config_handle.0 => {
+                    if let Some(config) = configs.get(handle) {
+                        commands.insert_resource(config.clone());
+                    }
+                }
+                AssetEvent::Modified { handle } if *handle == config_handle.0 => {
+                    if let Some(config) = configs.get(handle) {
+                        if matches!(state.get(), GameState::Gameplay) {
+                            commands.insert_resource(PendingGameConfig(config.clone()));
+                        } else {
+                            commands.insert_resource(config.clone());
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+fn apply_pending_game_config(
+    mut commands: Commands,
+    mut ev_wave_ended: EventReader<WaveEnded>,
+    pending_config: Option<Res<PendingGameConfig>>,
+    state: Res<State<GameState>>,
+) {
+    let wave_ended = ev_wave_ended.iter().count() > 0;
+
+    if let Some(pending_config) = pending_config {
+        if wave_ended || !matches!(state.get(), GameState::Gameplay) {
+            info!("Applying reloaded game config");
+            commands.insert_resource(pending_config.0.clone());
+            commands.remove_resource::<PendingGameConfig>();
+        }
+    }
+}
@@ -1,53 +1,148 @@
+use bevy::prelude::Vec2;
+
 // --- Gameplay screen ---
 
 pub const WINDOW_HEIGHT: f32 = 240.0 * 3.0;
 pub const WINDOW_WIDTH: f32 = 256.0 * 3.0;
 pub const WINDOW_RATIO: f32 = WINDOW_WIDTH / WINDOW_HEIGHT;
 
+// Distance outside the screen edges where entities spawn and get despawned
 pub const SPAWN_MARGIN: f32 = 100.0;
 pub const DESPAWN_MARGIN: f32 = 200.0;
 
-// --- Player ---
-
-pub const PLAYER_MAX_HEALTH: u32 = 3;
-
-pub const PLAYER_MOVEMENT_SPEED: f32 = 5.;
-pub const PLAYER_PROJECTILE_SPEED: f32 = 1000.;
-pub const PLAYER_DASH_SPEED: f32 = 300.0;
+pub const ACHIEVEMENT_TOAST_TIME: f32 = 3.0;
+
+pub const GAME_CONFIG: &str = "config/game.config.ron";
+pub const ACHIEVEMENTS: &str = "config/game.achievements.ron";
+pub const SHIPS: &str = "config/game.ships.ron";
+// String tables, one file for every language
+pub const LOCALES: [&str; 3] = [
+    "locales/en.locale.ron",
+    "locales/de.locale.ron",
+    "locales/es.locale.ron",
+];
+// Language used before one is picked and for strings missing from a table
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+// --- Saves ---
+
+pub const SAVE_DIR: &str = "saves";
+pub const RUN_LOG_FILE: &str = "run_log.jsonl";
+pub const ACHIEVEMENTS_SAVE: &str = "achievements.ron";
+pub const RUN_SAVE: &str = "run.ron";
+pub const PROFILE_SAVE: &str = "profile.ron";
+pub const SETTINGS_SAVE: &str = "settings.ron";
+
+// --- Menus ---
+
+// Pixels of the highlight around the focused widget
+pub const MENU_FOCUS_BORDER: f32 = 3.0;
+
+pub const UI_SCALE_MIN: f32 = 0.75;
+pub const UI_SCALE_MAX: f32 = 1.5;
+pub const UI_SCALE_STEP: f32 = 0.05;
+
+// --- Transitions ---
+
+pub const TRANSITION_IRIS_RESOLUTION: u32 = 128;
+// Size of the open iris as a share of the longer screen side, in percent, large
+// enough for the hole to reach past the corners
+pub const TRANSITION_IRIS_SIZE: f32 = 150.0;
+
+// --- Title screen ---
+
+pub const TITLE_SPRITE_SPACESHIP: &str = "sprites/spaceship.png";
+pub const TITLE_ASEPRITE_PROPULSION: &str = "aseprites/spaceship-propulsion.aseprite";
+
+// Seconds without any input on the title screen before the demo starts
+pub const TITLE_ATTRACT_DELAY: f32 = 20.0;
+// Average seconds between drifting asteroids, the demo sends more of them to shoot at
+pub const TITLE_ASTEROID_INTERVAL: f32 = 2.5;
+pub const TITLE_DEMO_ASTEROID_INTERVAL: f32 = 0.8;
+// Drift speed as a share of the asteroid speed in a run
+pub const TITLE_ASTEROID_SPEED: f32 = 0.3;
+pub const TITLE_ASTEROID_SIDEWAYS_SPEED: f32 = 20.0;
+// Pixels and swings per second of the idle bobbing
+pub const TITLE_SHIP_BOB: f32 = 6.0;
+pub const TITLE_SHIP_BOB_SPEED: f32 = 0.6;
+pub const TITLE_LOGO_BOB: f32 = 8.0;
+pub const TITLE_LOGO_BOB_SPEED: f32 = 0.3;
+// Pixels per second the demo autopilot flies sideways, it slows down when closing in
+pub const TITLE_SHIP_SPEED: f32 = 300.0;
+pub const TITLE_SHIP_EASING: f32 = 5.0;
+// Pixels the autopilot may be off an asteroid and still shoot at it
+pub const TITLE_SHIP_AIM: f32 = 12.0;
+pub const TITLE_SHIP_SHOOT_COOLDOWN: f32 = 0.25;
+pub const TITLE_PROJECTILE_SPEED: f32 = 800.0;
+
+// --- Background ---
+
+pub const STARFIELD_Z: f32 = -10.0;
+pub const STARFIELD_STARS: usize = 300;
+// Pixels per second of the nearest star layer
+pub const STARFIELD_SCROLL_SPEED: f32 = 60.0;
+// Extra scroll speed right after a dash, as a share of the base speed
+pub const STARFIELD_DASH_BOOST: f32 = 3.0;
+// Scroll speed between waves, as a share of the base speed
+pub const STARFIELD_WAVE_BOOST: f32 = 2.5;
+pub const STARFIELD_SPEED_EASING: f32 = 3.0;
+// Average seconds between nebulas and planets
+pub const STARFIELD_OBJECT_INTERVAL: f32 = 20.0;
+// Starfield shown outside of runs
+pub const MENU_STARFIELD_SEED: u64 = 0x5741_5253;
 
-pub const PLAYER_DASH_TIME_LEN: f32 = 0.5;
-
-pub const PLAYER_CHARGE_SHOT_CHARGING_TIME: f32 = 0.2;
+// --- Player ---
 
-pub const PLAYER_FIRING_COOLDOWN: f32 = 0.1;
-pub const PLAYER_CHARGE_SHOT_COOLDOWN: f32 = 1.0;
-pub const PLAYER_DASH_COOLDOWN: f32 = 0.3;
+pub const MAX_PLAYERS: usize = 2;
 
-pub const PLAYER_CHARGE_SHOT_WIDTH: f32 = 6.0;
 pub const PLAYER_CHARGE_SHOT_HEIGHT: f32 = WINDOW_HEIGHT;
-
-pub const PLAYER_INVULNERABILITY_TIME: f32 = 3.0;
-pub const PLAYER_INVULNERABILITY_ANIMATION_TIME: f32 = 0.2;
+pub const PLAYER_BEAM_FADE_TIME: f32 = 0.1;
+pub const PLAYER_CHARGE_METER_SIZE: Vec2 = Vec2::new(40.0, 4.0);
 
 pub const PLAYER_Z: f32 = 10.0;
 pub const PLAYER_PROPULSION_Z: f32 = 11.0;
 pub const PLAYER_PROJECTILE_Z: f32 = 1.0;
 pub const PLAYER_CHARGE_SHOT_Z: f32 = 2.0;
 
-pub const PLAYER_SPRITE_SPACESHIP: &str = "sprites/spaceship.png";
 pub const PLAYER_SPRITE_PROJECTILE: &str = "sprites/spaceship-projectile.png";
-pub const PLAYER_ASEPRITE_PROPULSION: &str = "aseprites/spaceship-propulsion.aseprite";
-
-// --- Enemy ---
-
-pub const ENEMY_Z: f32 = 0.0;
 
 // --- Score ---
 
-pub const SCORE_ADD_ASTEROID: u32 = 1;
+pub const SCORE_POPUP_Z: f32 = 20.0;
+pub const SCORE_POPUP_TIME: f32 = 0.8;
+pub const SCORE_POPUP_SPEED: f32 = 40.0;
 
-// --- Stage ---
+// --- Enemy ---
+
+pub const ENEMY_Z: f32 = 0.0;
 
-pub const STAGE_INIT_COOLDOWN: f32 = 3.0;
-pub const STAGE_COOLDOWN: f32 = 5.0;
-pub const STAGE_LENGTH: f32 = 30.0;
+// Seconds the wave banner stays on screen after a wave starts
+pub const WAVE_BANNER_TIME: f32 = 2.0;
+
+// --- Asteroid ---
+
+pub const ASTEROID_ASEPRITES_SMALL: [&str; 3] = [
+    "aseprites/asteroid-small-1.aseprite",
+    "aseprites/asteroid-small-2.aseprite",
+    "aseprites/asteroid-small-3.aseprite",
+];
+pub const ASTEROID_ASEPRITES_MEDIUM: [&str; 3] = [
+    "aseprites/asteroid-medium-1.aseprite",
+    "aseprites/asteroid-medium-2.aseprite",
+    "aseprites/asteroid-medium-3.aseprite",
+];
+pub const ASTEROID_ASEPRITES_LARGE: [&str; 3] = [
+    "aseprites/asteroid-large-1.aseprite",
+    "aseprites/asteroid-large-2.aseprite",
+    "aseprites/asteroid-large-3.aseprite",
+];
+
+// Radians per second
+pub const ASTEROID_MAX_SPIN: f32 = 2.0;
+
+// Momentum every hit passes on to an asteroid, as mass times pixels per second
+pub const ASTEROID_HIT_IMPULSE: f32 = 150.0;
+// Speed at which fragments of a split asteroid fly apart
+pub const ASTEROID_SPLIT_SPEED: f32 = 80.0;
+
+pub const ASTEROID_BREAK_ANIMATION_TIME: f32 = 0.4;
@@ -0,0 +1,249 @@
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::ToggleActions;
+
+use crate::{
+    camera::WorldCursor,
+    common::{Asteroid, AsteroidType, EntityType},
+    config::GameConfig,
+    consts,
+    enemy::GameplayStage,
+    events::SpawnEnemy,
+    player::{GodMode, Spaceship, SpaceshipAction},
+    Stats,
+};
+
+const CONSOLE_HISTORY_LEN: usize = 6;
+
+#[derive(Resource, Default)]
+pub struct DevConsole {
+    open: bool,
+    input: String,
+    history: Vec<String>,
+}
+
+impl DevConsole {
+    fn log(&mut self, line: String) {
+        self.history.push(line);
+
+        if self.history.len() > CONSOLE_HISTORY_LEN {
+            self.history.remove(0);
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct DevConsoleUi;
+
+#[derive(Component)]
+pub struct DevConsoleText;
+
+#[derive(Event, Debug)]
+pub enum DevCommand {
+    GodMode,
+    SkipWave,
+    SpawnAsteroid(AsteroidType),
+    SetScore(u32),
+}
+
+impl DevCommand {
+    fn parse(input: &str) -> Result<DevCommand, String> {
+        let mut args = input.split_whitespace();
+
+        match (args.next(), args.next()) {
+            (Some("god"), None) => Ok(DevCommand::GodMode),
+            (Some("skip"), None) => Ok(DevCommand::SkipWave),
+            (Some("spawn"), Some(size)) => match size {
+                "small" => Ok(DevCommand::SpawnAsteroid(AsteroidType::Small)),
+                "medium" => Ok(DevCommand::SpawnAsteroid(AsteroidType::Medium)),
+                "large" => Ok(DevCommand::SpawnAsteroid(AsteroidType::Large)),
+                _ => Err(format!("Unknown asteroid size: {}", size)),
+            },
+            (Some("score"), Some(score)) => score
+                .parse()
+                .map(DevCommand::SetScore)
+                .map_err(|_| format!("Invalid score: {}", score)),
+            _ => Err(format!(
+                "Unknown command: {} (god, skip, spawn <small|medium|large>, score <n>)",
+                input
+            )),
+        }
+    }
+}
+
+// ===
+
+pub fn setup_dev_console(mut commands: Commands) {
+    commands
+        .spawn((
+            DevConsoleUi,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(0.0),
+                    padding: UiRect::all(Val::Px(5.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.8).into(),
+                visibility: Visibility::Hidden,
+                z_index: ZIndex::Global(100),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                DevConsoleText,
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 16.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+            ));
+        });
+}
+
+pub fn toggle_dev_console(
+    kbd: Res<Input<KeyCode>>,
+    mut console: ResMut<DevConsole>,
+    mut toggle_actions: ResMut<ToggleActions<SpaceshipAction>>,
+    mut ui_query: Query<&mut Visibility, With<DevConsoleUi>>,
+) {
+    if kbd.just_pressed(KeyCode::Grave) {
+        console.open = !console.open;
+        console.input.clear();
+
+        // Typing into the console shouldn't move the spaceship
+        toggle_actions.enabled = !console.open;
+
+        if let Ok(mut visibility) = ui_query.get_single_mut() {
+            *visibility = match console.open {
+                true => Visibility::Visible,
+                false => Visibility::Hidden,
+            };
+        }
+    }
+}
+
+pub fn dev_console_input(
+    kbd: Res<Input<KeyCode>>,
+    mut ev_char: EventReader<ReceivedCharacter>,
+    mut ev_command: EventWriter<DevCommand>,
+    mut console: ResMut<DevConsole>,
+) {
+    if !console.open {
+        ev_char.clear();
+        return;
+    }
+
+    for char_ev in ev_char.iter() {
+        if !char_ev.char.is_control() && char_ev.char != '`' {
+            console.input.push(char_ev.char);
+        }
+    }
+
+    if kbd.just_pressed(KeyCode::Back) {
+        console.input.pop();
+    }
+
+    if kbd.just_pressed(KeyCode::Return) {
+        let input = std::mem::take(&mut console.input);
+        console.log(format!("> {}", input));
+
+        match DevCommand::parse(&input) {
+            Ok(command) => ev_command.send(command),
+            Err(err) => console.log(err),
+        }
+    }
+}
+
+pub fn update_dev_console_text(
+    console: Res<DevConsole>,
+    mut ui_query: Query<&mut Text, With<DevConsoleText>>,
+) {
+    if console.is_changed() {
+        if let Ok(mut ui_element) = ui_query.get_single_mut() {
+            let mut lines = console.history.clone();
+            lines.push(format!("> {}_", console.input));
+
+            ui_element.sections[0].value = lines.join("\n");
+        }
+    }
+}
+
+pub fn run_dev_commands(
+    mut commands: Commands,
+    mut ev_command: EventReader<DevCommand>,
+    mut console: ResMut<DevConsole>,
+    mut stats: ResMut<Stats>,
+    spaceship_query: Query<(Entity, Option<&GodMode>), With<Spaceship>>,
+    mut stage_query: Query<&mut GameplayStage>,
+) {
+    for command in ev_command.iter() {
+        match command {
+            DevCommand::GodMode => {
+                let god_mode = spaceship_query
+                    .iter()
+                    .any(|(_, god_mode)| god_mode.is_some());
+
+                for (entity, _) in spaceship_query.iter() {
+                    match god_mode {
+                        true => commands.entity(entity).remove::<GodMode>(),
+                        false => commands.entity(entity).insert(GodMode),
+                    };
+                }
+
+                match god_mode {
+                    true => console.log("God mode off".to_string()),
+                    false => console.log("God mode on".to_string()),
+                }
+            }
+            DevCommand::SkipWave => {
+                if let Ok(mut stage) = stage_query.get_single_mut() {
+                    stage.skip_wave();
+                    console.log(format!("Skipped wave {}", stage.wave()));
+                }
+            }
+            // Spawned by spawn_dev_asteroids
+            DevCommand::SpawnAsteroid(_) => (),
+            DevCommand::SetScore(score) => {
+                // Credited to the first player so the shares still add up to the total
+                stats.score = *score;
+                stats.player_scores = [0; consts::MAX_PLAYERS];
+                stats.player_scores[0] = *score;
+                console.log(format!("Score set to {}", score));
+            }
+        }
+    }
+}
+
+// Asteroids show up under the mouse cursor
+pub fn spawn_dev_asteroids(
+    mut ev_command: EventReader<DevCommand>,
+    mut ev_spawn: EventWriter<SpawnEnemy>,
+    mut console: ResMut<DevConsole>,
+    cursor: WorldCursor,
+    config: Res<GameConfig>,
+) {
+    for command in ev_command.iter() {
+        let DevCommand::SpawnAsteroid(asteroid_type) = command else {
+            continue;
+        };
+
+        match cursor.position() {
+            Some(spawn_point) => {
+                ev_spawn.send(SpawnEnemy::new(
+                    EntityType::Asteroid(Asteroid {
+                        asteroid_type: *asteroid_type,
+                    }),
+                    config.asteroids.get(*asteroid_type).initial_velocity(),
+                    spawn_point.extend(consts::ENEMY_Z),
+                ));
+                console.log(format!("Spawned {:?} asteroid", asteroid_type));
+            }
+            None => console.log("Cursor is outside of the game".to_string()),
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use bevy::{diagnostic::FrameTimeDiagnosticsPlugin, prelude::*};
+use bevy_inspector_egui::quick::WorldInspectorPlugin;
+use bevy_mod_aseprite::Aseprite;
+
+use crate::{
+    common::{Collider, Projectile},
+    player::{PlayerAssetDimensions, PlayerHandles, Spaceship},
+};
+
+mod console;
+mod overlay;
+
+pub struct DevPlugin;
+
+impl Plugin for DevPlugin {
+    fn build(&self, app: &mut App) {
+        // GameConfig can be tweaked through the inspector while playing
+        app.add_plugins((WorldInspectorPlugin::new(), FrameTimeDiagnosticsPlugin))
+            .init_resource::<overlay::DebugOverlay>()
+            .init_resource::<console::DevConsole>()
+            .add_event::<console::DevCommand>()
+            .add_systems(
+                Startup,
+                (overlay::setup_debug_overlay, console::setup_dev_console),
+            )
+            .add_systems(
+                Update,
+                (
+                    overlay::toggle_debug_overlay,
+                    (
+                        overlay::update_debug_overlay_text,
+                        overlay::draw_colliders,
+                        overlay::draw_spawner_areas,
+                    )
+                        .run_if(overlay::debug_overlay_visible),
+                    (
+                        console::toggle_dev_console,
+                        console::dev_console_input,
+                        (
+                            console::run_dev_commands,
+                            console::spawn_dev_asteroids,
+                            console::update_dev_console_text,
+                        ),
+                    )
+                        .chain(),
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                (reload_player_asset_dimensions, reload_aseprite_atlases),
+            );
+    }
+}
+
+// ===
+
+fn is_modified<T: bevy::asset::Asset>(ev: &AssetEvent<T>, handle: &Handle<T>) -> bool {
+    matches!(ev, AssetEvent::Modified { handle: modified } if modified == handle)
+}
+
+fn reload_player_asset_dimensions(
+    mut ev_image: EventReader<AssetEvent<Image>>,
+    images: Res<Assets<Image>>,
+    player_assets: Option<Res<PlayerHandles>>,
+    player_dims: Option<ResMut<PlayerAssetDimensions>>,
+    mut spaceship_query: Query<(&Handle<Image>, &mut Collider), With<Spaceship>>,
+    mut projectile_query: Query<&mut Collider, (With<Projectile>, Without<Spaceship>)>,
+) {
+    if let (Some(player_assets), Some(mut player_dims)) = (player_assets, player_dims) {
+        for image_ev in ev_image.iter() {
+            // Every ship has its own sprite, the propulsion follows the new size on its own
+            for (spaceship_image, mut collider) in spaceship_query.iter_mut() {
+                if is_modified(image_ev, spaceship_image) {
+                    if let Some(image) = images.get(spaceship_image) {
+                        collider.size = image.size();
+                    }
+                }
+            }
+
+            if is_modified(image_ev, &player_assets.projectile) {
+                if let Some(image) = images.get(&player_assets.projectile) {
+                    player_dims.projectile = image.size();
+
+                    for mut collider in projectile_query.iter_mut() {
+                        collider.size = player_dims.projectile;
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Reloaded aseprites get a new texture atlas, entities still point at the old one
+fn reload_aseprite_atlases(
+    mut ev_aseprite: EventReader<AssetEvent<Aseprite>>,
+    aseprites: Res<Assets<Aseprite>>,
+    mut query: Query<(&Handle<Aseprite>, &mut Handle<TextureAtlas>)>,
+) {
+    for aseprite_ev in ev_aseprite.iter() {
+        if let AssetEvent::Modified { handle } = aseprite_ev {
+            if let Some(aseprite) = aseprites.get(handle) {
+                for (aseprite_handle, mut atlas) in query.iter_mut() {
+                    if aseprite_handle == handle {
+                        *atlas = aseprite.atlas().clone_weak();
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use bevy::{
+    diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    prelude::*,
+};
+
+use crate::{
+    common::Collider,
+    enemy::{EnemyCount, GameplayStage},
+};
+
+#[derive(Resource, Default)]
+pub struct DebugOverlay {
+    pub visible: bool,
+}
+
+#[derive(Component)]
+pub struct DebugOverlayText;
+
+// ===
+
+pub fn debug_overlay_visible(overlay: Res<DebugOverlay>) -> bool {
+    overlay.visible
+}
+
+pub fn setup_debug_overlay(mut commands: Commands) {
+    commands.spawn((
+        DebugOverlayText,
+        TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font_size: 16.0,
+                    color: Color::YELLOW,
+                    ..default()
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(10.0),
+                bottom: Val::Px(10.0),
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(100),
+            ..default()
+        },
+    ));
+}
+
+pub fn toggle_debug_overlay(
+    kbd: Res<Input<KeyCode>>,
+    mut overlay: ResMut<DebugOverlay>,
+    mut ui_query: Query<&mut Visibility, With<DebugOverlayText>>,
+) {
+    if kbd.just_pressed(KeyCode::F3) {
+        overlay.visible = !overlay.visible;
+
+        if let Ok(mut visibility) = ui_query.get_single_mut() {
+            *visibility = match overlay.visible {
+                true => Visibility::Visible,
+                false => Visibility::Hidden,
+            };
+        }
+    }
+}
+
+pub fn update_debug_overlay_text(
+    diagnostics: Res<DiagnosticsStore>,
+    stage_query: Query<(&GameplayStage, &EnemyCount)>,
+    entity_query: Query<Entity>,
+    mut ui_query: Query<&mut Text, With<DebugOverlayText>>,
+) {
+    if let Ok(mut ui_element) = ui_query.get_single_mut() {
+        let fps = diagnostics
+            .get(FrameTimeDiagnosticsPlugin::FPS)
+            .and_then(|fps| fps.smoothed())
+            .unwrap_or(0.0);
+
+        let mut lines = vec![
+            format!("FPS: {:.0}", fps),
+            format!("Entities: {}", entity_query.iter().count()),
+        ];
+
+        if let Ok((stage, enemy_count)) = stage_query.get_single() {
+            lines.push(format!("Asteroids: {}", enemy_count.asteroids));
+            lines.push(format!("Wave: {}", stage.wave()));
+            lines.push(format!("Stage: {}", stage.state_summary()));
+        }
+
+        ui_element.sections[0].value = lines.join("\n");
+    }
+}
+
+// Same rectangles that are passed to the collision checks
+pub fn draw_colliders(mut gizmos: Gizmos, query: Query<(&Transform, &Collider)>) {
+    for (tf, collider) in query.iter() {
+        gizmos.rect_2d(
+            tf.translation.truncate(),
+            0.0,
+            collider.scaled_size(tf),
+            Color::GREEN,
+        );
+    }
+}
+
+pub fn draw_spawner_areas(mut gizmos: Gizmos, query: Query<&GameplayStage>) {
+    if let Ok(stage) = query.get_single() {
+        for (center, size) in stage.spawner_areas() {
+            gizmos.rect_2d(center, 0.0, size, Color::RED);
+        }
+    }
+}
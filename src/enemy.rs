@@ -1,14 +1,23 @@
-use bevy::{math::Vec3Swizzles, prelude::*, sprite::collide_aabb::collide};
-use rand::{thread_rng, Rng};
+use bevy::{ecs::system::SystemParam, prelude::*, sprite::collide_aabb::collide, utils::HashSet};
+use bevy_mod_aseprite::AsepriteBundle;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    common::{Asteroid, AsteroidType, EntityType},
+    common::{Asteroid, AsteroidType, Collider, EntityType},
+    config::{GameConfig, StageConfig, WavePattern},
     consts,
-    events::{DespawnEntity, EventSet, SpaceshipIsHit, SpawnEnemy},
+    events::{
+        AddScore, AddScoreType, BreakAsteroid, DespawnEntity, EventSet, SpaceshipIsHit, SpawnEnemy,
+        WaveEnded,
+    },
     is_playing,
-    movement::{Movable, Velocity},
-    player::{Invulnerability, PlayerAssetDimensions, Point, Spaceship},
-    GameState, WinSize,
+    movement::{AngularVelocity, Movable, Velocity},
+    player::{Downed, Invulnerability, PlayerCount, PlayerId, Point, Spaceship},
+    rng::GameRng,
+    run_save::RunSnapshot,
+    run_stats::RunStats,
+    save, GameState, Stats, WinSize,
 };
 
 pub struct EnemyPlugin;
@@ -22,7 +31,10 @@ impl Plugin for EnemyPlugin {
                     .run_if(is_playing)
                     .in_set(EventSet::CreateEv),
             )
-            .add_systems(PreUpdate, stage_manager.run_if(is_playing));
+            .add_systems(
+                PreUpdate,
+                (stage_manager, end_cleared_wave).chain().run_if(is_playing),
+            );
     }
 }
 
@@ -34,14 +46,18 @@ pub struct Gameplay;
 #[derive(Component, Debug)]
 pub struct Enemy;
 
-#[derive(Debug, Copy, Clone)]
+// Hits the enemy takes before it's destroyed
+#[derive(Component, Debug)]
+pub struct EnemyHealth(pub u32);
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
 struct SpawnerArea {
     center: Point,
     width: f32,
     height: f32,
 }
 
-#[derive(Component, Debug)]
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
 struct EnemySpawner {
     entity_type: EntityType,
     // TODO Each tick a random amount of enemies spawn equaling up to a predetermined total amount
@@ -49,14 +65,16 @@ struct EnemySpawner {
     // tick: Timer,
     spawned: u32,
     spawn_total: u32,
+    #[serde(with = "save::timer_serde")]
     interval: Timer,
     area: SpawnerArea,
+    // Patterns available in the current wave
+    #[serde(default)]
+    patterns: Vec<WavePattern>,
 }
 
 impl EnemySpawner {
-    fn get_enemy_spawn_point(&self) -> Vec3 {
-        let mut rng = thread_rng();
-
+    fn get_enemy_spawn_point(&self, rng: &mut GameRng) -> Vec3 {
         let w_span_left = self.area.center.x - self.area.width / 2.0;
         let w_span_right = self.area.center.x + self.area.width / 2.0;
 
@@ -70,26 +88,37 @@ impl EnemySpawner {
         )
     }
 
-    fn get_enemy_initial_velocity(&self) -> Velocity {
+    fn get_enemy_initial_velocity(&self, config: &GameConfig) -> Velocity {
         if let EntityType::Asteroid(asteroid) = self.entity_type {
-            match asteroid.asteroid_type {
-                AsteroidType::Small => Velocity::new(0.0, -300.0),
-                AsteroidType::Medium => Velocity::new(0.0, -200.0),
-                AsteroidType::Large => Velocity::new(0.0, -100.0),
-            }
+            config
+                .asteroids
+                .get(asteroid.asteroid_type)
+                .initial_velocity()
         } else {
             Velocity { x: 0.0, y: 0.0 }
         }
     }
 
+    // Every spawned enemy rolls for the patterns in order and takes the first one it hits
+    fn pick_pattern(&self, rng: &mut GameRng) -> Option<&WavePattern> {
+        self.patterns
+            .iter()
+            .find(|wave_pattern| rng.gen_bool(wave_pattern.chance.clamp(0.0, 1.0)))
+    }
+
     fn add_spawned_count(&mut self, amount: u32) {
         self.spawned += amount;
     }
 
+    fn is_finished(&self) -> bool {
+        self.spawned >= self.spawn_total
+    }
+
     fn create_spawners(
         wave: &u32,
         stage_type: &StageType,
         win_size: &WinSize,
+        config: &StageConfig,
     ) -> Vec<EnemySpawner> {
         // TODO add variation to different waves
         // Different events - asteroid field, saucer invasion, etc.
@@ -98,7 +127,7 @@ impl EnemySpawner {
 
         if matches!(stage_type, StageType::Normal) {
             let spawn_total = 10 * wave;
-            let interval = consts::STAGE_LENGTH / spawn_total as f32;
+            let interval = config.length / spawn_total as f32;
             let spawner_location = SpawnerArea {
                 center: Point {
                     x: 0.0,
@@ -107,6 +136,12 @@ impl EnemySpawner {
                 width: win_size.w - consts::SPAWN_MARGIN * 2.0,
                 height: 30.0,
             };
+            let patterns: Vec<WavePattern> = config
+                .patterns
+                .iter()
+                .filter(|wave_pattern| wave_pattern.from_wave <= *wave)
+                .cloned()
+                .collect();
 
             vec![
                 EnemySpawner {
@@ -117,6 +152,7 @@ impl EnemySpawner {
                     spawn_total,
                     interval: Timer::from_seconds(interval, TimerMode::Repeating),
                     area: spawner_location,
+                    patterns: patterns.clone(),
                 },
                 EnemySpawner {
                     entity_type: EntityType::Asteroid(Asteroid {
@@ -126,6 +162,7 @@ impl EnemySpawner {
                     spawn_total,
                     interval: Timer::from_seconds(interval, TimerMode::Repeating),
                     area: spawner_location,
+                    patterns: patterns.clone(),
                 },
                 EnemySpawner {
                     entity_type: EntityType::Asteroid(Asteroid {
@@ -135,6 +172,7 @@ impl EnemySpawner {
                     spawn_total,
                     interval: Timer::from_seconds(interval, TimerMode::Repeating),
                     area: spawner_location,
+                    patterns: patterns.clone(),
                 },
             ]
         } else {
@@ -143,20 +181,22 @@ impl EnemySpawner {
     }
 }
 
-#[derive(Component, Debug)]
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
 enum StageState {
     Spawning(Vec<EnemySpawner>),
-    Cooldown(Timer),
+    // Everything is spawned, the wave is cleared once the last enemy is gone
+    Clearing,
+    Cooldown(#[serde(with = "save::timer_serde")] Timer),
 }
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 enum StageType {
     Normal,
     // AsteroidField,
     // SaucerInvasion,
 }
 
-#[derive(Component, Debug)]
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
 struct StageWave {
     wave: u32,
     stage_type: StageType,
@@ -182,10 +222,167 @@ impl StageWave {
     }
 }
 
-#[derive(Component, Debug)]
-struct GameplayStage {
+// Run stats at the start of a wave, the wave tally is counted from them
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default)]
+struct WaveStart {
+    time_secs: f32,
+    shots_fired: u32,
+    hits: u32,
+    damage_taken: u32,
+}
+
+// Run stats the wave start and the wave tally are taken from
+#[derive(SystemParam)]
+struct RunProgress<'w> {
+    stats: Res<'w, Stats>,
+    run_stats: Res<'w, RunStats>,
+}
+
+impl RunProgress<'_> {
+    fn wave_start(&self) -> WaveStart {
+        WaveStart {
+            time_secs: self.stats.watch.elapsed_secs(),
+            shots_fired: self.run_stats.shots_fired,
+            hits: self.run_stats.hits,
+            damage_taken: self.run_stats.damage_taken,
+        }
+    }
+}
+
+// Bonus points for clearing a wave, shown during the intermission
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct WaveTally {
+    pub time_secs: f32,
+    pub time_bonus: u32,
+    pub accuracy: f32,
+    pub accuracy_bonus: u32,
+    pub no_damage_bonus: u32,
+}
+
+impl WaveTally {
+    fn new(start: &WaveStart, stats: &Stats, run_stats: &RunStats, config: &GameConfig) -> Self {
+        let score = &config.score;
+        let time_secs = stats.watch.elapsed_secs() - start.time_secs;
+        let par_time = config.stage.length + score.wave_par_time;
+
+        let shots_fired = run_stats.shots_fired - start.shots_fired;
+        let hits = run_stats.hits - start.hits;
+        let accuracy = match shots_fired {
+            0 => 0.0,
+            _ => (hits as f32 / shots_fired as f32 * 100.0).min(100.0),
+        };
+
+        let no_damage_bonus = match run_stats.damage_taken == start.damage_taken {
+            true => score.wave_no_damage_bonus,
+            false => 0,
+        };
+
+        Self {
+            time_secs,
+            time_bonus: ((par_time - time_secs).max(0.0) * score.wave_time_bonus as f32) as u32,
+            accuracy,
+            accuracy_bonus: (accuracy / 100.0 * score.wave_accuracy_bonus as f32).round() as u32,
+            no_damage_bonus,
+        }
+    }
+
+    pub fn total(&self) -> u32 {
+        self.time_bonus + self.accuracy_bonus + self.no_damage_bonus
+    }
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct GameplayStage {
     wave: StageWave,
     state: StageState,
+    #[serde(default)]
+    wave_start: WaveStart,
+    // Tally of the last cleared wave, shown until the next one starts
+    #[serde(default)]
+    tally: Option<WaveTally>,
+}
+
+impl GameplayStage {
+    pub fn wave(&self) -> u32 {
+        self.wave.wave
+    }
+
+    pub fn is_between_waves(&self) -> bool {
+        matches!(self.state, StageState::Cooldown(_))
+    }
+
+    pub fn tally(&self) -> Option<&WaveTally> {
+        self.tally.as_ref()
+    }
+
+    // Seconds until the next wave starts, only while between waves
+    pub fn cooldown_left(&self) -> Option<f32> {
+        match &self.state {
+            StageState::Cooldown(timer) => Some(timer.remaining_secs()),
+            _ => None,
+        }
+    }
+
+    // Seconds since the current wave started, based on the gameplay watch
+    pub fn wave_time(&self, stats: &Stats) -> f32 {
+        stats.watch.elapsed_secs() - self.wave_start.time_secs
+    }
+
+    // Share of the wave enemies that have been spawned, from 0.0 up to 1.0
+    pub fn spawn_progress(&self) -> Option<f32> {
+        match &self.state {
+            StageState::Spawning(spawners) => {
+                let spawned: u32 = spawners.iter().map(|spawner| spawner.spawned).sum();
+                let spawn_total: u32 = spawners.iter().map(|spawner| spawner.spawn_total).sum();
+                Some(match spawn_total {
+                    0 => 1.0,
+                    _ => spawned as f32 / spawn_total as f32,
+                })
+            }
+            StageState::Clearing => Some(1.0),
+            StageState::Cooldown(_) => None,
+        }
+    }
+}
+
+#[cfg(feature = "dev")]
+impl GameplayStage {
+    pub fn state_summary(&self) -> String {
+        match &self.state {
+            StageState::Spawning(spawners) => {
+                let spawned: u32 = spawners.iter().map(|spawner| spawner.spawned).sum();
+                let spawn_total: u32 = spawners.iter().map(|spawner| spawner.spawn_total).sum();
+                format!("Spawning {}/{}", spawned, spawn_total)
+            }
+            StageState::Clearing => "Clearing".to_string(),
+            StageState::Cooldown(timer) => {
+                format!("Cooldown {:.1}s", timer.remaining_secs())
+            }
+        }
+    }
+
+    // Areas of the active spawners as center and size
+    pub fn spawner_areas(&self) -> Vec<(Vec2, Vec2)> {
+        match &self.state {
+            StageState::Spawning(spawners) => spawners
+                .iter()
+                .filter(|spawner| !spawner.is_finished())
+                .map(|spawner| {
+                    (
+                        Vec2::new(spawner.area.center.x, spawner.area.center.y),
+                        Vec2::new(spawner.area.width, spawner.area.height),
+                    )
+                })
+                .collect(),
+            StageState::Clearing | StageState::Cooldown(_) => vec![],
+        }
+    }
+
+    // Drops the remaining spawns and starts the next wave on the following update
+    pub fn skip_wave(&mut self) {
+        self.tally = None;
+        self.state = StageState::Cooldown(Timer::from_seconds(0.0, TimerMode::Once));
+    }
 }
 
 #[derive(Component)]
@@ -206,7 +403,7 @@ impl EnemyCount {
 
     pub fn remove_enemy_count(&mut self, entity_type: EntityType, amount: u32) {
         if matches!(entity_type, EntityType::Asteroid(_)) {
-            self.asteroids -= amount;
+            self.asteroids = self.asteroids.saturating_sub(amount);
         }
     }
 }
@@ -224,8 +421,11 @@ pub struct EnemyBundle {
     entity_type: EntityType,
     movable: Movable,
     velocity: Velocity,
+    angular_velocity: AngularVelocity,
+    health: EnemyHealth,
+    collider: Collider,
     #[bundle()]
-    sprite: SpriteBundle,
+    sprite: AsepriteBundle,
 }
 
 impl EnemyBundle {
@@ -234,36 +434,59 @@ impl EnemyBundle {
     pub fn new(
         entity_type: EntityType,
         velocity: Velocity,
-        sprite: Sprite,
-        spawn_point: Vec3,
+        angular_velocity: AngularVelocity,
+        health: EnemyHealth,
+        collider: Collider,
+        sprite: AsepriteBundle,
     ) -> Self {
         EnemyBundle {
             enemy: Enemy,
             entity_type,
             movable: Movable::new(true),
             velocity,
-            sprite: SpriteBundle {
-                sprite,
-                transform: Transform::from_translation(spawn_point),
-                ..default()
-            },
+            angular_velocity,
+            health,
+            collider,
+            sprite,
         }
     }
 }
 
 // ===
 
-fn spawn_stage(mut commands: Commands) {
+fn spawn_stage(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    snapshot: Option<Res<RunSnapshot>>,
+) {
+    // Resumed run picks up the stage where it was saved
+    let (stage, enemy_count) = match snapshot {
+        Some(snapshot) => {
+            let mut enemy_count = EnemyCount::new();
+            for entity_type in snapshot.enemy_types() {
+                enemy_count.add_enemy_count(entity_type, 1);
+            }
+
+            (snapshot.stage.clone(), enemy_count)
+        }
+        None => (
+            GameplayStage {
+                wave: StageWave::new(),
+                state: StageState::Cooldown(Timer::from_seconds(
+                    config.stage.init_cooldown,
+                    TimerMode::Once,
+                )),
+                wave_start: WaveStart::default(),
+                tally: None,
+            },
+            EnemyCount::new(),
+        ),
+    };
+
     commands.spawn(GameplayBundle {
         gameplay: Gameplay,
-        stage: GameplayStage {
-            wave: StageWave::new(),
-            state: StageState::Cooldown(Timer::from_seconds(
-                consts::STAGE_INIT_COOLDOWN,
-                TimerMode::Once,
-            )),
-        },
-        enemy_count: EnemyCount::new(),
+        stage,
+        enemy_count,
     });
 }
 
@@ -271,47 +494,43 @@ fn stage_manager(
     mut ev_spawn: EventWriter<SpawnEnemy>,
     time: Res<Time>,
     win_size: Res<WinSize>,
+    config: Res<GameConfig>,
+    progress: RunProgress,
+    mut rng: ResMut<GameRng>,
     mut query: Query<&mut GameplayStage>,
 ) {
     if let Ok(mut stage) = query.get_single_mut() {
         match stage.state {
             StageState::Spawning(ref mut spawners) => {
-                let mut finished_spawners = vec![];
-
-                for (idx, spawner) in spawners.iter_mut().enumerate() {
-                    if spawner.spawned >= spawner.spawn_total {
-                        finished_spawners.push(idx);
+                for spawner in spawners.iter_mut() {
+                    if spawner.is_finished() {
                         continue;
                     }
 
                     spawner.interval.tick(time.delta());
 
                     if spawner.interval.finished() {
-                        let spawn_point = spawner.get_enemy_spawn_point();
-                        let initial_velocity = spawner.get_enemy_initial_velocity();
-
-                        ev_spawn.send(SpawnEnemy::new(
-                            spawner.entity_type,
-                            initial_velocity,
-                            spawn_point,
-                        ));
+                        let spawn_point = spawner.get_enemy_spawn_point(&mut rng);
+                        let initial_velocity = spawner.get_enemy_initial_velocity(&config);
+                        let mut spawn_ev =
+                            SpawnEnemy::new(spawner.entity_type, initial_velocity, spawn_point);
+
+                        if let Some(wave_pattern) = spawner.pick_pattern(&mut rng) {
+                            spawn_ev = spawn_ev.with_pattern(wave_pattern);
+                        }
+
+                        ev_spawn.send(spawn_ev);
                         spawner.add_spawned_count(1);
                     }
                 }
 
-                // Remove spawners that have finished
-                for idx in finished_spawners.iter().rev() {
-                    spawners.remove(*idx);
-                }
-
-                // When spawners have finished start cooldown
-                if spawners.is_empty() {
-                    stage.state = StageState::Cooldown(Timer::from_seconds(
-                        consts::STAGE_COOLDOWN,
-                        TimerMode::Once,
-                    ));
+                // Finished spawners are kept around for the wave progress
+                if spawners.iter().all(|spawner| spawner.is_finished()) {
+                    stage.state = StageState::Clearing;
                 }
             }
+            // Waits for the last enemy, see end_cleared_wave
+            StageState::Clearing => (),
             StageState::Cooldown(ref mut timer) => {
                 timer.tick(time.delta());
 
@@ -321,41 +540,101 @@ fn stage_manager(
                         &stage.wave.wave,
                         &stage.wave.stage_type,
                         &win_size,
+                        &config.stage,
                     );
                     stage.state = StageState::Spawning(spawners);
+                    stage.tally = None;
+                    stage.wave_start = progress.wave_start();
+                }
+            }
+        }
+    }
+}
+
+// Tally and bonus once the last enemy was destroyed or left the screen
+fn end_cleared_wave(
+    mut ev_wave_ended: EventWriter<WaveEnded>,
+    mut ev_add_score: EventWriter<AddScore>,
+    config: Res<GameConfig>,
+    progress: RunProgress,
+    player_count: Res<PlayerCount>,
+    mut query: Query<&mut GameplayStage>,
+    enemy_query: Query<(), With<Enemy>>,
+) {
+    if let Ok(mut stage) = query.get_single_mut() {
+        if matches!(stage.state, StageState::Clearing) && enemy_query.is_empty() {
+            let tally = WaveTally::new(
+                &stage.wave_start,
+                &progress.stats,
+                &progress.run_stats,
+                &config,
+            );
+
+            // Bonus is shared between the players
+            let players = player_count.0.max(1) as u32;
+            for player in (0..player_count.0).map(PlayerId) {
+                let mut points = tally.total() / players;
+                if player.0 == 0 {
+                    points += tally.total() % players;
                 }
+
+                ev_add_score.send(AddScore {
+                    score_type: AddScoreType::WaveBonus(points),
+                    player,
+                    translation: Vec3::ZERO,
+                });
             }
+
+            ev_wave_ended.send(WaveEnded(stage.wave.wave));
+            stage.tally = Some(tally);
+            stage.state =
+                StageState::Cooldown(Timer::from_seconds(config.stage.cooldown, TimerMode::Once));
         }
     }
 }
 
 fn enemy_collision_detection(
     mut ev_despawn: EventWriter<DespawnEntity>,
+    mut ev_break_asteroid: EventWriter<BreakAsteroid>,
     mut ev_spaceship_hit: EventWriter<SpaceshipIsHit>,
-    enemy_query: Query<(Entity, &Transform, &Sprite, &EntityType), With<Enemy>>,
-    spaceship_query: Query<(Entity, &Transform), (With<Spaceship>, Without<Invulnerability>)>,
-    player_asset_dimensions: Res<PlayerAssetDimensions>,
+    enemy_query: Query<(Entity, &Transform, &Collider, &EntityType), With<Enemy>>,
+    spaceship_query: Query<
+        (Entity, &Transform, &Collider),
+        (With<Spaceship>, Without<Invulnerability>, Without<Downed>),
+    >,
 ) {
-    if let Ok((spaceship_entity, spaceship_tf)) = spaceship_query.get_single() {
-        for (enemy_entity, enemy_tf, enemy_sprite, enemy_type) in enemy_query.iter() {
-            let enemy_size = match enemy_sprite.custom_size {
-                Some(size) => size * enemy_tf.scale.xy(),
-                None => panic!("Enemy sprite has no custom size"),
-            };
+    let mut processed_entities: HashSet<Entity> = HashSet::new();
+
+    for (spaceship_entity, spaceship_tf, spaceship_collider) in spaceship_query.iter() {
+        for (enemy_entity, enemy_tf, enemy_collider, enemy_type) in enemy_query.iter() {
+            if processed_entities.contains(&enemy_entity) {
+                continue;
+            }
 
             let collision = collide(
                 spaceship_tf.translation,
-                player_asset_dimensions.spaceship,
+                spaceship_collider.scaled_size(spaceship_tf),
                 enemy_tf.translation,
-                enemy_size,
+                enemy_collider.scaled_size(enemy_tf),
             );
 
             if collision.is_some() {
+                processed_entities.insert(enemy_entity);
                 ev_spaceship_hit.send(SpaceshipIsHit(spaceship_entity));
-                ev_despawn.send(DespawnEntity {
-                    entity: enemy_entity,
-                    entity_type: *enemy_type,
-                });
+
+                if let EntityType::Asteroid(_) = enemy_type {
+                    ev_break_asteroid.send(BreakAsteroid {
+                        entity: enemy_entity,
+                        entity_type: *enemy_type,
+                        source: None,
+                        charged: false,
+                    });
+                } else {
+                    ev_despawn.send(DespawnEntity {
+                        entity: enemy_entity,
+                        entity_type: *enemy_type,
+                    });
+                }
             }
         }
     }
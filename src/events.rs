@@ -1,14 +1,28 @@
-use bevy::{prelude::*, window::WindowResized};
-use rand::{thread_rng, Rng};
+use std::f32::consts::TAU;
+
+use bevy::{
+    ecs::system::SystemParam,
+    prelude::*,
+    utils::{HashMap, HashSet},
+    window::WindowResized,
+};
+use bevy_mod_aseprite::{Aseprite, AsepriteAnimation};
+use rand::Rng;
 
 use crate::{
-    common::{Asteroid, AsteroidType, EntityType},
+    common::{Asteroid, AsteroidAssets, AsteroidBreaking, AsteroidType, Collider, EntityType},
+    config::{Escort, GameConfig, WavePattern},
     consts,
-    enemy::EnemyCount,
+    enemy::{Enemy, EnemyCount, EnemyHealth},
     is_playing,
-    movement::Velocity,
-    player::{Invulnerability, Spaceship, SpaceshipHealth},
-    GameplayState, Stats, WinSize,
+    movement::{Movable, MovementPattern, PatternMovement, Velocity},
+    player::{
+        Beam, Downed, GodMode, Invulnerability, PlayerCount, PlayerId, Respawning, Spaceship,
+        SpaceshipHealth,
+    },
+    rng::GameRng,
+    score::{Combo, ScorePopup},
+    FontHandle, Stats, WinSize,
 };
 
 pub struct EventsPlugin;
@@ -16,19 +30,31 @@ pub struct EventsPlugin;
 impl Plugin for EventsPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<DespawnEntity>()
+            .add_event::<BreakAsteroid>()
             .add_event::<AddScore>()
+            .add_event::<EnemyIsHit>()
             .add_event::<SpaceshipIsHit>()
+            .add_event::<SpaceshipDamaged>()
+            .add_event::<LifeLost>()
+            .add_event::<SpaceshipDashed>()
             .add_event::<SpawnEnemy>()
             .add_event::<SplitAsteroid>()
+            .add_event::<WaveEnded>()
             .add_systems(
                 PreUpdate,
                 (
+                    enemy_hit_handler
+                        .in_set(EventSet::HandleHit)
+                        .after(EventSet::CreateEv),
                     spaceship_hit_handler
                         .in_set(EventSet::HandleHit)
                         .after(EventSet::CreateEv),
                     despawn_entities_handler
                         .in_set(EventSet::HandleDespawn)
                         .after(EventSet::HandleHit),
+                    break_asteroid_handler
+                        .in_set(EventSet::HandleAsteroidBreak)
+                        .after(EventSet::HandleHit),
                     add_score_handler
                         .in_set(EventSet::HandleScore)
                         .after(EventSet::HandleDespawn),
@@ -53,31 +79,121 @@ pub struct DespawnEntity {
     pub entity_type: EntityType,
 }
 
+// Destroyed asteroid that plays its break apart animation before despawning
+#[derive(Event)]
+pub struct BreakAsteroid {
+    pub entity: Entity,
+    pub entity_type: EntityType,
+    // Projectile, beam or mine that destroyed the asteroid, none when it crashed into a spaceship
+    pub source: Option<Entity>,
+    pub charged: bool,
+}
+
+impl BreakAsteroid {
+    // Asteroids crashing into a spaceship weren't destroyed by anyone,
+    // only shot down ones count towards the stats and achievements
+    pub fn is_shot_down(&self) -> bool {
+        self.source.is_some()
+    }
+
+    // Same asteroid can be hit by several things in one frame,
+    // only the first of its events is kept
+    pub fn unique<'a>(
+        events: impl Iterator<Item = &'a BreakAsteroid>,
+    ) -> impl Iterator<Item = &'a BreakAsteroid> {
+        let mut processed_entities: HashSet<Entity> = HashSet::new();
+        events.filter(move |break_ev| processed_entities.insert(break_ev.entity))
+    }
+}
+
 pub enum AddScoreType {
     EnemyDestroyed(EntityType),
+    // Bonus for destroying several enemies with a single shot
+    MultiKill(u32),
+    // Share of the wave clear bonus, not affected by the combo
+    WaveBonus(u32),
+}
+
+#[derive(Event)]
+pub struct AddScore {
+    pub score_type: AddScoreType,
+    pub player: PlayerId,
+    // Where the score popup shows up
+    pub translation: Vec3,
+}
+
+impl AddScore {
+    // Multi kill bonus pops up in the middle of everything the shot destroyed
+    pub fn multi_kill(player: PlayerId, kill_translations: &[Vec3]) -> Option<Self> {
+        let kill_count = kill_translations.len();
+
+        (kill_count > 1).then(|| AddScore {
+            score_type: AddScoreType::MultiKill(kill_count as u32),
+            player,
+            translation: kill_translations.iter().sum::<Vec3>() / kill_count as f32,
+        })
+    }
 }
 
+// Enemy hit by anything a spaceship fired
 #[derive(Event)]
-pub struct AddScore(pub AddScoreType);
+pub struct EnemyIsHit {
+    pub entity: Entity,
+    pub damage: u32,
+    pub player: PlayerId,
+    // Projectile, beam or mine that hit the enemy
+    pub source: Entity,
+    // Source was fired by a charged weapon
+    pub charged: bool,
+    pub impact: Vec3,
+    // Direction the hit pushes the enemy in
+    pub direction: Vec2,
+}
 
 #[derive(Event)]
 pub struct SpaceshipIsHit(pub Entity);
 
+// Hit that actually took health from the spaceship
+#[derive(Event)]
+pub struct SpaceshipDamaged;
+
+#[derive(Event)]
+pub struct SpaceshipDashed;
+
+// Spaceship of the player is out of health and can't be revived
+#[derive(Event)]
+pub struct LifeLost(pub PlayerId);
+
+// Sent when every spawner of the wave has finished, carries the wave number
+#[derive(Event)]
+pub struct WaveEnded(pub u32);
+
 #[derive(Event)]
 pub struct SplitAsteroid {
     translation: Vec3,
     size: Vec2,
     velocity: Velocity,
     asteroid: Asteroid,
+    impact: Vec3,
+    direction: Vec2,
 }
 
 impl SplitAsteroid {
-    pub fn new(translation: Vec3, size: Vec2, velocity: Velocity, asteroid: Asteroid) -> Self {
+    pub fn new(
+        translation: Vec3,
+        size: Vec2,
+        velocity: Velocity,
+        asteroid: Asteroid,
+        impact: Vec3,
+        direction: Vec2,
+    ) -> Self {
         SplitAsteroid {
             translation,
             size,
             velocity,
             asteroid,
+            impact,
+            direction,
         }
     }
 }
@@ -87,6 +203,8 @@ pub struct SpawnEnemy {
     entity_type: EntityType,
     initial_velocity: Velocity,
     spawn_point: Vec3,
+    pattern: Option<MovementPattern>,
+    escort: Option<Escort>,
 }
 
 impl SpawnEnemy {
@@ -95,8 +213,16 @@ impl SpawnEnemy {
             entity_type,
             initial_velocity,
             spawn_point,
+            pattern: None,
+            escort: None,
         }
     }
+
+    pub fn with_pattern(mut self, wave_pattern: &WavePattern) -> Self {
+        self.pattern = wave_pattern.pattern.clone();
+        self.escort = wave_pattern.escort.clone();
+        self
+    }
 }
 
 #[derive(SystemSet, Clone, Hash, Debug, Eq, PartialEq)]
@@ -104,6 +230,7 @@ pub enum EventSet {
     CreateEv,
     HandleHit,
     HandleDespawn,
+    HandleAsteroidBreak,
     HandleScore,
     HandleAsteroidSplit,
     HandleSpawn,
@@ -114,10 +241,22 @@ pub enum EventSet {
 fn despawn_entities_handler(
     mut commands: Commands,
     mut ev_despawn: EventReader<DespawnEntity>,
+    mut ev_break_asteroid: EventReader<BreakAsteroid>,
     mut query: Query<&mut EnemyCount>,
 ) {
+    // Asteroids broken in the same frame are counted by the break handler
+    // and despawned once their animation is over
+    let mut processed_entities: HashSet<Entity> = ev_break_asteroid
+        .iter()
+        .map(|break_ev| break_ev.entity)
+        .collect();
+
     if let Ok(mut enemy_count) = query.get_single_mut() {
         for despawn_ev in ev_despawn.iter() {
+            if !processed_entities.insert(despawn_ev.entity) {
+                continue;
+            }
+
             commands.entity(despawn_ev.entity).despawn();
 
             enemy_count.remove_enemy_count(despawn_ev.entity_type, 1);
@@ -125,42 +264,238 @@ fn despawn_entities_handler(
     }
 }
 
-fn add_score_handler(mut add_score_events: EventReader<AddScore>, mut stats: ResMut<Stats>) {
+fn break_asteroid_handler(
+    mut commands: Commands,
+    mut ev_break_asteroid: EventReader<BreakAsteroid>,
+    mut query: Query<&mut EnemyCount>,
+    asteroid_query: Query<&Handle<Aseprite>, With<Enemy>>,
+    aseprites: Res<Assets<Aseprite>>,
+) {
+    if let Ok(mut enemy_count) = query.get_single_mut() {
+        for break_ev in BreakAsteroid::unique(ev_break_asteroid.iter()) {
+            if let Ok(aseprite_handle) = asteroid_query.get(break_ev.entity) {
+                // Broken asteroid stops being an enemy, so it can't collide anymore,
+                // but keeps drifting and spinning while the animation plays
+                let mut asteroid = commands.entity(break_ev.entity);
+                asteroid
+                    .remove::<(Enemy, EntityType, Movable, Collider)>()
+                    .insert(AsteroidBreaking::new());
+
+                if let Some(aseprite) = aseprites.get(aseprite_handle) {
+                    asteroid.insert(AsepriteAnimation::new(aseprite.info(), "break"));
+                }
+
+                enemy_count.remove_enemy_count(break_ev.entity_type, 1);
+            }
+        }
+    }
+}
+
+fn add_score_handler(
+    mut commands: Commands,
+    mut add_score_events: EventReader<AddScore>,
+    mut stats: ResMut<Stats>,
+    mut combo: ResMut<Combo>,
+    font: Res<FontHandle>,
+    config: Res<GameConfig>,
+) {
     for add_score_ev in add_score_events.iter() {
-        match add_score_ev.0 {
+        let points = match add_score_ev.score_type {
             AddScoreType::EnemyDestroyed(entity_type) => {
-                if let EntityType::Asteroid(_) = entity_type {
-                    stats.score += consts::SCORE_ADD_ASTEROID;
+                combo.add_kill(&config.score);
+                config.score.points(&entity_type) * combo.multiplier(&config.score)
+            }
+            AddScoreType::MultiKill(kills) => {
+                config.score.multi_kill_bonus
+                    * kills.saturating_sub(1)
+                    * combo.multiplier(&config.score)
+            }
+            AddScoreType::WaveBonus(points) => points,
+        };
+
+        if points > 0 {
+            stats.score += points;
+            stats.player_scores[add_score_ev.player.0] += points;
+
+            // Wave bonus is already shown in the intermission tally
+            if !matches!(add_score_ev.score_type, AddScoreType::WaveBonus(_)) {
+                ScorePopup::spawn(&mut commands, &font, add_score_ev.translation, points);
+            }
+        }
+    }
+}
+
+// Everything that follows from destroying an enemy
+#[derive(SystemParam)]
+struct DestroyedEnemyEvents<'w> {
+    despawn: EventWriter<'w, DespawnEntity>,
+    break_asteroid: EventWriter<'w, BreakAsteroid>,
+    split_asteroid: EventWriter<'w, SplitAsteroid>,
+    add_score: EventWriter<'w, AddScore>,
+}
+
+fn enemy_hit_handler(
+    mut commands: Commands,
+    mut ev_enemy_hit: EventReader<EnemyIsHit>,
+    mut ev_destroyed: DestroyedEnemyEvents,
+    mut enemy_query: Query<
+        (
+            &Transform,
+            &Collider,
+            &EntityType,
+            &mut Velocity,
+            &mut EnemyHealth,
+            Option<&PatternMovement>,
+        ),
+        With<Enemy>,
+    >,
+    mut beam_query: Query<&mut Beam>,
+    config: Res<GameConfig>,
+) {
+    let mut destroyed_entities: HashSet<Entity> = HashSet::new();
+    // Enemies destroyed by every source that is gone within the frame,
+    // beams keep their own count for as long as they last
+    let mut kills: HashMap<Entity, (PlayerId, Vec<Vec3>)> = HashMap::new();
+
+    for hit_ev in ev_enemy_hit.iter() {
+        if destroyed_entities.contains(&hit_ev.entity) {
+            continue;
+        }
+
+        if let Ok((tf, collider, entity_type, mut velocity, mut health, movement)) =
+            enemy_query.get_mut(hit_ev.entity)
+        {
+            health.0 = health.0.saturating_sub(hit_ev.damage);
+
+            // Surviving asteroid is knocked off its course
+            if health.0 > 0 {
+                if let EntityType::Asteroid(asteroid) = entity_type {
+                    let push = hit_ev.direction * consts::ASTEROID_HIT_IMPULSE
+                        / config.asteroids.get(asteroid.asteroid_type).mass;
+                    velocity.x += push.x;
+                    velocity.y += push.y;
+
+                    if movement.is_some() {
+                        commands.entity(hit_ev.entity).remove::<PatternMovement>();
+                    }
+                }
+                continue;
+            }
+
+            destroyed_entities.insert(hit_ev.entity);
+            match beam_query.get_mut(hit_ev.source) {
+                Ok(mut beam) => beam.add_kill(tf.translation),
+                Err(_) => kills
+                    .entry(hit_ev.source)
+                    .or_insert_with(|| (hit_ev.player, Vec::new()))
+                    .1
+                    .push(tf.translation),
+            }
+
+            ev_destroyed.add_score.send(AddScore {
+                score_type: AddScoreType::EnemyDestroyed(*entity_type),
+                player: hit_ev.player,
+                translation: tf.translation,
+            });
+
+            if let EntityType::Asteroid(asteroid) = entity_type {
+                ev_destroyed.break_asteroid.send(BreakAsteroid {
+                    entity: hit_ev.entity,
+                    entity_type: *entity_type,
+                    source: Some(hit_ev.source),
+                    charged: hit_ev.charged,
+                });
+
+                if !matches!(asteroid.asteroid_type, AsteroidType::Small) {
+                    ev_destroyed.split_asteroid.send(SplitAsteroid::new(
+                        tf.translation,
+                        collider.scaled_size(tf),
+                        *velocity,
+                        *asteroid,
+                        hit_ev.impact,
+                        hit_ev.direction,
+                    ));
                 }
+            } else {
+                ev_destroyed.despawn.send(DespawnEntity {
+                    entity: hit_ev.entity,
+                    entity_type: *entity_type,
+                });
             }
         }
     }
+
+    for (player, kill_translations) in kills.into_values() {
+        if let Some(multi_kill) = AddScore::multi_kill(player, &kill_translations) {
+            ev_destroyed.add_score.send(multi_kill);
+        }
+    }
 }
 
 fn spaceship_hit_handler(
     mut commands: Commands,
     mut ev_hit: EventReader<SpaceshipIsHit>,
-    mut spaceship_query: Query<&mut SpaceshipHealth>,
+    mut ev_life_lost: EventWriter<LifeLost>,
+    mut ev_damaged: EventWriter<SpaceshipDamaged>,
+    mut spaceship_query: Query<(
+        Entity,
+        &PlayerId,
+        &mut SpaceshipHealth,
+        Option<&Downed>,
+        Option<&GodMode>,
+    )>,
+    player_count: Res<PlayerCount>,
+    config: Res<GameConfig>,
 ) {
-    if let Ok(mut health) = spaceship_query.get_single_mut() {
-        if let Some(hit_ev) = ev_hit.iter().next() {
-            if health.0 > 0 {
+    let mut processed_entities: HashSet<Entity> = HashSet::new();
+    let mut downed_entities: HashSet<Entity> = HashSet::new();
+
+    for hit_ev in ev_hit.iter() {
+        if !processed_entities.insert(hit_ev.0) {
+            continue;
+        }
+
+        if let Ok((_, player, mut health, _, god_mode)) = spaceship_query.get_mut(hit_ev.0) {
+            if health.0 > 0 && god_mode.is_none() {
                 health.0 -= 1;
+                ev_damaged.send(SpaceshipDamaged);
 
-                if health.0 == 0 {
-                    commands.insert_resource(NextState(Some(GameplayState::GameOver)));
+                if health.0 == 0 && player_count.is_coop() {
+                    // Teammate can still bring the ship back, see revive_downed_spaceships
+                    commands
+                        .entity(hit_ev.0)
+                        .remove::<Invulnerability>()
+                        .insert((Downed::new(&config.player), Velocity::new(0., 0.)));
+                    downed_entities.insert(hit_ev.0);
+                } else if health.0 == 0 {
+                    ev_life_lost.send(LifeLost(*player));
                 } else {
-                    commands.entity(hit_ev.0).insert(Invulnerability::new());
+                    commands
+                        .entity(hit_ev.0)
+                        .insert(Invulnerability::new(&config.player));
                 }
             }
         }
     }
+
+    // Nobody is left to revive anyone, so every downed spaceship loses a life
+    if !downed_entities.is_empty() {
+        let all_downed = spaceship_query
+            .iter()
+            .all(|(entity, _, _, downed, _)| downed.is_some() || downed_entities.contains(&entity));
+
+        if all_downed {
+            for (_, player, ..) in spaceship_query.iter() {
+                ev_life_lost.send(LifeLost(*player));
+            }
+        }
+    }
 }
 
 fn window_resize_handler(
     mut win_size: ResMut<WinSize>,
     mut ev_resize: EventReader<WindowResized>,
-    mut player_query: Query<&mut Transform, With<Spaceship>>,
+    mut player_query: Query<&mut Transform, (With<Spaceship>, Without<Respawning>)>,
 ) {
     // When window is resizing, look after which window dim
     // is the smaller value, then based on the smallest dim
@@ -173,54 +508,79 @@ fn window_resize_handler(
     }
 
     // TODO ship can go outside screen bounds when resizing
-    if let Ok(mut tf) = player_query.get_single_mut() {
+    for mut tf in player_query.iter_mut() {
         tf.translation.y = Spaceship::player_position(win_size.h);
     }
 }
 
+// Fragments keep the momentum of the asteroid together with the push of the hit,
+// the mass that doesn't end up in a fragment turns to dust moving along with them
 fn split_asteroid_handler(
     mut ev_spawn: EventWriter<SpawnEnemy>,
     mut ev_asteroid_split: EventReader<SplitAsteroid>,
+    mut rng: ResMut<GameRng>,
+    config: Res<GameConfig>,
 ) {
+    let mass = |asteroid_type: &AsteroidType| config.asteroids.get(*asteroid_type).mass;
+
     for asteroid_split_ev in ev_asteroid_split.iter() {
         let asteroid = asteroid_split_ev.asteroid;
-        let translation = asteroid_split_ev.translation;
-        let velocity = asteroid_split_ev.velocity;
+        let center = asteroid_split_ev.translation.truncate();
         let size = asteroid_split_ev.size;
+        let direction = asteroid_split_ev.direction;
 
-        let mut rng = thread_rng();
-        let entity_x = translation.x;
-        let entity_y = translation.y;
-
-        let w_span_left = entity_x - size.x / 2.0;
-        let w_span_right = entity_x + size.x / 2.0;
-
-        let h_span_bottom = entity_y - size.y / 2.0;
-        let h_span_top = entity_y + size.y / 2.0;
+        let parent_mass = mass(&asteroid.asteroid_type);
+        let momentum = Vec2::new(asteroid_split_ev.velocity.x, asteroid_split_ev.velocity.y)
+            * parent_mass
+            + direction * consts::ASTEROID_HIT_IMPULSE;
 
         let asteroid_amount = rng.gen_range(0..2);
-        for _ in 0..=asteroid_amount {
-            let spawn_point = Vec3::new(
-                rng.gen_range(w_span_left..w_span_right),
-                rng.gen_range(h_span_bottom..h_span_top),
-                consts::ENEMY_Z,
-            );
-
-            let asteroid_type = if let AsteroidType::Large = asteroid.asteroid_type {
-                match rng.gen_bool(0.7) {
+        let fragment_types: Vec<AsteroidType> = (0..=asteroid_amount)
+            .map(|_| match asteroid.asteroid_type {
+                AsteroidType::Large => match rng.gen_bool(0.7) {
                     true => AsteroidType::Medium,
                     false => AsteroidType::Small,
-                }
-            } else {
-                AsteroidType::Small
-            };
+                },
+                _ => AsteroidType::Small,
+            })
+            .collect();
+
+        // Fragments are placed side by side across the direction of the hit
+        let across = match direction.try_normalize() {
+            Some(direction) => direction.perp(),
+            None => Vec2::X,
+        };
+        let count = fragment_types.len() as f32;
+        let positions: Vec<Vec2> = (0..fragment_types.len())
+            .map(|idx| center + across * (idx as f32 - (count - 1.0) / 2.0) * size.x / count)
+            .collect();
+
+        // Every fragment is kicked away from the impact point, the kicks are evened out
+        // by mass so they don't add any momentum
+        let kicks: Vec<Vec2> = positions
+            .iter()
+            .map(|position| {
+                (*position - asteroid_split_ev.impact.truncate()).normalize_or_zero()
+                    * consts::ASTEROID_SPLIT_SPEED
+            })
+            .collect();
+        let fragments_mass: f32 = fragment_types.iter().map(mass).sum();
+        let mean_kick = fragment_types
+            .iter()
+            .zip(kicks.iter())
+            .map(|(asteroid_type, kick)| *kick * mass(asteroid_type))
+            .sum::<Vec2>()
+            / fragments_mass;
 
-            let initial_velocity = Velocity::new(rng.gen_range(-60.0..60.0), velocity.y);
+        for ((asteroid_type, position), kick) in
+            fragment_types.into_iter().zip(positions).zip(kicks)
+        {
+            let velocity = momentum / parent_mass + kick - mean_kick;
 
             ev_spawn.send(SpawnEnemy::new(
                 EntityType::Asteroid(Asteroid { asteroid_type }),
-                initial_velocity,
-                spawn_point,
+                Velocity::new(velocity.x, velocity.y),
+                position.extend(consts::ENEMY_Z),
             ));
         }
     }
@@ -230,18 +590,78 @@ fn spawn_enemies_handler(
     mut commands: Commands,
     mut ev_spawn: EventReader<SpawnEnemy>,
     mut query: Query<&mut EnemyCount>,
+    asteroid_assets: AsteroidAssets,
+    mut rng: ResMut<GameRng>,
 ) {
     if let Ok(mut enemy_count) = query.get_single_mut() {
         for spawn_ev in ev_spawn.iter() {
             if let EntityType::Asteroid(asteroid) = spawn_ev.entity_type {
-                let asteroid_bundle = asteroid.construct_asteroid_bundle(
+                if let Some(asteroid_bundle) = asteroid.construct_asteroid_bundle(
                     spawn_ev.entity_type,
                     spawn_ev.initial_velocity,
                     spawn_ev.spawn_point,
-                );
-                commands.spawn(asteroid_bundle);
-                enemy_count.add_enemy_count(spawn_ev.entity_type, 1);
+                    &asteroid_assets,
+                    &mut rng,
+                ) {
+                    let mut enemy = commands.spawn(asteroid_bundle);
+                    if let Some(pattern) = &spawn_ev.pattern {
+                        enemy.insert(PatternMovement::new(
+                            pattern.clone(),
+                            spawn_ev.initial_velocity,
+                        ));
+                    }
+                    let anchor = enemy.id();
+                    enemy_count.add_enemy_count(spawn_ev.entity_type, 1);
+
+                    spawn_escort(
+                        &mut commands,
+                        &mut enemy_count,
+                        anchor,
+                        spawn_ev,
+                        &asteroid_assets,
+                        &mut rng,
+                    );
+                }
             }
         }
     }
 }
+
+// Escorting asteroids are spread evenly around the orbit of the enemy they guard
+fn spawn_escort(
+    commands: &mut Commands,
+    enemy_count: &mut EnemyCount,
+    anchor: Entity,
+    spawn_ev: &SpawnEnemy,
+    asteroid_assets: &AsteroidAssets,
+    rng: &mut GameRng,
+) {
+    let Some(escort) = &spawn_ev.escort else {
+        return;
+    };
+
+    let asteroid = Asteroid {
+        asteroid_type: AsteroidType::Small,
+    };
+    let entity_type = EntityType::Asteroid(asteroid);
+
+    for idx in 0..escort.count {
+        let phase = TAU * idx as f32 / escort.count as f32;
+        let spawn_point =
+            spawn_ev.spawn_point + PatternMovement::orbit_offset(escort.radius, phase).extend(0.0);
+
+        if let Some(asteroid_bundle) = asteroid.construct_asteroid_bundle(
+            entity_type,
+            spawn_ev.initial_velocity,
+            spawn_point,
+            asteroid_assets,
+            rng,
+        ) {
+            commands.spawn((
+                asteroid_bundle,
+                PatternMovement::orbit(escort.radius, escort.angular_speed, anchor, phase),
+            ));
+            enemy_count.add_enemy_count(entity_type, 1);
+        }
+    }
+}
@@ -0,0 +1,295 @@
+use std::fmt::Display;
+
+use bevy::{
+    asset::{AssetLoader, AssetPath, LoadContext, LoadedAsset},
+    ecs::system::SystemParam,
+    prelude::*,
+    reflect::{TypePath, TypeUuid},
+    utils::{BoxedFuture, HashMap},
+};
+use serde::Deserialize;
+
+use crate::{
+    assets::{check_loading_progress, AddAssetCollection, AssetCollection, LoadingAssets},
+    consts,
+    settings::Settings,
+    FontHandle, GameState,
+};
+
+pub struct LocalePlugin;
+
+impl Plugin for LocalePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Locale>()
+            .init_asset_loader::<LocaleLoader>()
+            .add_asset_collection::<LocaleHandles>(GameState::LoadingMenu)
+            .add_event::<CycleLanguage>()
+            .add_systems(
+                Update,
+                track_locale_fonts
+                    .before(check_loading_progress)
+                    .run_if(in_state(GameState::LoadingMenu)),
+            )
+            // Loading screen texts switch over as soon as the string tables are in
+            .add_systems(
+                Update,
+                (cycle_language, update_localized_texts)
+                    .chain()
+                    .run_if(resource_exists::<LocaleHandles>()),
+            );
+    }
+}
+
+// ===
+
+// String table of a language, loaded from the assets folder
+#[derive(Deserialize, TypeUuid, TypePath, Debug)]
+#[uuid = "6b1e0f47-93c2-4d8a-a5f3-2e7c9d04b6a1"]
+pub struct Locale {
+    pub id: String,
+    // Shown in the language picker, written in the language itself
+    pub name: String,
+    // Font for scripts the default font doesn't cover, loaded along with the table
+    #[serde(default)]
+    font: Option<String>,
+    #[serde(skip)]
+    font_handle: Option<Handle<Font>>,
+    strings: HashMap<String, String>,
+}
+
+#[derive(Resource)]
+pub struct LocaleHandles(Vec<Handle<Locale>>);
+
+impl AssetCollection for LocaleHandles {
+    fn load(asset_server: &AssetServer) -> Self {
+        LocaleHandles(
+            consts::LOCALES
+                .iter()
+                .map(|path| asset_server.load(*path))
+                .collect(),
+        )
+    }
+
+    fn handles(&self) -> Vec<HandleUntyped> {
+        self.0
+            .iter()
+            .map(|handle| handle.clone_weak_untyped())
+            .collect()
+    }
+}
+
+#[derive(Default)]
+struct LocaleLoader;
+
+impl AssetLoader for LocaleLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let mut locale = ron::de::from_bytes::<Locale>(bytes)?;
+
+            match locale.font.clone() {
+                Some(font) => {
+                    let font_path = AssetPath::from(font.as_str()).to_owned();
+                    locale.font_handle = Some(load_context.get_handle(font_path.clone()));
+                    load_context
+                        .set_default_asset(LoadedAsset::new(locale).with_dependency(font_path));
+                }
+                None => load_context.set_default_asset(LoadedAsset::new(locale)),
+            }
+
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["locale.ron"]
+    }
+}
+
+// Switches to the previous or the next loaded language
+#[derive(Event)]
+pub struct CycleLanguage {
+    pub is_next: bool,
+}
+
+// Text that is looked up by its key and follows the picked language
+#[derive(Component)]
+pub struct LocalizedText(pub &'static str);
+
+#[derive(SystemParam)]
+pub struct Localization<'w> {
+    settings: Res<'w, Settings>,
+    locale_handles: Res<'w, LocaleHandles>,
+    locales: Res<'w, Assets<Locale>>,
+    font: Res<'w, FontHandle>,
+}
+
+impl<'w> Localization<'w> {
+    // Loaded languages, in the order of the locale files
+    pub fn languages(&self) -> impl Iterator<Item = &Locale> {
+        self.locale_handles
+            .0
+            .iter()
+            .filter_map(|handle| self.locales.get(handle))
+    }
+
+    pub fn is_changed(&self) -> bool {
+        self.settings.is_changed()
+    }
+
+    fn locale(&self, id: &str) -> Option<&Locale> {
+        self.languages().find(|locale| locale.id == id)
+    }
+
+    pub fn language_name(&self) -> String {
+        self.locale(&self.settings.language).map_or_else(
+            || self.settings.language.clone(),
+            |locale| locale.name.clone(),
+        )
+    }
+
+    // Strings missing from the picked language fall back to the default one,
+    // and to the key itself when that doesn't have them either
+    pub fn text(&self, key: &str) -> String {
+        [self.settings.language.as_str(), consts::DEFAULT_LANGUAGE]
+            .into_iter()
+            .filter_map(|id| self.locale(id))
+            .find_map(|locale| locale.strings.get(key))
+            .cloned()
+            .unwrap_or_else(|| key.to_string())
+    }
+
+    // Fills in the `{name}` placeholders of the string
+    pub fn format(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        args.iter().fold(self.text(key), |text, (name, value)| {
+            text.replace(&format!("{{{}}}", name), &value.to_string())
+        })
+    }
+
+    // Font of the picked language, if it brings its own
+    pub fn locale_font(&self) -> Option<Handle<Font>> {
+        self.locale(&self.settings.language)
+            .and_then(|locale| locale.font_handle.clone())
+    }
+
+    pub fn font(&self) -> Handle<Font> {
+        self.locale_font().unwrap_or_else(|| self.font.0.clone())
+    }
+
+    pub fn style(&self, font_size: f32, color: Color) -> TextStyle {
+        TextStyle {
+            font: self.font(),
+            font_size,
+            color,
+        }
+    }
+
+    pub fn label(
+        &self,
+        key: &'static str,
+        font_size: f32,
+        color: Color,
+    ) -> (LocalizedText, TextBundle) {
+        (
+            LocalizedText(key),
+            TextBundle::from_section(self.text(key), self.style(font_size, color)),
+        )
+    }
+}
+
+// ===
+
+fn cycle_language(
+    mut ev_cycle: EventReader<CycleLanguage>,
+    mut settings: ResMut<Settings>,
+    locale_handles: Res<LocaleHandles>,
+    locales: Res<Assets<Locale>>,
+) {
+    let ids: Vec<&String> = locale_handles
+        .0
+        .iter()
+        .filter_map(|handle| locales.get(handle))
+        .map(|locale| &locale.id)
+        .collect();
+
+    if ids.is_empty() {
+        return;
+    }
+
+    for cycle_ev in ev_cycle.iter() {
+        let current = ids
+            .iter()
+            .position(|id| **id == settings.language)
+            .unwrap_or_default();
+        let step = match cycle_ev.is_next {
+            true => 1,
+            false => ids.len() - 1,
+        };
+
+        settings.language = ids[(current + step) % ids.len()].clone();
+        settings.store();
+    }
+}
+
+// Fonts are only dependencies of the string tables, so the loading screen
+// waits for them separately once the tables are in
+fn track_locale_fonts(
+    mut loading_assets: ResMut<LoadingAssets>,
+    locale_handles: Res<LocaleHandles>,
+    locales: Res<Assets<Locale>>,
+) {
+    let font_handles = locale_handles
+        .0
+        .iter()
+        .filter_map(|handle| locales.get(handle))
+        .filter_map(|locale| locale.font_handle.as_ref())
+        .map(|handle| handle.clone_weak_untyped());
+
+    loading_assets.add_handles(font_handles);
+}
+
+// Texts already on screen switch over to the picked language and its font,
+// texts spawned before the string tables were loaded are filled in once they are
+fn update_localized_texts(
+    localization: Localization,
+    mut ev_locale: EventReader<AssetEvent<Locale>>,
+    mut text_query: Query<(&mut Text, Option<Ref<LocalizedText>>)>,
+) {
+    let changed = ev_locale.iter().count() > 0 || localization.is_changed();
+
+    if localization.languages().next().is_none() {
+        return;
+    }
+
+    let font = localization.font();
+    // Texts on the built-in font are left on it
+    let is_localized_font = |handle: &Handle<Font>| {
+        *handle == localization.font.0
+            || localization
+                .languages()
+                .any(|locale| locale.font_handle.as_ref() == Some(handle))
+    };
+
+    for (mut text, localized_text) in text_query.iter_mut() {
+        let is_added = localized_text.as_ref().is_some_and(Ref::is_added);
+
+        if !changed && !is_added {
+            continue;
+        }
+
+        if let Some(key) = localized_text {
+            text.sections[0].value = localization.text(key.0);
+        }
+
+        if changed {
+            for section in text.sections.iter_mut() {
+                if is_localized_font(&section.style.font) {
+                    section.style.font = font.clone();
+                }
+            }
+        }
+    }
+}
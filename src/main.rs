@@ -1,22 +1,49 @@
+use assets::{AddAssetCollection, AssetCollection};
+#[cfg(feature = "dev")]
+use bevy::{asset::ChangeWatcher, utils::Duration};
 use bevy::{prelude::*, time::Stopwatch, window::WindowResolution};
 use bevy_mod_aseprite::AsepritePlugin;
-// use bevy_inspector_egui::quick::WorldInspectorPlugin;
+use serde::{Deserialize, Serialize};
 
+mod achievements;
+mod assets;
+mod background;
 mod camera;
 mod common;
+mod config;
 mod consts;
+#[cfg(feature = "dev")]
+mod dev;
 mod enemy;
 mod events;
+mod locale;
 mod movement;
 mod player;
+mod profile;
+mod rng;
+mod run_save;
+mod run_stats;
+mod save;
+mod score;
+mod settings;
+mod shop;
+mod title;
+mod transition;
 mod ui;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, States, Default)]
 pub enum GameState {
     #[default]
+    LoadingMenu,
     MainMenu,
+    ShipSelect,
     LoadingGame,
     Gameplay,
+    Achievements,
+    // Unlocks bought with the profile credits
+    Hangar,
+    Settings,
+    LoadingError,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, States, Default)]
@@ -24,11 +51,22 @@ pub enum GameplayState {
     #[default]
     Playing,
     Paused,
+    // Upgrade shop between waves
+    Shop,
+    Continue,
     GameOver,
 }
 
-pub fn is_playing(game: Res<State<GameState>>, gameplay: Res<State<GameplayState>>) -> bool {
-    matches!(game.get(), GameState::Gameplay) && matches!(gameplay.get(), GameplayState::Playing)
+// Gameplay stands still while a transition covers the screen,
+// so nothing can happen behind it
+pub fn is_playing(
+    game: Res<State<GameState>>,
+    gameplay: Res<State<GameplayState>>,
+    transitions: Res<transition::Transitions>,
+) -> bool {
+    matches!(game.get(), GameState::Gameplay)
+        && matches!(gameplay.get(), GameplayState::Playing)
+        && transition::is_idle(transitions)
 }
 
 pub fn is_gameplay(game: Res<State<GameState>>) -> bool {
@@ -36,12 +74,14 @@ pub fn is_gameplay(game: Res<State<GameState>>) -> bool {
 }
 
 fn main() {
-    App::new()
+    let mut app = App::new();
+
+    app
         // --- Initial resources ---
         .insert_resource(ClearColor(Color::BLACK))
         .init_resource::<WinSize>()
         .init_resource::<Stats>()
-        .add_systems(Startup, load_font)
+        .init_resource::<rng::GameRng>()
         // --- Initial game states ---
         .add_state::<GameState>()
         .add_state::<GameplayState>()
@@ -49,6 +89,12 @@ fn main() {
         .add_plugins((
             DefaultPlugins
                 .set(ImagePlugin::default_nearest())
+                .set(AssetPlugin {
+                    // Dev builds reload changed files from the assets folder while running
+                    #[cfg(feature = "dev")]
+                    watch_for_changes: ChangeWatcher::with_delay(Duration::from_millis(200)),
+                    ..default()
+                })
                 .set(WindowPlugin {
                     primary_window: Some(Window {
                         resolution: WindowResolution::new(
@@ -60,17 +106,36 @@ fn main() {
                     }),
                     ..default()
                 }),
+            assets::AssetsPlugin,
+            config::ConfigPlugin,
             camera::CameraPlugin,
+            background::BackgroundPlugin,
             player::PlayerPlugin,
             enemy::EnemyPlugin,
             movement::MovementPlugin,
             common::CommonPlugin,
+            score::ScorePlugin,
+            run_stats::RunStatsPlugin,
+            run_save::RunSavePlugin,
+            achievements::AchievementsPlugin,
             ui::UiPlugin,
             events::EventsPlugin,
         ))
+        .add_plugins((
+            shop::ShopPlugin,
+            profile::ProfilePlugin,
+            settings::SettingsPlugin,
+            locale::LocalePlugin,
+            transition::TransitionPlugin,
+            title::TitlePlugin,
+        ))
         .add_plugins(AsepritePlugin)
-        // .add_plugins(WorldInspectorPlugin::new())
-        .run();
+        .add_asset_collection::<FontHandle>(GameState::LoadingMenu);
+
+    #[cfg(feature = "dev")]
+    app.add_plugins(dev::DevPlugin);
+
+    app.run();
 }
 
 // ===
@@ -90,9 +155,12 @@ impl Default for WinSize {
     }
 }
 
-#[derive(Resource, Debug)]
+#[derive(Resource, Serialize, Deserialize, Clone, Debug)]
 pub struct Stats {
     pub score: u32,
+    // Share of the score earned by each player, used in co-op
+    pub player_scores: [u32; consts::MAX_PLAYERS],
+    #[serde(with = "save::stopwatch_serde")]
     pub watch: Stopwatch,
 }
 
@@ -100,7 +168,11 @@ impl Default for Stats {
     fn default() -> Self {
         let mut watch = Stopwatch::new();
         watch.pause();
-        Self { score: 0, watch }
+        Self {
+            score: 0,
+            player_scores: [0; consts::MAX_PLAYERS],
+            watch,
+        }
     }
 }
 
@@ -118,13 +190,18 @@ impl Stats {
 #[derive(Resource, Debug)]
 pub struct FontHandle(Handle<Font>);
 
-// ===
+impl AssetCollection for FontHandle {
+    fn load(asset_server: &AssetServer) -> Self {
+        FontHandle(asset_server.load("fonts/PixeloidSans-mLxMm.ttf"))
+    }
 
-fn load_font(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let font = asset_server.load("fonts/PixeloidSans-mLxMm.ttf");
-    commands.insert_resource(FontHandle(font));
+    fn handles(&self) -> Vec<HandleUntyped> {
+        vec![self.0.clone_weak_untyped()]
+    }
 }
 
+// ===
+
 // Despawns all entities that have a specific component attached to it
 pub fn despawn_entities<T: Component>(mut commands: Commands, query: Query<Entity, With<T>>) {
     for entity in query.iter() {
@@ -1,6 +1,13 @@
+use std::f32::consts::TAU;
+
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::{is_playing, player::Spaceship};
+use crate::{
+    enemy::Enemy,
+    is_playing,
+    player::{Downed, Spaceship},
+};
 
 pub struct MovementPlugin;
 
@@ -8,10 +15,13 @@ impl Plugin for MovementPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             Update,
-            apply_velocity
-                .run_if(is_playing)
-                .in_set(MovementSet::ApplyVelocity)
-                .after(MovementSet::UpdateVelocity),
+            (
+                apply_movement_patterns.in_set(MovementSet::UpdateVelocity),
+                (apply_velocity, apply_angular_velocity)
+                    .in_set(MovementSet::ApplyVelocity)
+                    .after(MovementSet::UpdateVelocity),
+            )
+                .run_if(is_playing),
         );
     }
 }
@@ -33,7 +43,7 @@ impl Movable {
     }
 }
 
-#[derive(Component, Clone, Copy, Debug)]
+#[derive(Component, Serialize, Deserialize, Clone, Copy, Debug)]
 pub struct Velocity {
     pub x: f32,
     pub y: f32,
@@ -45,7 +55,102 @@ impl Velocity {
     }
 }
 
-#[derive(Debug)]
+// Rotation speed around the z axis in radians per second
+#[derive(Component, Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct AngularVelocity(pub f32);
+
+// Path an enemy follows on top of its initial velocity, defined in the stage config
+#[derive(Reflect, Serialize, Deserialize, Clone, Debug)]
+pub enum MovementPattern {
+    // Sways sideways, amplitude in pixels and frequency in swings per second
+    Sine {
+        amplitude: f32,
+        frequency: f32,
+    },
+    // Switches the sideways direction every half of the period
+    ZigZag {
+        speed: f32,
+        period: f32,
+    },
+    // Cubic bezier curve starting at the spawn point, points are relative to it,
+    // the enemy keeps the direction of the curve end once it's done
+    Bezier {
+        points: [[f32; 2]; 3],
+        duration: f32,
+    },
+    // Turns towards the closest spaceship, turn rate is in radians per second.
+    // Gives up after the lifetime in seconds and flies straight on, off the screen
+    Homing {
+        speed: f32,
+        turn_rate: f32,
+        #[serde(default = "default_homing_lifetime")]
+        lifetime: f32,
+    },
+    // Circles around the anchor entity, angular speed is in radians per second
+    Orbit {
+        radius: f32,
+        angular_speed: f32,
+    },
+}
+
+fn default_homing_lifetime() -> f32 {
+    6.0
+}
+
+// Movement pattern of a single enemy together with its progress
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct PatternMovement {
+    pattern: MovementPattern,
+    elapsed: f32,
+    base_velocity: Velocity,
+    // Starting angle of an orbit
+    phase: f32,
+    // Enemy the orbit goes around, orbiting enemies fly straight on once it's gone.
+    // Saved runs store it as an index into their enemy list, see run_save
+    #[serde(skip)]
+    anchor: Option<Entity>,
+}
+
+impl PatternMovement {
+    pub fn new(pattern: MovementPattern, base_velocity: Velocity) -> Self {
+        Self {
+            pattern,
+            elapsed: 0.0,
+            base_velocity,
+            phase: 0.0,
+            anchor: None,
+        }
+    }
+
+    pub fn orbit(radius: f32, angular_speed: f32, anchor: Entity, phase: f32) -> Self {
+        Self {
+            pattern: MovementPattern::Orbit {
+                radius,
+                angular_speed,
+            },
+            elapsed: 0.0,
+            base_velocity: Velocity::new(0.0, 0.0),
+            phase,
+            anchor: Some(anchor),
+        }
+    }
+
+    pub fn anchor(&self) -> Option<Entity> {
+        self.anchor
+    }
+
+    pub fn with_anchor(mut self, anchor: Entity) -> Self {
+        self.anchor = Some(anchor);
+        self
+    }
+
+    // Position on the orbit relative to the anchor
+    pub fn orbit_offset(radius: f32, angle: f32) -> Vec2 {
+        Vec2::new(angle.cos(), angle.sin()) * radius
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
 pub enum Direction {
     Right,
     Left,
@@ -62,3 +167,124 @@ fn apply_velocity(
         tf.translation.y += velocity.y * time.delta_seconds();
     }
 }
+
+fn apply_angular_velocity(mut query: Query<(&mut Transform, &AngularVelocity)>, time: Res<Time>) {
+    for (mut tf, angular_velocity) in query.iter_mut() {
+        tf.rotate_z(angular_velocity.0 * time.delta_seconds());
+    }
+}
+
+fn bezier_velocity(points: &[[f32; 2]; 3], duration: f32, t: f32) -> Vec2 {
+    let [p1, p2, p3] = points.map(Vec2::from_array);
+    let u = (t / duration).clamp(0.0, 1.0);
+
+    // Derivative of the curve starting at zero, scaled from curve time to seconds
+    let derivative = 3.0 * (1.0 - u).powi(2) * p1
+        + 6.0 * (1.0 - u) * u * (p2 - p1)
+        + 3.0 * u.powi(2) * (p3 - p2);
+    derivative / duration
+}
+
+fn apply_movement_patterns(
+    mut commands: Commands,
+    mut query: Query<(Entity, &Transform, &mut Velocity, &mut PatternMovement)>,
+    anchor_query: Query<&Transform, With<Enemy>>,
+    spaceship_query: Query<&Transform, (With<Spaceship>, Without<Downed>)>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_seconds();
+
+    for (entity, tf, mut velocity, mut movement) in query.iter_mut() {
+        movement.elapsed += delta;
+        let t = movement.elapsed;
+        let base = movement.base_velocity;
+
+        match movement.pattern {
+            MovementPattern::Sine {
+                amplitude,
+                frequency,
+            } => {
+                velocity.x = base.x + amplitude * TAU * frequency * (TAU * frequency * t).cos();
+                velocity.y = base.y;
+            }
+            MovementPattern::ZigZag { speed, period } => {
+                let swing = match (t / (period / 2.0)) as u32 % 2 {
+                    0 => speed,
+                    _ => -speed,
+                };
+                velocity.x = base.x + swing;
+                velocity.y = base.y;
+            }
+            MovementPattern::Bezier {
+                ref points,
+                duration,
+            } => {
+                let curve_velocity = bezier_velocity(points, duration, t);
+                velocity.x = base.x + curve_velocity.x;
+                velocity.y = base.y + curve_velocity.y;
+            }
+            MovementPattern::Homing {
+                speed,
+                turn_rate,
+                lifetime,
+            } => {
+                if t >= lifetime {
+                    commands.entity(entity).remove::<PatternMovement>();
+                    continue;
+                }
+
+                let position = tf.translation.truncate();
+                let target = spaceship_query
+                    .iter()
+                    .map(|spaceship_tf| spaceship_tf.translation.truncate())
+                    .min_by(|a, b| {
+                        a.distance_squared(position)
+                            .total_cmp(&b.distance_squared(position))
+                    });
+
+                let current = match Vec2::new(velocity.x, velocity.y).try_normalize() {
+                    Some(direction) => direction,
+                    None => Vec2::NEG_Y,
+                };
+                // Enemy right on top of its target has nowhere to turn to
+                let to_target = target.and_then(|target| (target - position).try_normalize());
+                let direction = match to_target {
+                    Some(to_target) => {
+                        let max_turn = turn_rate * delta;
+                        let turn = current.angle_between(to_target).clamp(-max_turn, max_turn);
+                        Vec2::from_angle(turn).rotate(current)
+                    }
+                    None => current,
+                };
+
+                velocity.x = direction.x * speed;
+                velocity.y = direction.y * speed;
+            }
+            MovementPattern::Orbit {
+                radius,
+                angular_speed,
+            } => {
+                let anchor_tf = movement
+                    .anchor
+                    .and_then(|anchor| anchor_query.get(anchor).ok());
+
+                match anchor_tf {
+                    // Velocity that gets the enemy to its next spot on the orbit
+                    Some(anchor_tf) if delta > 0.0 => {
+                        let angle = movement.phase + angular_speed * t;
+                        let target = anchor_tf.translation.truncate()
+                            + PatternMovement::orbit_offset(radius, angle);
+                        let step = (target - tf.translation.truncate()) / delta;
+
+                        velocity.x = step.x;
+                        velocity.y = step.y;
+                    }
+                    Some(_) => (),
+                    None => {
+                        commands.entity(entity).remove::<PatternMovement>();
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use bevy::{ecs::system::SystemParam, prelude::*, utils::HashSet};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::GameConfig, consts, events::LifeLost, movement::Velocity, transition::ChangeState,
+    GameplayState, Stats, WinSize,
+};
+
+use super::{
+    Downed, Invulnerability, PlayerCount, PlayerId, RunUpgrades, ShipDefinition, Spaceship,
+    SpaceshipHealth,
+};
+
+// Lives of every player, each spaceship only spends its own
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Lives {
+    // Counts the life being played, the player is out once it reaches 0
+    pub remaining: [u32; consts::MAX_PLAYERS],
+    // Index of the next extra life score threshold of every player
+    next_extra_life: [usize; consts::MAX_PLAYERS],
+}
+
+impl Lives {
+    fn reset(&mut self, config: &GameConfig) {
+        self.remaining = [config.lives.lives; consts::MAX_PLAYERS];
+        self.next_extra_life = [0; consts::MAX_PLAYERS];
+    }
+
+    pub fn has_lives(&self, player: PlayerId) -> bool {
+        self.remaining[player.0] > 0
+    }
+}
+
+// Spaceship flying back in from below the screen after a lost life
+#[derive(Component, Debug)]
+#[component(storage = "SparseSet")]
+pub struct Respawning {
+    timer: Timer,
+}
+
+type RespawnQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static PlayerId,
+        &'static ShipDefinition,
+        &'static mut Transform,
+        &'static mut SpaceshipHealth,
+        &'static mut Velocity,
+        &'static mut Sprite,
+    ),
+    With<Spaceship>,
+>;
+
+// Puts spaceships back at their spawn point with full health
+#[derive(SystemParam)]
+pub struct Respawner<'w, 's> {
+    commands: Commands<'w, 's>,
+    spaceship_query: RespawnQuery<'w, 's>,
+    player_count: Res<'w, PlayerCount>,
+    win_size: Res<'w, WinSize>,
+    config: Res<'w, GameConfig>,
+    upgrades: Res<'w, RunUpgrades>,
+}
+
+impl Respawner<'_, '_> {
+    // Only the spaceships of the given players fly back in, the others keep playing
+    fn respawn(&mut self, players: &HashSet<PlayerId>) {
+        for (entity, player, ship, mut tf, mut health, mut velocity, mut sprite) in
+            self.spaceship_query.iter_mut()
+        {
+            if !players.contains(player) {
+                continue;
+            }
+
+            health.0 = self.upgrades.max_health(ship);
+            *velocity = Velocity::new(0.0, 0.0);
+            sprite.color = Color::WHITE;
+
+            tf.translation.x = Spaceship::spawn_x(*player, &self.player_count, self.win_size.w);
+            tf.translation.y = Spaceship::respawn_position(self.win_size.h);
+
+            // Invulnerability lasts through the whole fly in
+            let mut invulnerability = Invulnerability::new(&self.config.player);
+            invulnerability.length += self.config.lives.respawn_time;
+
+            self.commands.entity(entity).remove::<Downed>().insert((
+                invulnerability,
+                Respawning {
+                    timer: Timer::from_seconds(self.config.lives.respawn_time, TimerMode::Once),
+                },
+            ));
+        }
+    }
+}
+
+// ===
+
+pub fn reset_lives(mut lives: ResMut<Lives>, config: Res<GameConfig>) {
+    lives.reset(&config);
+}
+
+pub fn lose_life(
+    mut ev_life_lost: EventReader<LifeLost>,
+    mut ev_change: EventWriter<ChangeState>,
+    mut lives: ResMut<Lives>,
+    player_count: Res<PlayerCount>,
+    mut respawner: Respawner,
+) {
+    // A spaceship going down more than once in the same frame still costs a single life
+    let players: HashSet<PlayerId> = ev_life_lost.iter().map(|life_lost| life_lost.0).collect();
+    if players.is_empty() {
+        return;
+    }
+
+    for player in players.iter() {
+        lives.remaining[player.0] = lives.remaining[player.0].saturating_sub(1);
+    }
+
+    // Players without lives stay down until the run is continued
+    let respawned = players
+        .into_iter()
+        .filter(|player| lives.has_lives(*player))
+        .collect();
+    respawner.respawn(&respawned);
+
+    if (0..player_count.0).all(|idx| !lives.has_lives(PlayerId(idx))) {
+        ev_change.send(ChangeState::to(GameplayState::Continue));
+    }
+}
+
+// Continuing gives back all lives, but the score starts over
+pub fn continue_run(
+    mut ev_change: EventWriter<ChangeState>,
+    mut lives: ResMut<Lives>,
+    mut stats: ResMut<Stats>,
+    player_count: Res<PlayerCount>,
+    mut respawner: Respawner,
+) {
+    lives.reset(&respawner.config);
+    stats.score = 0;
+    stats.player_scores = [0; consts::MAX_PLAYERS];
+
+    respawner.respawn(&(0..player_count.0).map(PlayerId).collect());
+
+    ev_change.send(ChangeState::to(GameplayState::Playing));
+}
+
+// Every player earns extra lives at the thresholds of their own score
+pub fn add_extra_lives(
+    mut lives: ResMut<Lives>,
+    stats: Res<Stats>,
+    player_count: Res<PlayerCount>,
+    config: Res<GameConfig>,
+) {
+    for idx in 0..player_count.0 {
+        let score = stats.player_scores[idx];
+        while let Some(extra_life_score) = config
+            .lives
+            .extra_life_scores
+            .get(lives.next_extra_life[idx])
+        {
+            if score < *extra_life_score {
+                break;
+            }
+
+            lives.remaining[idx] += 1;
+            lives.next_extra_life[idx] += 1;
+        }
+    }
+}
+
+pub fn respawn_fly_in(
+    mut commands: Commands,
+    time: Res<Time>,
+    win_size: Res<WinSize>,
+    mut query: Query<(Entity, &mut Respawning, &mut Transform), With<Spaceship>>,
+) {
+    let start_y = Spaceship::respawn_position(win_size.h);
+    let end_y = Spaceship::player_position(win_size.h);
+
+    for (entity, mut respawning, mut tf) in query.iter_mut() {
+        respawning.timer.tick(time.delta());
+
+        tf.translation.y = start_y + (end_y - start_y) * respawning.timer.percent();
+
+        if respawning.timer.finished() {
+            commands.entity(entity).remove::<Respawning>();
+        }
+    }
+}
@@ -1,33 +1,84 @@
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy::reflect::TypePath;
-use bevy_mod_aseprite::{Aseprite, AsepriteAnimation, AsepriteBundle};
+use bevy_mod_aseprite::{AsepriteAnimation, AsepriteBundle};
 use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::common::EntityType;
+use crate::assets::{AddAssetCollection, AssetCollection, FailedAssets};
+use crate::common::{Collider, EntityType};
+use crate::config::{GameConfig, PlayerConfig};
 use crate::consts;
-use crate::events::EventSet;
+use crate::events::{EventSet, LifeLost};
 use crate::movement::{MovementSet, Velocity};
+use crate::profile::Profile;
+use crate::run_save::RunSnapshot;
+use crate::transition::ChangeState;
 use crate::{is_playing, GameState, WinSize};
 
+mod lives;
 mod movement;
+mod ships;
 mod shoot;
+mod upgrades;
+mod weapon;
+
+pub use lives::{continue_run, Lives, Respawning};
+pub use movement::SpaceshipDash;
+pub use ships::{
+    SelectedShips, ShipDefinition, ShipList, ShipSprites, Ships, ShipsHandle, WeaponSlot,
+};
+pub use shoot::SpaceshipShoot;
+pub use upgrades::{Modifier, RunUpgrades, Stat, StatModifier};
+pub use weapon::{Beam, ChargedShot, Homing, Mine};
 
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(InputManagerPlugin::<SpaceshipAction>::default())
+            .add_asset::<ShipList>()
+            .init_asset_loader::<ships::ShipListLoader>()
+            .register_type::<ShipDefinition>()
+            .register_type::<ships::DashCurve>()
+            .register_type::<weapon::Weapon>()
+            .register_type::<weapon::FirePattern>()
+            .register_type::<weapon::Charge>()
+            .register_type::<weapon::ChargeLevel>()
+            .register_type::<weapon::ProjectilePrefab>()
+            .init_resource::<PlayerCount>()
+            .init_resource::<SelectedShips>()
+            .init_resource::<Lives>()
+            .init_resource::<RunUpgrades>()
+            .add_asset_collection::<ShipsHandle>(GameState::LoadingMenu)
+            // Loaded with the menu, the title screen demo shoots the same projectiles
+            .add_asset_collection::<PlayerHandles>(GameState::LoadingMenu)
+            .add_systems(OnEnter(GameState::LoadingGame), ships::load_ship_assets)
+            .add_systems(OnExit(GameState::LoadingMenu), load_player_asset_dimensions)
             .add_systems(
                 OnEnter(GameState::Gameplay),
-                (load_player_asset_dimensions, spawn_spaceship),
+                (
+                    spawn_spaceship.after(upgrades::reset_upgrades),
+                    lives::reset_lives,
+                    upgrades::reset_upgrades,
+                ),
             )
             .add_systems(
                 Update,
                 (
                     (
                         shoot::spaceship_shoot,
-                        shoot::charged_shot_hit_detection,
+                        shoot::show_charge.after(shoot::spaceship_shoot),
+                        weapon::beam_hit_detection,
+                        weapon::update_beams,
+                        weapon::update_mines,
+                        weapon::steer_homing_projectiles.in_set(MovementSet::UpdateVelocity),
                         spaceship_invincibility,
+                        revive_downed_spaceships,
+                        set_downed_color,
+                        lives::lose_life,
+                        lives::add_extra_lives,
+                        lives::respawn_fly_in,
                     ),
                     movement::spaceship_movement
                         .in_set(MovementSet::UpdateVelocity)
@@ -36,8 +87,20 @@ impl Plugin for PlayerPlugin {
                         .in_set(MovementSet::ApplyVelocity)
                         .after(MovementSet::UpdateVelocity),
                     movement::set_propulsion_position.after(MovementSet::ApplyVelocity),
+                    upgrades::collect_scrap,
                 )
                     .run_if(is_playing),
+            )
+            .add_systems(
+                PreUpdate,
+                weapon::despawn_finished_beams
+                    .after(EventSet::HandleHit)
+                    .before(EventSet::HandleScore)
+                    .run_if(is_playing),
+            )
+            .add_systems(
+                Update,
+                reload_ship_definitions.run_if(in_state(GameState::Gameplay)),
             );
     }
 }
@@ -47,13 +110,53 @@ impl Plugin for PlayerPlugin {
 #[derive(Component, Debug)]
 pub struct SpaceshipHealth(pub u32);
 
+// Which player controls the spaceship, also attached to everything the spaceship owns
+#[derive(Component, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PlayerId(pub usize);
+
+// Amount of spaceships in the run, chosen in the main menu
+#[derive(Resource, Debug)]
+pub struct PlayerCount(pub usize);
+
+impl Default for PlayerCount {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+impl PlayerCount {
+    pub fn is_coop(&self) -> bool {
+        self.0 > 1
+    }
+}
+
 #[derive(Component, Debug)]
 pub struct Spaceship;
 
 impl Spaceship {
+    // Spaceships are spread out evenly along the bottom of the screen
+    pub fn spawn_x(player: PlayerId, player_count: &PlayerCount, window_width: f32) -> f32 {
+        let spacing = window_width / (player_count.0 + 1) as f32;
+        spacing * (player.0 + 1) as f32 - window_width / 2.0
+    }
+
     pub fn player_position(window_height: f32) -> f32 {
         -(window_height / 2.0) * (4.0 / 5.0)
     }
+
+    // Respawned spaceships start right below the screen
+    pub fn respawn_position(window_height: f32) -> f32 {
+        -(window_height / 2.0) - consts::SPAWN_MARGIN
+    }
+
+    // Propulsion is placed right below the spaceship sprite
+    pub fn propulsion_offset(spaceship_height: f32) -> f32 {
+        -spaceship_height + 13.0
+    }
+
+    pub fn propulsion_position(window_height: f32, spaceship_height: f32) -> f32 {
+        Spaceship::player_position(window_height) + Spaceship::propulsion_offset(spaceship_height)
+    }
 }
 
 #[derive(Component, Debug)]
@@ -69,18 +172,39 @@ pub enum SpaceshipAction {
 }
 
 impl SpaceshipAction {
-    fn default_input_map() -> InputMap<SpaceshipAction> {
-        InputMap::new([
-            (KeyCode::A, SpaceshipAction::MoveLeft),
-            (KeyCode::D, SpaceshipAction::MoveRight),
-            (KeyCode::E, SpaceshipAction::DashRight),
-            (KeyCode::Q, SpaceshipAction::DashLeft),
-            (KeyCode::Space, SpaceshipAction::Shoot),
-        ])
+    // First player uses the left side of the keyboard, second one the arrow keys,
+    // each player also gets the gamepad with the same index
+    fn input_map(player: PlayerId) -> InputMap<SpaceshipAction> {
+        let mut input_map = match player.0 {
+            0 => InputMap::new([
+                (KeyCode::A, SpaceshipAction::MoveLeft),
+                (KeyCode::D, SpaceshipAction::MoveRight),
+                (KeyCode::E, SpaceshipAction::DashRight),
+                (KeyCode::Q, SpaceshipAction::DashLeft),
+                (KeyCode::Space, SpaceshipAction::Shoot),
+            ]),
+            _ => InputMap::new([
+                (KeyCode::Left, SpaceshipAction::MoveLeft),
+                (KeyCode::Right, SpaceshipAction::MoveRight),
+                (KeyCode::Period, SpaceshipAction::DashRight),
+                (KeyCode::Comma, SpaceshipAction::DashLeft),
+                (KeyCode::Up, SpaceshipAction::Shoot),
+            ]),
+        };
+
+        input_map
+            .insert(GamepadButtonType::DPadLeft, SpaceshipAction::MoveLeft)
+            .insert(GamepadButtonType::DPadRight, SpaceshipAction::MoveRight)
+            .insert(GamepadButtonType::RightTrigger, SpaceshipAction::DashRight)
+            .insert(GamepadButtonType::LeftTrigger, SpaceshipAction::DashLeft)
+            .insert(GamepadButtonType::South, SpaceshipAction::Shoot)
+            .set_gamepad(Gamepad::new(player.0));
+
+        input_map
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
 pub struct Point {
     pub x: f32,
     pub y: f32,
@@ -94,23 +218,56 @@ pub struct Invulnerability {
 }
 
 impl Invulnerability {
-    pub fn new() -> Self {
+    pub fn new(config: &PlayerConfig) -> Self {
         Self {
-            length: consts::PLAYER_INVULNERABILITY_TIME,
+            length: config.invulnerability_time,
             animation_timer: Timer::from_seconds(
-                consts::PLAYER_INVULNERABILITY_ANIMATION_TIME,
+                config.invulnerability_animation_time,
                 TimerMode::Repeating,
             ),
         }
     }
 }
 
+// Spaceship that ignores every hit, toggled from the dev console
+#[derive(Component, Debug)]
+pub struct GodMode;
+
+// Spaceship that has lost all of its health in co-op,
+// a teammate staying close by brings it back
+#[derive(Component, Debug)]
+#[component(storage = "SparseSet")]
+pub struct Downed {
+    revive_timer: Timer,
+    // Life is lost when nobody comes to the rescue in time
+    bleed_out_timer: Timer,
+}
+
+impl Downed {
+    pub fn new(config: &PlayerConfig) -> Self {
+        Self {
+            revive_timer: Timer::from_seconds(config.revive_time, TimerMode::Once),
+            bleed_out_timer: Timer::from_seconds(config.bleed_out_time, TimerMode::Once),
+        }
+    }
+
+    fn color() -> Color {
+        Color::rgba(0.5, 0.5, 0.5, 0.5)
+    }
+}
+
+// Spaceship that is flying and under control of its player
+type ActiveSpaceship = (With<Spaceship>, Without<Downed>, Without<Respawning>);
+
 #[derive(Bundle)]
 struct SpaceshipBundle {
     spaceship: Spaceship,
+    player: PlayerId,
     entity_type: EntityType,
+    ship: ShipDefinition,
     health: SpaceshipHealth,
     velocity: Velocity,
+    collider: Collider,
     dash: movement::SpaceshipDash,
     shooting: shoot::SpaceshipShoot,
     #[bundle()]
@@ -122,71 +279,173 @@ struct SpaceshipBundle {
 #[derive(Bundle)]
 struct SpaceshipPropulsionBundle {
     spaceship_propulsion: SpaceshipPropulsion,
+    player: PlayerId,
     #[bundle()]
     propulsion: AsepriteBundle,
 }
 
+// Assets shared by every ship, sprites of the ships themselves are in `ShipHandles`
 #[derive(Resource)]
 pub struct PlayerHandles {
-    pub spaceship: Handle<Image>,
     pub projectile: Handle<Image>,
-    pub propulsion: Handle<Aseprite>,
+}
+
+impl AssetCollection for PlayerHandles {
+    fn load(asset_server: &AssetServer) -> Self {
+        Self {
+            projectile: asset_server.load(consts::PLAYER_SPRITE_PROJECTILE),
+        }
+    }
+
+    fn handles(&self) -> Vec<HandleUntyped> {
+        vec![self.projectile.clone_weak_untyped()]
+    }
 }
 
 #[derive(Resource)]
 pub struct PlayerAssetDimensions {
-    pub spaceship: Vec2,
     pub projectile: Vec2,
 }
 
+// Selected ships with the loadout from the hangar and the upgrades of the run
+#[derive(SystemParam)]
+struct Outfitter<'w> {
+    ships: Ships<'w>,
+    profile: Res<'w, Profile>,
+    upgrades: Res<'w, RunUpgrades>,
+}
+
+impl Outfitter<'_> {
+    fn ship(&self, player: PlayerId) -> Option<ShipDefinition> {
+        let ship_list = self.ships.list()?;
+        let ship = self.ships.selected(player)?;
+
+        Some(self.profile.outfit(ship, ship_list))
+    }
+}
+
 // ===
 
 fn spawn_spaceship(
     mut commands: Commands,
-    player_assets: Res<PlayerHandles>,
-    player_dims: Res<PlayerAssetDimensions>,
+    outfitter: Outfitter,
+    ship_sprites: ShipSprites,
     win_size: Res<WinSize>,
-    asesprites: Res<Assets<Aseprite>>,
+    config: Res<GameConfig>,
+    player_count: Res<PlayerCount>,
+    snapshot: Option<Res<RunSnapshot>>,
 ) {
-    let propulsion_handle = &player_assets.propulsion;
-    let propulsion_aseprite = asesprites.get(propulsion_handle).unwrap();
-    let propulsion_animation = AsepriteAnimation::new(propulsion_aseprite.info(), "thrust");
-
-    commands.spawn(SpaceshipBundle {
-        spaceship: Spaceship,
-        entity_type: EntityType::Spaceship,
-        health: SpaceshipHealth(consts::PLAYER_MAX_HEALTH),
-        velocity: Velocity::new(0.0, 0.0),
-        dash: movement::SpaceshipDash::new(),
-        shooting: shoot::SpaceshipShoot::new(),
-        input_manager: InputManagerBundle {
-            input_map: SpaceshipAction::default_input_map(),
-            ..default()
-        },
-        sprite: SpriteBundle {
-            texture: player_assets.spaceship.clone(),
-            transform: Transform::from_xyz(
-                0.,
-                Spaceship::player_position(win_size.h),
-                consts::PLAYER_Z,
+    for player in (0..player_count.0).map(PlayerId) {
+        let Some(ship) = outfitter.ship(player) else {
+            error!("No ship definitions are loaded");
+            return;
+        };
+        let Some((ship_assets, spaceship_size, propulsion_aseprite)) = ship_sprites.get(&ship.id)
+        else {
+            error!("Sprites of ship {} are not loaded", ship.id);
+            continue;
+        };
+
+        let saved_spaceship = snapshot
+            .as_ref()
+            .and_then(|snapshot| snapshot.spaceship(player));
+
+        let (spawn_x, health, dash, shooting) = match saved_spaceship {
+            Some(saved) => (
+                saved.x,
+                saved.health,
+                saved.dash.clone(),
+                saved.shoot.clone(),
             ),
-            ..default()
-        },
-    });
+            None => (
+                Spaceship::spawn_x(player, &player_count, win_size.w),
+                outfitter.upgrades.max_health(&ship),
+                movement::SpaceshipDash::new(),
+                shoot::SpaceshipShoot::new(),
+            ),
+        };
 
-    let transform_y = Spaceship::player_position(win_size.h) - player_dims.spaceship.y + 13.0;
-
-    commands.spawn(SpaceshipPropulsionBundle {
-        spaceship_propulsion: SpaceshipPropulsion,
-        propulsion: AsepriteBundle {
-            texture_atlas: propulsion_aseprite.atlas().clone_weak(),
-            sprite: TextureAtlasSprite::new(propulsion_animation.current_frame()),
-            aseprite: propulsion_handle.clone_weak(),
-            animation: propulsion_animation,
-            transform: Transform::from_xyz(0., transform_y, consts::PLAYER_PROPULSION_Z),
-            ..default()
-        },
-    });
+        let mut spaceship = commands.spawn(SpaceshipBundle {
+            spaceship: Spaceship,
+            player,
+            entity_type: EntityType::Spaceship,
+            ship,
+            health: SpaceshipHealth(health),
+            velocity: Velocity::new(0.0, 0.0),
+            collider: Collider::new(spaceship_size),
+            dash,
+            shooting,
+            input_manager: InputManagerBundle {
+                input_map: SpaceshipAction::input_map(player),
+                ..default()
+            },
+            sprite: SpriteBundle {
+                texture: ship_assets.spaceship.clone(),
+                transform: Transform::from_xyz(
+                    spawn_x,
+                    Spaceship::player_position(win_size.h),
+                    consts::PLAYER_Z,
+                ),
+                ..default()
+            },
+        });
+
+        spaceship.with_children(|parent| {
+            parent.spawn(shoot::ChargeMeterBundle::new(spaceship_size));
+        });
+
+        // Short grace period, so a resumed run doesn't start with a hit
+        if saved_spaceship.is_some() {
+            match health {
+                0 => spaceship.insert(Downed::new(&config.player)),
+                _ => spaceship.insert(Invulnerability::new(&config.player)),
+            };
+        }
+
+        let propulsion_animation = AsepriteAnimation::new(propulsion_aseprite.info(), "thrust");
+        let transform_y = Spaceship::propulsion_position(win_size.h, spaceship_size.y);
+
+        commands.spawn(SpaceshipPropulsionBundle {
+            spaceship_propulsion: SpaceshipPropulsion,
+            player,
+            propulsion: AsepriteBundle {
+                texture_atlas: propulsion_aseprite.atlas().clone_weak(),
+                sprite: TextureAtlasSprite::new(propulsion_animation.current_frame()),
+                aseprite: ship_assets.propulsion.clone_weak(),
+                animation: propulsion_animation,
+                transform: Transform::from_xyz(spawn_x, transform_y, consts::PLAYER_PROPULSION_Z),
+                ..default()
+            },
+        });
+    }
+}
+
+// Edited ship definitions reach the spaceships right away,
+// health is kept but can't go over the new maximum
+fn reload_ship_definitions(
+    mut ev_ship_list: EventReader<AssetEvent<ShipList>>,
+    outfitter: Outfitter,
+    mut spaceship_query: Query<
+        (&PlayerId, &mut ShipDefinition, &mut SpaceshipHealth),
+        With<Spaceship>,
+    >,
+) {
+    let modified = ev_ship_list
+        .iter()
+        .any(|ship_list_ev| matches!(ship_list_ev, AssetEvent::Modified { .. }));
+
+    if !modified {
+        return;
+    }
+
+    for (player, mut ship, mut health) in spaceship_query.iter_mut() {
+        if let Some(reloaded) = outfitter.ship(*player) {
+            health.0 = health.0.min(outfitter.upgrades.max_health(&reloaded));
+            *ship = reloaded;
+        }
+    }
+
+    info!("Applied reloaded ship definitions");
 }
 
 fn spaceship_invincibility(
@@ -194,7 +453,7 @@ fn spaceship_invincibility(
     time: Res<Time>,
     mut query: Query<(Entity, &mut Invulnerability, &mut Sprite), With<Spaceship>>,
 ) {
-    if let Ok((entity, mut invincibility, mut sprite)) = query.get_single_mut() {
+    for (entity, mut invincibility, mut sprite) in query.iter_mut() {
         invincibility.length -= time.delta_seconds();
         invincibility.animation_timer.tick(time.delta());
 
@@ -212,40 +471,90 @@ fn spaceship_invincibility(
     }
 }
 
-pub fn load_player_assets(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let assets = PlayerHandles {
-        spaceship: asset_server.load(consts::PLAYER_SPRITE_SPACESHIP),
-        projectile: asset_server.load(consts::PLAYER_SPRITE_PROJECTILE),
-        propulsion: asset_server.load(consts::PLAYER_ASEPRITE_PROPULSION),
-    };
+// Downed spaceships are revived by a teammate staying within reach,
+// the revive progress resets when the teammate moves away and the
+// spaceship loses a life when it isn't revived before it bleeds out
+fn revive_downed_spaceships(
+    mut commands: Commands,
+    mut ev_life_lost: EventWriter<LifeLost>,
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    lives: Res<Lives>,
+    mut downed_query: Query<
+        (
+            Entity,
+            &PlayerId,
+            &Transform,
+            &mut Downed,
+            &mut SpaceshipHealth,
+            &mut Sprite,
+        ),
+        With<Spaceship>,
+    >,
+    teammate_query: Query<&Transform, (With<Spaceship>, Without<Downed>)>,
+) {
+    for (entity, player, tf, mut downed, mut health, mut sprite) in downed_query.iter_mut() {
+        // Players out of lives stay down until the run is continued
+        if !lives.has_lives(*player) {
+            continue;
+        }
+
+        let teammate_in_reach = teammate_query.iter().any(|teammate_tf| {
+            (teammate_tf.translation.x - tf.translation.x).abs() <= config.player.revive_distance
+        });
 
-    commands.insert_resource(assets);
+        if teammate_in_reach {
+            downed.revive_timer.tick(time.delta());
+        } else {
+            downed.revive_timer.reset();
+            downed.bleed_out_timer.tick(time.delta());
+        }
+
+        // Reset so the life is only lost once while the respawn is on its way
+        if downed.bleed_out_timer.finished() {
+            downed.bleed_out_timer.reset();
+            ev_life_lost.send(LifeLost(*player));
+            continue;
+        }
+
+        if downed.revive_timer.finished() {
+            health.0 = 1;
+            sprite.color = Color::WHITE;
+            commands
+                .entity(entity)
+                .remove::<Downed>()
+                .insert(Invulnerability::new(&config.player));
+        }
+    }
+}
+
+fn set_downed_color(mut query: Query<&mut Sprite, Added<Downed>>) {
+    for mut sprite in query.iter_mut() {
+        sprite.color = Downed::color();
+    }
 }
 
-pub fn load_player_asset_dimensions(
+// Gameplay systems rely on the dimensions, so the game can't go on without them
+fn load_player_asset_dimensions(
     mut commands: Commands,
+    mut ev_change: EventWriter<ChangeState>,
+    mut failed_assets: ResMut<FailedAssets>,
     images: Res<Assets<Image>>,
     player_assets: Res<PlayerHandles>,
-    asesprites: Res<Assets<Aseprite>>,
 ) {
-    let spaceship_size = match images.get(&player_assets.spaceship) {
-        Some(image) => image.size(),
-        None => return,
-    };
     let projectile_size = match images.get(&player_assets.projectile) {
         Some(image) => image.size(),
-        None => return,
-    };
-
-    // TODO create proper asset check method
-    let _propulsion_handle = &player_assets.propulsion;
-    let _propulsion_aseprite = match asesprites.get(&player_assets.propulsion) {
-        Some(aseprite) => aseprite,
-        None => return,
+        None => {
+            error!("Projectile sprite is missing after loading");
+            failed_assets
+                .0
+                .push(consts::PLAYER_SPRITE_PROJECTILE.to_string());
+            ev_change.send(ChangeState::to(GameState::LoadingError));
+            return;
+        }
     };
 
     commands.insert_resource(PlayerAssetDimensions {
-        spaceship: spaceship_size,
         projectile: projectile_size,
     });
 }
@@ -1,24 +1,38 @@
 use crate::{
-    consts,
+    common::Collider,
+    events::SpaceshipDashed,
     movement::{Direction, Velocity},
-    WinSize,
+    run_stats::RunStats,
+    save, WinSize,
 };
 use bevy::prelude::*;
 use leafwing_input_manager::prelude::ActionState;
+use serde::{Deserialize, Serialize};
 
-use super::{Point, Spaceship, SpaceshipAction, SpaceshipPropulsion};
+use super::{
+    ships::{DashCurve, ShipDefinition},
+    upgrades::{RunUpgrades, Stat},
+    ActiveSpaceship, PlayerId, Point, Spaceship, SpaceshipAction, SpaceshipPropulsion,
+};
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Dash {
     direction: Direction,
+    speed: f32,
+    peak_at: f32,
+    end_boost: f32,
+    #[serde(with = "save::timer_serde")]
     timer: Timer,
 }
 
 impl Dash {
-    fn new(direction: Direction) -> Self {
+    fn new(direction: Direction, curve: &DashCurve) -> Self {
         Self {
             direction,
-            timer: Timer::from_seconds(consts::PLAYER_DASH_TIME_LEN, TimerMode::Once),
+            speed: curve.speed,
+            peak_at: curve.peak_at,
+            end_boost: curve.end_boost,
+            timer: Timer::from_seconds(curve.time_len, TimerMode::Once),
         }
     }
 
@@ -29,26 +43,25 @@ impl Dash {
             let elapsed_secs: f32 = self.timer.elapsed_secs();
 
             let boost = {
-                let speed = consts::PLAYER_DASH_SPEED;
-                let dash_time = consts::PLAYER_DASH_TIME_LEN;
-                let half_dash_time = dash_time / 2.0;
+                let speed = self.speed;
+                let dash_time = self.timer.duration().as_secs_f32();
+                let peak_time = dash_time * self.peak_at;
 
-                let parabola_max =
-                    -speed * half_dash_time.powi(2) + speed * dash_time * half_dash_time;
+                let parabola_max = speed * peak_time.powi(2);
 
-                if elapsed_secs < half_dash_time {
+                if elapsed_secs < peak_time {
                     // parabola: -A*x^2 + B*x
                     // A & B - constants
                     // x - time
-                    -speed * elapsed_secs.powi(2) + speed * dash_time * elapsed_secs
+                    -speed * elapsed_secs.powi(2) + speed * 2.0 * peak_time * elapsed_secs
                 } else {
                     let p1 = Point {
-                        x: half_dash_time,
+                        x: peak_time,
                         y: parabola_max,
                     };
                     let p2 = Point {
                         x: dash_time,
-                        y: -parabola_max / 2.0,
+                        y: parabola_max * self.end_boost,
                     };
 
                     let slope = (p2.y - p1.y) / (p2.x - p1.x);
@@ -71,11 +84,11 @@ impl Dash {
     }
 }
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 enum DashState {
     Idle,
     Dashing(Dash),
-    Cooldown(Timer),
+    Cooldown(#[serde(with = "save::timer_serde")] Timer),
 }
 
 impl DashState {
@@ -84,7 +97,7 @@ impl DashState {
     }
 }
 
-#[derive(Component, Debug)]
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
 pub struct SpaceshipDash {
     state: DashState,
 }
@@ -103,14 +116,18 @@ pub fn spaceship_movement(
     mut player_query: Query<
         (
             &ActionState<SpaceshipAction>,
+            &ShipDefinition,
             &mut Velocity,
             &mut SpaceshipDash,
         ),
-        With<Spaceship>,
+        ActiveSpaceship,
     >,
+    mut ev_dashed: EventWriter<SpaceshipDashed>,
+    mut run_stats: ResMut<RunStats>,
+    upgrades: Res<RunUpgrades>,
     time: Res<Time>,
 ) {
-    if let Ok((action_state, mut velocity, mut spaceship_dash)) = player_query.get_single_mut() {
+    for (action_state, ship, mut velocity, mut spaceship_dash) in player_query.iter_mut() {
         if spaceship_dash.state.is_idle() {
             let direction = {
                 if action_state.just_pressed(SpaceshipAction::DashRight) {
@@ -123,18 +140,20 @@ pub fn spaceship_movement(
             };
 
             if let Some(d) = direction {
-                spaceship_dash.state = DashState::Dashing(Dash::new(d));
+                spaceship_dash.state = DashState::Dashing(Dash::new(d, &ship.dash));
+                run_stats.dashes += 1;
+                ev_dashed.send(SpaceshipDashed);
             }
         }
 
         match &mut spaceship_dash.state {
             DashState::Idle => {
                 if action_state.pressed(SpaceshipAction::MoveRight) {
-                    velocity.x += consts::PLAYER_MOVEMENT_SPEED;
+                    velocity.x += ship.movement_speed;
                 }
 
                 if action_state.pressed(SpaceshipAction::MoveLeft) {
-                    velocity.x -= consts::PLAYER_MOVEMENT_SPEED;
+                    velocity.x -= ship.movement_speed;
                 }
             }
             DashState::Dashing(ref mut dash) => match dash.calc_boost(&time) {
@@ -143,7 +162,7 @@ pub fn spaceship_movement(
                 }
                 None => {
                     spaceship_dash.state = DashState::Cooldown(Timer::from_seconds(
-                        consts::PLAYER_DASH_COOLDOWN,
+                        upgrades.apply(Stat::DashCooldown, ship.dash.cooldown),
                         TimerMode::Once,
                     ));
                 }
@@ -164,13 +183,13 @@ pub fn apply_spaceship_velocity(
     time: Res<Time>,
     win_size: Res<WinSize>,
 ) {
-    if let Ok((mut tf, velocity)) = player_query.get_single_mut() {
+    for (mut tf, velocity) in player_query.iter_mut() {
         let w_bound = win_size.w / 2.;
         if (-w_bound > tf.translation.x && velocity.x < 0.)
             || (w_bound < tf.translation.x && velocity.x > 0.)
         {
             // TODO ships velocity should get back to 0 faster when on the border
-            return;
+            continue;
         }
 
         tf.translation.x += velocity.x * time.delta_seconds();
@@ -178,12 +197,20 @@ pub fn apply_spaceship_velocity(
 }
 
 pub fn set_propulsion_position(
-    mut player_query: Query<&mut Transform, With<Spaceship>>,
-    mut propulsion_query: Query<&mut Transform, (With<SpaceshipPropulsion>, Without<Spaceship>)>,
+    player_query: Query<(&Transform, &PlayerId, &Collider), With<Spaceship>>,
+    mut propulsion_query: Query<
+        (&mut Transform, &PlayerId),
+        (With<SpaceshipPropulsion>, Without<Spaceship>),
+    >,
 ) {
-    if let Ok(mut tf_propulsion) = propulsion_query.get_single_mut() {
-        if let Ok(tf_player) = player_query.get_single_mut() {
+    for (mut tf_propulsion, propulsion_player) in propulsion_query.iter_mut() {
+        if let Some((tf_player, _, collider)) = player_query
+            .iter()
+            .find(|(_, player, _)| *player == propulsion_player)
+        {
             tf_propulsion.translation.x = tf_player.translation.x;
+            tf_propulsion.translation.y =
+                tf_player.translation.y + Spaceship::propulsion_offset(collider.size.y);
         }
     }
 }
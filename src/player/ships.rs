@@ -0,0 +1,218 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    ecs::system::SystemParam,
+    prelude::*,
+    reflect::{TypePath, TypeUuid},
+    utils::{BoxedFuture, HashMap},
+};
+use bevy_mod_aseprite::Aseprite;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    assets::{AssetCollection, LoadingAssets},
+    consts,
+};
+
+use super::{weapon::Weapon, PlayerId};
+
+// Shape of the dash boost over time, the boost rises along a parabola
+// up to its peak and then falls off linearly until the dash ends
+#[derive(Reflect, Deserialize, Clone, Debug)]
+pub struct DashCurve {
+    pub speed: f32,
+    pub time_len: f32,
+    pub cooldown: f32,
+    // Share of the dash time at which the boost peaks
+    pub peak_at: f32,
+    // Boost at the end of the dash as a share of the peak,
+    // negative values brake the spaceship
+    pub end_boost: f32,
+}
+
+// Playable spaceship, also attached to the spawned spaceship
+// so its systems can read the loadout and it can be tuned in the inspector
+#[derive(Component, Reflect, Deserialize, Clone, Debug)]
+pub struct ShipDefinition {
+    pub id: String,
+    // Keys of the name and the description in the string tables
+    pub name: String,
+    pub description: String,
+    pub sprite: String,
+    pub propulsion: String,
+    pub max_health: u32,
+    pub movement_speed: f32,
+    pub dash: DashCurve,
+    pub primary: Weapon,
+    pub secondary: Weapon,
+    // Credits it costs in the hangar, free ships are available from the start
+    #[serde(default)]
+    pub price: u32,
+    // Sprite tint, replaced by the palette equipped in the hangar
+    #[serde(default = "default_ship_color")]
+    pub color: [f32; 3],
+}
+
+fn default_ship_color() -> [f32; 3] {
+    [1.0, 1.0, 1.0]
+}
+
+impl ShipDefinition {
+    pub fn color(&self) -> Color {
+        Color::rgb(self.color[0], self.color[1], self.color[2])
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WeaponSlot {
+    Primary,
+    Secondary,
+}
+
+// Weapon bought in the hangar, replaces the weapon in its slot of any ship
+#[derive(Deserialize, Clone, Debug)]
+pub struct WeaponUnlock {
+    pub id: String,
+    pub price: u32,
+    pub slot: WeaponSlot,
+    pub weapon: Weapon,
+}
+
+// Cosmetic ship color bought in the hangar
+#[derive(Deserialize, Clone, Debug)]
+pub struct Palette {
+    pub id: String,
+    // Key of the name in the string tables
+    pub name: String,
+    pub price: u32,
+    pub color: [f32; 3],
+}
+
+// Ship definitions, loaded from the assets folder
+#[derive(Deserialize, TypeUuid, TypePath, Debug)]
+#[uuid = "2c7d9f40-1e6b-4a35-8d9c-5b0e3f7a1c64"]
+pub struct ShipList {
+    pub ships: Vec<ShipDefinition>,
+    #[serde(default)]
+    pub weapons: Vec<WeaponUnlock>,
+    #[serde(default)]
+    pub palettes: Vec<Palette>,
+}
+
+#[derive(Resource)]
+pub struct ShipsHandle(pub Handle<ShipList>);
+
+impl AssetCollection for ShipsHandle {
+    fn load(asset_server: &AssetServer) -> Self {
+        ShipsHandle(asset_server.load(consts::SHIPS))
+    }
+
+    fn handles(&self) -> Vec<HandleUntyped> {
+        vec![self.0.clone_weak_untyped()]
+    }
+}
+
+#[derive(Default)]
+pub struct ShipListLoader;
+
+impl AssetLoader for ShipListLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let ship_list = ron::de::from_bytes::<ShipList>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(ship_list));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ships.ron"]
+    }
+}
+
+// Index of the ship every player picked on the ship select screen
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SelectedShips(pub [usize; consts::MAX_PLAYERS]);
+
+pub struct ShipAssets {
+    pub spaceship: Handle<Image>,
+    pub propulsion: Handle<Aseprite>,
+}
+
+// Sprites of every ship by ship id, loaded with the rest of the gameplay assets
+#[derive(Resource, Default)]
+pub struct ShipHandles(pub HashMap<String, ShipAssets>);
+
+#[derive(SystemParam)]
+pub struct Ships<'w> {
+    ships_handle: Res<'w, ShipsHandle>,
+    ship_lists: Res<'w, Assets<ShipList>>,
+    selected_ships: Res<'w, SelectedShips>,
+}
+
+impl<'w> Ships<'w> {
+    pub fn list(&self) -> Option<&ShipList> {
+        self.ship_lists.get(&self.ships_handle.0)
+    }
+
+    pub fn all(&self) -> &[ShipDefinition] {
+        match self.list() {
+            Some(ship_list) => &ship_list.ships,
+            None => &[],
+        }
+    }
+
+    // Falls back to the first ship if the list has changed since the selection
+    pub fn selected(&self, player: PlayerId) -> Option<&ShipDefinition> {
+        let ships = self.all();
+        ships
+            .get(self.selected_ships.0[player.0])
+            .or_else(|| ships.first())
+    }
+}
+
+// Loaded sprites of the ships, with the size of the spaceship sprite
+#[derive(SystemParam)]
+pub struct ShipSprites<'w> {
+    ship_handles: Res<'w, ShipHandles>,
+    images: Res<'w, Assets<Image>>,
+    aseprites: Res<'w, Assets<Aseprite>>,
+}
+
+impl<'w> ShipSprites<'w> {
+    pub fn get(&self, ship_id: &str) -> Option<(&ShipAssets, Vec2, &Aseprite)> {
+        let ship_assets = self.ship_handles.0.get(ship_id)?;
+        let image = self.images.get(&ship_assets.spaceship)?;
+        let aseprite = self.aseprites.get(&ship_assets.propulsion)?;
+
+        Some((ship_assets, image.size(), aseprite))
+    }
+}
+
+// ===
+
+pub fn load_ship_assets(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut loading_assets: ResMut<LoadingAssets>,
+    ships: Ships,
+) {
+    let mut ship_handles = ShipHandles::default();
+
+    for ship in ships.all() {
+        let ship_assets = ShipAssets {
+            spaceship: asset_server.load(ship.sprite.as_str()),
+            propulsion: asset_server.load(ship.propulsion.as_str()),
+        };
+
+        loading_assets.add_handles([
+            ship_assets.spaceship.clone_weak_untyped(),
+            ship_assets.propulsion.clone_weak_untyped(),
+        ]);
+        ship_handles.0.insert(ship.id.clone(), ship_assets);
+    }
+
+    commands.insert_resource(ship_handles);
+}
@@ -1,39 +1,32 @@
-use bevy::{math::Vec3Swizzles, prelude::*, sprite::collide_aabb::collide, utils::HashSet};
+use bevy::{prelude::*, sprite::Anchor};
 use leafwing_input_manager::prelude::ActionState;
+use serde::{Deserialize, Serialize};
 
-use crate::{
-    common::{AsteroidType, EntityType, ProjectileBundle, ProjectileSource},
-    consts,
-    enemy::Enemy,
-    events::{DespawnEntity, SplitAsteroid},
-    movement::Velocity,
-};
+use crate::{consts, run_stats::RunStats, save};
 
-use super::{PlayerHandles, Spaceship, SpaceshipAction};
+use super::{
+    ships::ShipDefinition,
+    upgrades::{RunUpgrades, Stat},
+    weapon::{ChargeLevel, FireContext, Weapon},
+    ActiveSpaceship, Downed, PlayerAssetDimensions, PlayerHandles, PlayerId, Spaceship,
+    SpaceshipAction,
+};
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 enum ShootingState {
     Idle,
-    Charging(Timer),
-    Shooting(EntityType),
-    Cooldown(Timer),
+    // Seconds the shoot button has been held
+    Charging(f32),
+    Cooldown(#[serde(with = "save::timer_serde")] Timer),
 }
 
 impl ShootingState {
     fn is_idle(&self) -> bool {
         matches!(self, ShootingState::Idle)
     }
-
-    fn is_charging_finished(&self) -> bool {
-        if let ShootingState::Charging(timer) = self {
-            timer.finished()
-        } else {
-            false
-        }
-    }
 }
 
-#[derive(Component, Debug)]
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
 pub struct SpaceshipShoot {
     state: ShootingState,
 }
@@ -44,57 +37,42 @@ impl SpaceshipShoot {
             state: ShootingState::Idle,
         }
     }
-}
-
-#[derive(Component, Debug)]
-pub struct ChargedShot;
-
-#[derive(Component, Clone, Copy, Debug)]
-pub struct DamageArea {
-    width: f32,
-    height: f32,
-}
 
-impl DamageArea {
-    pub fn new() -> Self {
-        Self {
-            width: consts::PLAYER_CHARGE_SHOT_WIDTH,
-            height: consts::PLAYER_CHARGE_SHOT_HEIGHT,
+    pub fn charge_secs(&self) -> Option<f32> {
+        match self.state {
+            ShootingState::Charging(secs) => Some(secs),
+            _ => None,
         }
     }
-
-    pub fn xy(&self) -> Vec2 {
-        Vec2::new(self.width, self.height)
-    }
 }
 
+// Charge progress shown right above the spaceship
+#[derive(Component, Debug)]
+pub struct ChargeMeter;
+
 #[derive(Bundle)]
-struct ChargedShotBundle {
-    charged_shot: ChargedShot,
-    damage_area: DamageArea,
+pub struct ChargeMeterBundle {
+    charge_meter: ChargeMeter,
     #[bundle()]
     sprite: SpriteBundle,
 }
 
-impl ChargedShotBundle {
-    fn new(spaceship_tf: Vec2) -> Self {
-        let damage_area = DamageArea::new();
-        let spawn_point = Vec3::new(
-            spaceship_tf.x,
-            spaceship_tf.y + damage_area.height / 2.0,
-            consts::PLAYER_CHARGE_SHOT_Z,
-        );
-
-        ChargedShotBundle {
-            charged_shot: ChargedShot,
-            damage_area,
+impl ChargeMeterBundle {
+    pub fn new(spaceship_size: Vec2) -> Self {
+        Self {
+            charge_meter: ChargeMeter,
             sprite: SpriteBundle {
                 sprite: Sprite {
-                    color: Color::rgb(0.9, 0.9, 0.9),
-                    custom_size: Some(Vec2::new(damage_area.width, damage_area.height)),
+                    custom_size: Some(consts::PLAYER_CHARGE_METER_SIZE),
+                    anchor: Anchor::CenterLeft,
                     ..default()
                 },
-                transform: Transform::from_translation(spawn_point),
+                transform: Transform::from_xyz(
+                    -consts::PLAYER_CHARGE_METER_SIZE.x / 2.0,
+                    spaceship_size.y / 2.0 + consts::PLAYER_CHARGE_METER_SIZE.y * 2.0,
+                    1.0,
+                ),
+                visibility: Visibility::Hidden,
                 ..default()
             },
         }
@@ -103,70 +81,59 @@ impl ChargedShotBundle {
 
 // ===
 
+// Holding the shoot button charges the secondary weapon, releasing it fires
+// the secondary weapon at the reached charge level and the primary weapon otherwise
 pub fn spaceship_shoot(
     mut commands: Commands,
     mut player_query: Query<
         (
+            Entity,
             &ActionState<SpaceshipAction>,
             &Transform,
+            &PlayerId,
+            &ShipDefinition,
             &mut SpaceshipShoot,
         ),
-        With<Spaceship>,
+        ActiveSpaceship,
     >,
+    mut run_stats: ResMut<RunStats>,
+    upgrades: Res<RunUpgrades>,
     time: Res<Time>,
     player_assets: Res<PlayerHandles>,
+    player_dims: Res<PlayerAssetDimensions>,
 ) {
-    if let Ok((action_state, tf, mut spaceship_shoot)) = player_query.get_single_mut() {
+    for (entity, action_state, tf, player, ship, mut spaceship_shoot) in player_query.iter_mut() {
         if spaceship_shoot.state.is_idle() && action_state.just_pressed(SpaceshipAction::Shoot) {
-            spaceship_shoot.state = ShootingState::Charging(Timer::from_seconds(
-                consts::PLAYER_CHARGE_SHOT_CHARGING_TIME,
-                TimerMode::Once,
-            ));
+            spaceship_shoot.state = ShootingState::Charging(0.0);
         }
 
-        if action_state.just_released(SpaceshipAction::Shoot) {
-            if spaceship_shoot.state.is_charging_finished() {
-                spaceship_shoot.state = ShootingState::Shooting(EntityType::ChargedShot);
-            } else {
-                spaceship_shoot.state = ShootingState::Shooting(EntityType::Projectile);
+        let charge = &ship.secondary.charge;
+        let fired_weapon: Option<(&Weapon, Option<&ChargeLevel>)> = match spaceship_shoot.state {
+            ShootingState::Charging(secs) if !action_state.pressed(SpaceshipAction::Shoot) => {
+                match charge.level(secs) {
+                    Some((_, level)) => {
+                        run_stats.charged_shots += 1;
+                        Some((&ship.secondary, Some(level)))
+                    }
+                    None => Some((&ship.primary, None)),
+                }
             }
-        }
+            _ => None,
+        };
 
         match &mut spaceship_shoot.state {
             ShootingState::Idle => (),
-            ShootingState::Charging(ref mut timer) => {
-                timer.tick(time.delta());
-            }
-            ShootingState::Shooting(ref entity_type) => match entity_type {
-                EntityType::Projectile => {
-                    let projectile_bundle = ProjectileBundle::new(
-                        EntityType::Projectile,
-                        Velocity {
-                            x: 0.,
-                            y: consts::PLAYER_PROJECTILE_SPEED,
-                        },
-                        tf.translation * Vec2::ONE.extend(consts::PLAYER_PROJECTILE_Z),
-                        player_assets.projectile.clone(),
-                        ProjectileSource::FromSpaceship,
-                    );
-                    commands.spawn(projectile_bundle);
+            ShootingState::Charging(ref mut secs) => {
+                *secs += time.delta_seconds();
 
+                // Charge is lost and the weapon has to cool down for longer
+                if charge.is_overheated(*secs) {
                     spaceship_shoot.state = ShootingState::Cooldown(Timer::from_seconds(
-                        consts::PLAYER_FIRING_COOLDOWN,
+                        charge.overheat_cooldown,
                         TimerMode::Once,
                     ));
                 }
-                EntityType::ChargedShot => {
-                    let charge_shot_bundle = ChargedShotBundle::new(tf.translation.truncate());
-                    commands.spawn(charge_shot_bundle);
-
-                    spaceship_shoot.state = ShootingState::Cooldown(Timer::from_seconds(
-                        consts::PLAYER_CHARGE_SHOT_COOLDOWN,
-                        TimerMode::Once,
-                    ));
-                }
-                _ => (),
-            },
+            }
             ShootingState::Cooldown(ref mut timer) => {
                 timer.tick(time.delta());
 
@@ -175,60 +142,58 @@ pub fn spaceship_shoot(
                 }
             }
         }
+
+        if let Some((weapon, charge_level)) = fired_weapon {
+            weapon.fire(
+                &mut commands,
+                &FireContext {
+                    spaceship: entity,
+                    player: *player,
+                    translation: tf.translation,
+                    player_assets: &player_assets,
+                    player_dims: &player_dims,
+                    charge_level,
+                    charge_size: upgrades.apply(Stat::ChargeSize, 1.0),
+                },
+            );
+
+            let cooldown = upgrades.apply(Stat::FireCooldown, weapon.cooldown);
+            spaceship_shoot.state =
+                ShootingState::Cooldown(Timer::from_seconds(cooldown, TimerMode::Once));
+        }
     }
 }
 
-pub fn charged_shot_hit_detection(
-    mut ev_despawn: EventWriter<DespawnEntity>,
-    mut ev_split_asteroid: EventWriter<SplitAsteroid>,
-    charged_shot_query: Query<(Entity, &Transform, &DamageArea), With<ChargedShot>>,
-    enemy_query: Query<(Entity, &Transform, &Sprite, &EntityType, &Velocity), With<Enemy>>,
+// Spaceship glows in the color of the reached charge level,
+// alpha is left alone since it's used by the invulnerability blinking
+pub fn show_charge(
+    mut spaceship_query: Query<
+        (&SpaceshipShoot, &ShipDefinition, &Children, &mut Sprite),
+        (With<Spaceship>, Without<Downed>),
+    >,
+    mut meter_query: Query<(&mut Sprite, &mut Visibility), (With<ChargeMeter>, Without<Spaceship>)>,
 ) {
-    let mut processed_entities: HashSet<Entity> = HashSet::new();
-
-    if let Ok((charged_shot_entity, charged_shot_tf, damage_area)) = charged_shot_query.get_single()
-    {
-        for (enemy_entity, enemy_tf, enemy_sprite, enemy_type, enemy_velocity) in enemy_query.iter()
-        {
-            if processed_entities.contains(&enemy_entity) {
-                continue;
-            }
-
-            let enemy_size = match enemy_sprite.custom_size {
-                Some(size) => size * enemy_tf.scale.xy(),
-                None => panic!("Enemy sprite has no custom size"),
-            };
-
-            let collision = collide(
-                charged_shot_tf.translation,
-                damage_area.xy(),
-                enemy_tf.translation,
-                enemy_size,
-            );
-
-            if collision.is_some() {
-                processed_entities.insert(enemy_entity);
-                ev_despawn.send(DespawnEntity {
-                    entity: enemy_entity,
-                    entity_type: *enemy_type,
-                });
-
-                if let EntityType::Asteroid(asteroid) = enemy_type {
-                    if !matches!(asteroid.asteroid_type, AsteroidType::Small) {
-                        ev_split_asteroid.send(SplitAsteroid::new(
-                            enemy_tf.translation,
-                            enemy_size,
-                            *enemy_velocity,
-                            *asteroid,
-                        ));
+    for (spaceship_shoot, ship, children, mut sprite) in spaceship_query.iter_mut() {
+        let charge = &ship.secondary.charge;
+        let charge_secs = spaceship_shoot.charge_secs();
+        let glow = charge_secs.map_or(ship.color(), |secs| charge.color(secs));
+
+        sprite.color = glow.with_a(sprite.color.a());
+
+        for &child in children.iter() {
+            if let Ok((mut meter_sprite, mut visibility)) = meter_query.get_mut(child) {
+                match charge_secs {
+                    Some(secs) => {
+                        meter_sprite.color = glow;
+                        meter_sprite.custom_size = Some(
+                            consts::PLAYER_CHARGE_METER_SIZE
+                                * Vec2::new(charge.progress(secs), 1.0),
+                        );
+                        *visibility = Visibility::Visible;
                     }
+                    None => *visibility = Visibility::Hidden,
                 }
             }
         }
-
-        ev_despawn.send(DespawnEntity {
-            entity: charged_shot_entity,
-            entity_type: EntityType::ChargedShot,
-        });
     }
 }
@@ -0,0 +1,113 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::GameConfig,
+    events::{AddScore, AddScoreType},
+    profile::Profile,
+};
+
+use super::ShipDefinition;
+
+// Ship stats that upgrades can change
+#[derive(Reflect, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stat {
+    MaxHealth,
+    // Cooldown of both weapons after firing
+    FireCooldown,
+    DashCooldown,
+    // Size of charged shots
+    ChargeSize,
+}
+
+#[derive(Reflect, Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum Modifier {
+    Add(f32),
+    Multiply(f32),
+}
+
+#[derive(Reflect, Serialize, Deserialize, Clone, Debug)]
+pub struct StatModifier {
+    pub stat: Stat,
+    pub modifier: Modifier,
+}
+
+// Upgrades bought during the run, shared by every spaceship. Ship definitions are
+// left untouched, stats are read through the modifier stack instead
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct RunUpgrades {
+    // Currency for the shop, earned by destroying enemies
+    pub scrap: u32,
+    // Ids of the bought upgrades, once for every purchase
+    bought: Vec<String>,
+    modifiers: Vec<StatModifier>,
+}
+
+impl RunUpgrades {
+    pub fn times_bought(&self, id: &str) -> u32 {
+        self.bought.iter().filter(|bought| *bought == id).count() as u32
+    }
+
+    pub fn add(&mut self, id: &str, modifiers: &[StatModifier]) {
+        self.bought.push(id.to_string());
+        self.modifiers.extend_from_slice(modifiers);
+    }
+
+    // Starting upgrades from the hangar don't raise the shop prices
+    pub fn add_starting(&mut self, modifiers: &[StatModifier]) {
+        self.modifiers.extend_from_slice(modifiers);
+    }
+
+    // Additions are applied before multipliers, whatever order they were bought in
+    pub fn apply(&self, stat: Stat, base: f32) -> f32 {
+        let modifiers = self
+            .modifiers
+            .iter()
+            .filter(|stat_modifier| stat_modifier.stat == stat);
+
+        let added: f32 = modifiers
+            .clone()
+            .map(|stat_modifier| match stat_modifier.modifier {
+                Modifier::Add(amount) => amount,
+                Modifier::Multiply(_) => 0.0,
+            })
+            .sum();
+        let multiplied: f32 = modifiers
+            .map(|stat_modifier| match stat_modifier.modifier {
+                Modifier::Add(_) => 1.0,
+                Modifier::Multiply(factor) => factor,
+            })
+            .product();
+
+        (base + added) * multiplied
+    }
+
+    pub fn max_health(&self, ship: &ShipDefinition) -> u32 {
+        self.apply(Stat::MaxHealth, ship.max_health as f32)
+            .round()
+            .max(1.0) as u32
+    }
+}
+
+// ===
+
+pub fn reset_upgrades(
+    mut upgrades: ResMut<RunUpgrades>,
+    profile: Res<Profile>,
+    config: Res<GameConfig>,
+) {
+    *upgrades = profile.starting_upgrades(&config);
+}
+
+// Every destroyed enemy is worth its base points in scrap, combos don't count
+pub fn collect_scrap(
+    mut upgrades: ResMut<RunUpgrades>,
+    mut ev_add_score: EventReader<AddScore>,
+    config: Res<GameConfig>,
+) {
+    for add_score_ev in ev_add_score.iter() {
+        if let AddScoreType::EnemyDestroyed(entity_type) = add_score_ev.score_type {
+            upgrades.scrap += config.score.points(&entity_type);
+        }
+    }
+}
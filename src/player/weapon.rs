@@ -0,0 +1,590 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{prelude::*, sprite::collide_aabb::collide, utils::HashSet};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::{Collider, Damage, EntityType, ProjectileBundle, ProjectileSource},
+    consts,
+    enemy::Enemy,
+    events::{AddScore, EnemyIsHit},
+    movement::Velocity,
+};
+
+use super::{PlayerAssetDimensions, PlayerHandles, PlayerId, Spaceship};
+
+// How a weapon fires, every pattern spawns its own kind of shot
+#[derive(Reflect, Deserialize, Clone, Debug)]
+pub enum FirePattern {
+    // Single projectile flying straight up
+    Single {
+        speed: f32,
+    },
+    // Fan of projectiles spread over the angle, in degrees
+    Spread {
+        speed: f32,
+        count: u32,
+        angle: f32,
+    },
+    // Missiles that turn towards the closest enemy, turn rate is in radians per second
+    Homing {
+        speed: f32,
+        count: u32,
+        turn_rate: f32,
+    },
+    // Beam across the whole screen that follows the spaceship and fades out at the end
+    Beam {
+        width: f32,
+        duration: f32,
+    },
+    // Mine left in place that explodes on contact or once its lifetime runs out
+    Mine {
+        lifetime: f32,
+        radius: f32,
+    },
+}
+
+// Looks of the projectiles, every weapon shares the same projectile sprite
+#[derive(Reflect, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ProjectilePrefab {
+    pub scale: f32,
+    pub color: [f32; 3],
+}
+
+impl Default for ProjectilePrefab {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            color: [1.0, 1.0, 1.0],
+        }
+    }
+}
+
+impl ProjectilePrefab {
+    fn color(&self) -> Color {
+        Color::rgb(self.color[0], self.color[1], self.color[2])
+    }
+}
+
+// Damage and size of a shot charged for at least the time of the level
+#[derive(Reflect, Deserialize, Clone, Debug)]
+pub struct ChargeLevel {
+    pub time: f32,
+    pub damage: f32,
+    pub size: f32,
+}
+
+// Levels are sorted by time, the shoot button has to be held
+// for the time of the first level before the weapon fires at all
+#[derive(Reflect, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct Charge {
+    pub levels: Vec<ChargeLevel>,
+    // Holding the charge for this long overheats the weapon,
+    // the charge is lost and the weapon cools down for longer
+    pub overheat_time: Option<f32>,
+    pub overheat_cooldown: f32,
+}
+
+impl Charge {
+    pub fn level(&self, secs: f32) -> Option<(usize, &ChargeLevel)> {
+        self.levels
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, level)| secs >= level.time)
+    }
+
+    pub fn is_overheated(&self, secs: f32) -> bool {
+        self.overheat_time.is_some_and(|time| secs >= time)
+    }
+
+    // Share of the charge until the weapon overheats, or until the last level
+    pub fn progress(&self, secs: f32) -> f32 {
+        let full_time = self
+            .overheat_time
+            .or_else(|| self.levels.last().map(|level| level.time))
+            .unwrap_or(0.0);
+
+        match full_time > 0.0 {
+            true => (secs / full_time).min(1.0),
+            false => 0.0,
+        }
+    }
+
+    // Glow of the spaceship and color of the charge meters
+    pub fn color(&self, secs: f32) -> Color {
+        let past_last_level = self.levels.last().is_some_and(|level| secs >= level.time);
+
+        match self.level(secs) {
+            _ if past_last_level && self.overheat_time.is_some() => Color::rgb(1.0, 0.3, 0.2),
+            Some((0, _)) => Color::rgb(0.6, 0.9, 1.0),
+            Some((1, _)) => Color::rgb(0.4, 0.6, 1.0),
+            Some(_) => Color::rgb(1.0, 0.5, 1.0),
+            None => Color::WHITE,
+        }
+    }
+}
+
+#[derive(Reflect, Deserialize, Clone, Debug)]
+pub struct Weapon {
+    // Key of the name in the string tables
+    pub name: String,
+    pub cooldown: f32,
+    pub damage: u32,
+    // Only used by the secondary weapon
+    #[serde(default)]
+    pub charge: Charge,
+    #[serde(default)]
+    pub projectile: ProjectilePrefab,
+    pub pattern: FirePattern,
+}
+
+// Everything a weapon needs to know about the spaceship firing it
+pub struct FireContext<'a> {
+    pub spaceship: Entity,
+    pub player: PlayerId,
+    pub translation: Vec3,
+    pub player_assets: &'a PlayerHandles,
+    pub player_dims: &'a PlayerAssetDimensions,
+    // Reached charge level, if the weapon was charged
+    pub charge_level: Option<&'a ChargeLevel>,
+    // Size multiplier of charged shots from upgrades
+    pub charge_size: f32,
+}
+
+impl<'a> FireContext<'a> {
+    fn size(&self) -> f32 {
+        self.charge_level
+            .map_or(1.0, |level| level.size * self.charge_size)
+    }
+}
+
+impl Weapon {
+    fn damage(&self, ctx: &FireContext) -> Damage {
+        match ctx.charge_level {
+            Some(level) => Damage((self.damage as f32 * level.damage).round() as u32),
+            None => Damage(self.damage),
+        }
+    }
+
+    pub fn fire(&self, commands: &mut Commands, ctx: &FireContext) {
+        let shots: Vec<Entity> = match self.pattern {
+            FirePattern::Single { speed } => {
+                vec![self
+                    .spawn_projectile(commands, ctx, Vec2::new(0.0, speed))
+                    .id()]
+            }
+            FirePattern::Spread {
+                speed,
+                count,
+                angle,
+            } => fan_directions(count, angle)
+                .map(|direction| self.spawn_projectile(commands, ctx, direction * speed).id())
+                .collect(),
+            FirePattern::Homing {
+                speed,
+                count,
+                turn_rate,
+            } => {
+                // Missiles leave in a wide fan and then find their targets
+                fan_directions(count, 90.0)
+                    .map(|direction| {
+                        self.spawn_projectile(commands, ctx, direction * speed)
+                            .insert(Homing { turn_rate })
+                            .id()
+                    })
+                    .collect()
+            }
+            FirePattern::Beam { width, duration } => {
+                vec![commands
+                    .spawn(BeamBundle::new(
+                        ctx.translation.truncate(),
+                        width * ctx.size(),
+                        duration,
+                        self,
+                        ctx,
+                    ))
+                    .id()]
+            }
+            FirePattern::Mine { lifetime, radius } => {
+                vec![commands
+                    .spawn(MineBundle::new(lifetime, radius * ctx.size(), self, ctx))
+                    .id()]
+            }
+        };
+
+        if ctx.charge_level.is_some() {
+            for shot in shots {
+                commands.entity(shot).insert(ChargedShot);
+            }
+        }
+    }
+
+    fn spawn_projectile<'w, 's, 'a>(
+        &self,
+        commands: &'a mut Commands<'w, 's>,
+        ctx: &FireContext,
+        velocity: Vec2,
+    ) -> bevy::ecs::system::EntityCommands<'w, 's, 'a> {
+        commands.spawn(
+            ProjectileBundle::new(
+                EntityType::Projectile,
+                Velocity::new(velocity.x, velocity.y),
+                ctx.translation * Vec2::ONE.extend(consts::PLAYER_PROJECTILE_Z),
+                ctx.player_assets.projectile.clone(),
+                Collider::new(ctx.player_dims.projectile),
+                ProjectileSource::FromSpaceship(ctx.player),
+                self.damage(ctx),
+            )
+            .with_look(
+                self.projectile.color(),
+                facing(velocity),
+                self.projectile.scale * ctx.size(),
+            ),
+        )
+    }
+}
+
+// Directions evenly spread over the angle around straight up
+fn fan_directions(count: u32, angle: f32) -> impl Iterator<Item = Vec2> {
+    let spread = angle.to_radians();
+    let step = match count {
+        0 | 1 => 0.0,
+        _ => spread / (count - 1) as f32,
+    };
+
+    (0..count).map(move |i| {
+        let angle = match count {
+            1 => 0.0,
+            _ => -spread / 2.0 + step * i as f32,
+        };
+        Vec2::new(angle.sin(), angle.cos())
+    })
+}
+
+// Projectile sprites point up, so they are turned towards where they fly
+fn facing(velocity: Vec2) -> Quat {
+    Quat::from_rotation_z(velocity.y.atan2(velocity.x) - FRAC_PI_2)
+}
+
+// Projectile, beam or mine fired by a charged weapon
+#[derive(Component, Debug)]
+pub struct ChargedShot;
+
+#[derive(Component, Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct Homing {
+    turn_rate: f32,
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+pub struct DamageArea {
+    width: f32,
+    height: f32,
+}
+
+impl DamageArea {
+    pub fn new(width: f32) -> Self {
+        Self {
+            width,
+            height: consts::PLAYER_CHARGE_SHOT_HEIGHT,
+        }
+    }
+
+    pub fn xy(&self) -> Vec2 {
+        Vec2::new(self.width, self.height)
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct Beam {
+    spaceship: Entity,
+    timer: Timer,
+    // Every enemy is hit only once by the same beam
+    hit_entities: HashSet<Entity>,
+    // Where the enemies destroyed by the beam were, over its whole lifetime
+    kills: Vec<Vec3>,
+}
+
+impl Beam {
+    pub fn add_kill(&mut self, translation: Vec3) {
+        self.kills.push(translation);
+    }
+
+    fn is_finished(&self, spaceship_query: &Query<(), With<Spaceship>>) -> bool {
+        self.timer.finished() || !spaceship_query.contains(self.spaceship)
+    }
+}
+
+#[derive(Bundle)]
+struct BeamBundle {
+    beam: Beam,
+    player: PlayerId,
+    damage: Damage,
+    damage_area: DamageArea,
+    #[bundle()]
+    sprite: SpriteBundle,
+}
+
+impl BeamBundle {
+    fn new(
+        spaceship_tf: Vec2,
+        width: f32,
+        duration: f32,
+        weapon: &Weapon,
+        ctx: &FireContext,
+    ) -> Self {
+        let damage_area = DamageArea::new(width);
+        let spawn_point = Vec3::new(
+            spaceship_tf.x,
+            spaceship_tf.y + damage_area.height / 2.0,
+            consts::PLAYER_CHARGE_SHOT_Z,
+        );
+
+        BeamBundle {
+            beam: Beam {
+                spaceship: ctx.spaceship,
+                timer: Timer::from_seconds(duration, TimerMode::Once),
+                hit_entities: HashSet::new(),
+                kills: Vec::new(),
+            },
+            player: ctx.player,
+            damage: weapon.damage(ctx),
+            damage_area,
+            sprite: SpriteBundle {
+                sprite: Sprite {
+                    color: weapon.projectile.color(),
+                    custom_size: Some(damage_area.xy()),
+                    ..default()
+                },
+                transform: Transform::from_translation(spawn_point),
+                ..default()
+            },
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct Mine {
+    timer: Timer,
+    radius: f32,
+}
+
+#[derive(Bundle)]
+struct MineBundle {
+    mine: Mine,
+    player: PlayerId,
+    damage: Damage,
+    collider: Collider,
+    #[bundle()]
+    sprite: SpriteBundle,
+}
+
+impl MineBundle {
+    fn new(lifetime: f32, radius: f32, weapon: &Weapon, ctx: &FireContext) -> Self {
+        MineBundle {
+            mine: Mine {
+                timer: Timer::from_seconds(lifetime, TimerMode::Once),
+                radius,
+            },
+            player: ctx.player,
+            damage: weapon.damage(ctx),
+            collider: Collider::new(ctx.player_dims.projectile),
+            sprite: SpriteBundle {
+                texture: ctx.player_assets.projectile.clone(),
+                sprite: Sprite {
+                    color: weapon.projectile.color(),
+                    ..default()
+                },
+                transform: Transform::from_translation(
+                    ctx.translation * Vec2::ONE.extend(consts::PLAYER_PROJECTILE_Z),
+                )
+                .with_scale(Vec3::splat(weapon.projectile.scale * ctx.size())),
+                ..default()
+            },
+        }
+    }
+}
+
+// ===
+
+pub fn steer_homing_projectiles(
+    time: Res<Time>,
+    mut projectile_query: Query<(&mut Transform, &mut Velocity, &Homing), Without<Enemy>>,
+    enemy_query: Query<&Transform, With<Enemy>>,
+) {
+    for (mut tf, mut velocity, homing) in projectile_query.iter_mut() {
+        let position = tf.translation.truncate();
+        let target = enemy_query
+            .iter()
+            .map(|enemy_tf| enemy_tf.translation.truncate())
+            .min_by(|a, b| {
+                a.distance_squared(position)
+                    .total_cmp(&b.distance_squared(position))
+            });
+
+        // Missile right on top of its target, or standing still, has nowhere to turn to
+        let current = Vec2::new(velocity.x, velocity.y);
+        let to_target = target
+            .and_then(|target| (target - position).try_normalize())
+            .filter(|_| current != Vec2::ZERO);
+
+        if let Some(to_target) = to_target {
+            let max_turn = homing.turn_rate * time.delta_seconds();
+            let turn = current.angle_between(to_target).clamp(-max_turn, max_turn);
+            let steered = Vec2::from_angle(turn).rotate(current);
+
+            velocity.x = steered.x;
+            velocity.y = steered.y;
+            tf.rotation = facing(steered);
+        }
+    }
+}
+
+pub fn beam_hit_detection(
+    mut ev_enemy_hit: EventWriter<EnemyIsHit>,
+    mut beam_query: Query<(
+        Entity,
+        &Transform,
+        &DamageArea,
+        &Damage,
+        &PlayerId,
+        &mut Beam,
+        Option<&ChargedShot>,
+    )>,
+    enemy_query: Query<(Entity, &Transform, &Collider), With<Enemy>>,
+) {
+    for (beam_entity, beam_tf, damage_area, damage, player, mut beam, charged) in
+        beam_query.iter_mut()
+    {
+        for (enemy_entity, enemy_tf, enemy_collider) in enemy_query.iter() {
+            if beam.hit_entities.contains(&enemy_entity) {
+                continue;
+            }
+
+            let collision = collide(
+                beam_tf.translation,
+                damage_area.xy(),
+                enemy_tf.translation,
+                enemy_collider.scaled_size(enemy_tf),
+            );
+
+            if collision.is_some() {
+                beam.hit_entities.insert(enemy_entity);
+                ev_enemy_hit.send(EnemyIsHit {
+                    entity: enemy_entity,
+                    damage: damage.0,
+                    player: *player,
+                    source: beam_entity,
+                    charged: charged.is_some(),
+                    // Beam hits the enemy from below
+                    impact: Vec3::new(
+                        beam_tf.translation.x,
+                        enemy_tf.translation.y - enemy_collider.scaled_size(enemy_tf).y / 2.0,
+                        enemy_tf.translation.z,
+                    ),
+                    direction: Vec2::Y,
+                });
+            }
+        }
+    }
+}
+
+// Beams stay attached to the spaceship that fired them until they run out
+pub fn update_beams(
+    time: Res<Time>,
+    mut beam_query: Query<(&mut Transform, &mut Sprite, &mut Beam)>,
+    spaceship_query: Query<&Transform, Without<Beam>>,
+) {
+    for (mut tf, mut sprite, mut beam) in beam_query.iter_mut() {
+        beam.timer.tick(time.delta());
+        sprite
+            .color
+            .set_a((beam.timer.remaining_secs() / consts::PLAYER_BEAM_FADE_TIME).min(1.0));
+
+        if let Ok(spaceship_tf) = spaceship_query.get(beam.spaceship) {
+            tf.translation.x = spaceship_tf.translation.x;
+        }
+    }
+}
+
+// Runs after the hits of the last frame are handled, so every enemy the beam
+// destroyed counts towards a single multi kill bonus
+pub fn despawn_finished_beams(
+    mut commands: Commands,
+    mut ev_add_score: EventWriter<AddScore>,
+    beam_query: Query<(Entity, &PlayerId, &Beam)>,
+    spaceship_query: Query<(), With<Spaceship>>,
+) {
+    for (entity, player, beam) in beam_query.iter() {
+        if !beam.is_finished(&spaceship_query) {
+            continue;
+        }
+
+        if let Some(multi_kill) = AddScore::multi_kill(*player, &beam.kills) {
+            ev_add_score.send(multi_kill);
+        }
+        commands.entity(entity).despawn();
+    }
+}
+
+pub fn update_mines(
+    mut commands: Commands,
+    mut ev_enemy_hit: EventWriter<EnemyIsHit>,
+    time: Res<Time>,
+    mut mine_query: Query<(
+        Entity,
+        &Transform,
+        &Collider,
+        &Damage,
+        &PlayerId,
+        &mut Mine,
+        Option<&ChargedShot>,
+    )>,
+    enemy_query: Query<(Entity, &Transform, &Collider), With<Enemy>>,
+) {
+    for (mine_entity, mine_tf, mine_collider, damage, player, mut mine, charged) in
+        mine_query.iter_mut()
+    {
+        mine.timer.tick(time.delta());
+
+        let touched = enemy_query.iter().any(|(_, enemy_tf, enemy_collider)| {
+            collide(
+                mine_tf.translation,
+                mine_collider.scaled_size(mine_tf),
+                enemy_tf.translation,
+                enemy_collider.scaled_size(enemy_tf),
+            )
+            .is_some()
+        });
+
+        if !touched && !mine.timer.finished() {
+            continue;
+        }
+
+        // Explosion hits everything within its radius
+        for (enemy_entity, enemy_tf, _) in enemy_query.iter() {
+            if enemy_tf
+                .translation
+                .truncate()
+                .distance(mine_tf.translation.truncate())
+                <= mine.radius
+            {
+                ev_enemy_hit.send(EnemyIsHit {
+                    entity: enemy_entity,
+                    damage: damage.0,
+                    player: *player,
+                    source: mine_entity,
+                    charged: charged.is_some(),
+                    impact: mine_tf.translation,
+                    direction: (enemy_tf.translation - mine_tf.translation)
+                        .truncate()
+                        .normalize_or_zero(),
+                });
+            }
+        }
+
+        commands.entity(mine_entity).despawn();
+    }
+}
@@ -0,0 +1,429 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::GameConfig,
+    consts,
+    player::{RunUpgrades, ShipDefinition, ShipList, Ships, WeaponSlot},
+    run_stats::RunStats,
+    save, GameState, GameplayState, Stats,
+};
+
+pub struct ProfilePlugin;
+
+impl Plugin for ProfilePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(load_profile())
+            .add_event::<PickUnlock>()
+            .add_systems(OnEnter(GameplayState::GameOver), award_credits)
+            .add_systems(Update, pick_unlocks.run_if(in_state(GameState::Hangar)));
+    }
+}
+
+// ===
+
+// Steps that bring an older profile to the next version, `MIGRATIONS[0]` turns
+// version 1 into version 2 and so on. Every change to the saved format bumps
+// the version by adding a step here, steps work on the raw ron so fields can be
+// renamed, moved or filled in before the profile is read
+const MIGRATIONS: &[fn(&mut ron::Map)] = &[group_loadout];
+
+pub const PROFILE_VERSION: u32 = MIGRATIONS.len() as u32 + 1;
+
+// Progress kept between runs. Unlocks are stored by id, so ids that were
+// removed from the definitions are just ignored
+#[derive(Resource, Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Profile {
+    version: u32,
+    pub credits: u32,
+    pub runs: u32,
+    pub best_score: u32,
+    ships: Vec<String>,
+    weapons: Vec<String>,
+    palettes: Vec<String>,
+    starting_upgrades: Vec<String>,
+    loadout: Loadout,
+    // Profile that couldn't be read is left alone on disk instead of being overwritten
+    #[serde(skip)]
+    read_only: bool,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            version: PROFILE_VERSION,
+            credits: 0,
+            runs: 0,
+            best_score: 0,
+            ships: Vec::new(),
+            weapons: Vec::new(),
+            palettes: Vec::new(),
+            starting_upgrades: Vec::new(),
+            loadout: Loadout::default(),
+            read_only: false,
+        }
+    }
+}
+
+// Equipped items, fitted to whatever ship is picked
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+struct Loadout {
+    primary: Option<String>,
+    secondary: Option<String>,
+    palette: Option<String>,
+    upgrades: Vec<String>,
+}
+
+impl Profile {
+    fn store(&self) {
+        if !self.read_only {
+            save::store(consts::PROFILE_SAVE, self);
+        }
+    }
+
+    fn owned(&self, unlock: &Unlock) -> &Vec<String> {
+        match unlock {
+            Unlock::Ship(_) => &self.ships,
+            Unlock::Weapon(_) => &self.weapons,
+            Unlock::Palette(_) => &self.palettes,
+            Unlock::StartingUpgrade(_) => &self.starting_upgrades,
+        }
+    }
+
+    pub fn is_unlocked(&self, unlock: &Unlock) -> bool {
+        self.owned(unlock).contains(unlock.id())
+    }
+
+    pub fn is_ship_unlocked(&self, ship: &ShipDefinition) -> bool {
+        ship.price == 0 || self.ships.contains(&ship.id)
+    }
+
+    pub fn is_equipped(&self, unlock: &Unlock) -> bool {
+        let id = Some(unlock.id());
+
+        match unlock {
+            Unlock::Ship(_) => false,
+            Unlock::Weapon(_) => {
+                self.loadout.primary.as_ref() == id || self.loadout.secondary.as_ref() == id
+            }
+            Unlock::Palette(_) => self.loadout.palette.as_ref() == id,
+            Unlock::StartingUpgrade(_) => self.loadout.upgrades.contains(unlock.id()),
+        }
+    }
+
+    fn unlock(&mut self, unlock: &Unlock) {
+        let id = unlock.id().clone();
+
+        match unlock {
+            Unlock::Ship(_) => self.ships.push(id),
+            Unlock::Weapon(_) => self.weapons.push(id),
+            Unlock::Palette(_) => self.palettes.push(id),
+            Unlock::StartingUpgrade(_) => self.starting_upgrades.push(id),
+        }
+    }
+
+    // Only one weapon per slot and one palette can be equipped,
+    // any number of starting upgrades
+    fn toggle_equipped(&mut self, unlock: &Unlock, ship_list: &ShipList) {
+        fn toggle(equipped: &mut Option<String>, id: &str) {
+            *equipped = match equipped.as_deref() == Some(id) {
+                true => None,
+                false => Some(id.to_string()),
+            };
+        }
+
+        match unlock {
+            Unlock::Ship(_) => (),
+            Unlock::Weapon(id) => {
+                match ship_list.weapons.iter().find(|weapon| weapon.id == *id) {
+                    Some(weapon) if weapon.slot == WeaponSlot::Primary => {
+                        toggle(&mut self.loadout.primary, id)
+                    }
+                    Some(_) => toggle(&mut self.loadout.secondary, id),
+                    None => (),
+                };
+            }
+            Unlock::Palette(id) => toggle(&mut self.loadout.palette, id),
+            Unlock::StartingUpgrade(id) => {
+                match self
+                    .loadout
+                    .upgrades
+                    .iter()
+                    .position(|equipped| equipped == id)
+                {
+                    Some(idx) => {
+                        self.loadout.upgrades.remove(idx);
+                    }
+                    None => self.loadout.upgrades.push(id.clone()),
+                }
+            }
+        }
+    }
+
+    // Picked ship with the equipped weapons and palette
+    pub fn outfit(&self, ship: &ShipDefinition, ship_list: &ShipList) -> ShipDefinition {
+        let weapon = |id: &Option<String>, slot: WeaponSlot| {
+            ship_list
+                .weapons
+                .iter()
+                .find(|weapon| Some(&weapon.id) == id.as_ref() && weapon.slot == slot)
+                .map(|weapon| weapon.weapon.clone())
+        };
+
+        let mut ship = ship.clone();
+
+        if let Some(primary) = weapon(&self.loadout.primary, WeaponSlot::Primary) {
+            ship.primary = primary;
+        }
+        if let Some(secondary) = weapon(&self.loadout.secondary, WeaponSlot::Secondary) {
+            ship.secondary = secondary;
+        }
+        if let Some(palette) = ship_list
+            .palettes
+            .iter()
+            .find(|palette| Some(&palette.id) == self.loadout.palette.as_ref())
+        {
+            ship.color = palette.color;
+        }
+
+        ship
+    }
+
+    // Every run starts with the equipped starting upgrades
+    pub fn starting_upgrades(&self, config: &GameConfig) -> RunUpgrades {
+        let mut upgrades = RunUpgrades::default();
+
+        for upgrade in config
+            .shop
+            .upgrades
+            .iter()
+            .filter(|upgrade| self.loadout.upgrades.contains(&upgrade.id))
+        {
+            upgrades.add_starting(&upgrade.modifiers);
+        }
+
+        upgrades
+    }
+
+    pub fn run_credits(stats: &Stats, run_stats: &RunStats, config: &GameConfig) -> u32 {
+        (stats.score as f32 * config.profile.credits_per_score) as u32
+            + run_stats.waves_cleared * config.profile.credits_per_wave
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Unlock {
+    Ship(String),
+    Weapon(String),
+    Palette(String),
+    StartingUpgrade(String),
+}
+
+impl Unlock {
+    fn id(&self) -> &String {
+        match self {
+            Unlock::Ship(id)
+            | Unlock::Weapon(id)
+            | Unlock::Palette(id)
+            | Unlock::StartingUpgrade(id) => id,
+        }
+    }
+}
+
+// Line under the item name in the hangar, descriptions are keys in the string
+// tables, kinds without a description of their own are labelled by the UI
+#[derive(Clone, Debug)]
+pub enum ItemDescription {
+    Ship(String),
+    Weapon(WeaponSlot),
+    Palette,
+    StartingUpgrade(String),
+}
+
+// Everything that can be bought in the hangar, free ships are left out
+#[derive(Clone, Debug)]
+pub struct HangarItem {
+    pub unlock: Unlock,
+    // Key of the name in the string tables
+    pub name: String,
+    pub description: ItemDescription,
+    pub price: u32,
+}
+
+impl HangarItem {
+    pub fn all(ship_list: &ShipList, config: &GameConfig) -> Vec<Self> {
+        let ships = ship_list
+            .ships
+            .iter()
+            .filter(|ship| ship.price > 0)
+            .map(|ship| HangarItem {
+                unlock: Unlock::Ship(ship.id.clone()),
+                name: ship.name.clone(),
+                description: ItemDescription::Ship(ship.description.clone()),
+                price: ship.price,
+            });
+        let weapons = ship_list.weapons.iter().map(|weapon| HangarItem {
+            unlock: Unlock::Weapon(weapon.id.clone()),
+            name: weapon.weapon.name.clone(),
+            description: ItemDescription::Weapon(weapon.slot),
+            price: weapon.price,
+        });
+        let palettes = ship_list.palettes.iter().map(|palette| HangarItem {
+            unlock: Unlock::Palette(palette.id.clone()),
+            name: palette.name.clone(),
+            description: ItemDescription::Palette,
+            price: palette.price,
+        });
+        let starting_upgrades =
+            config
+                .profile
+                .starting_upgrades
+                .iter()
+                .filter_map(|starting_upgrade| {
+                    let upgrade = config
+                        .shop
+                        .upgrades
+                        .iter()
+                        .find(|upgrade| upgrade.id == starting_upgrade.upgrade)?;
+
+                    Some(HangarItem {
+                        unlock: Unlock::StartingUpgrade(upgrade.id.clone()),
+                        name: upgrade.name.clone(),
+                        description: ItemDescription::StartingUpgrade(upgrade.description.clone()),
+                        price: starting_upgrade.price,
+                    })
+                });
+
+        ships
+            .chain(weapons)
+            .chain(palettes)
+            .chain(starting_upgrades)
+            .collect()
+    }
+}
+
+// Item was picked in the hangar, it's bought when locked
+// and equipped or unequipped when already owned
+#[derive(Event)]
+pub struct PickUnlock(pub Unlock);
+
+// ===
+
+// Missing profile starts a new one
+fn load_profile() -> Profile {
+    if !save::exists(consts::PROFILE_SAVE) {
+        return Profile::default();
+    }
+
+    match save::load::<ron::Value>(consts::PROFILE_SAVE)
+        .ok_or_else(|| "unreadable profile".to_string())
+        .and_then(migrate)
+    {
+        Ok(profile) => profile,
+        Err(err) => {
+            error!(
+                "Failed to load the profile, progress won't be saved: {}",
+                err
+            );
+            Profile {
+                read_only: true,
+                ..default()
+            }
+        }
+    }
+}
+
+// Version 1 kept the equipped items next to the unlocks
+fn group_loadout(profile: &mut ron::Map) {
+    let mut loadout = ron::Map::new();
+
+    for (from, to) in [
+        ("primary", "primary"),
+        ("secondary", "secondary"),
+        ("palette", "palette"),
+        ("equipped_upgrades", "upgrades"),
+    ] {
+        if let Some(value) = profile.remove(&ron::Value::String(from.to_string())) {
+            loadout.insert(ron::Value::String(to.to_string()), value);
+        }
+    }
+
+    profile.insert(
+        ron::Value::String("loadout".to_string()),
+        ron::Value::Map(loadout),
+    );
+}
+
+fn migrate(value: ron::Value) -> Result<Profile, String> {
+    let version_key = ron::Value::String("version".to_string());
+
+    let ron::Value::Map(mut profile) = value else {
+        return Err("profile is not a struct".to_string());
+    };
+    let version = match profile.iter().find(|(key, _)| **key == version_key) {
+        Some((_, ron::Value::Number(number))) => number.as_i64().unwrap_or_default(),
+        _ => return Err("profile has no version".to_string()),
+    };
+
+    if !(1..=i64::from(PROFILE_VERSION)).contains(&version) {
+        return Err(format!("unsupported profile version {}", version));
+    }
+
+    for (idx, migration) in MIGRATIONS.iter().enumerate().skip(version as usize - 1) {
+        migration(&mut profile);
+        info!("Migrated the profile to version {}", idx + 2);
+    }
+
+    profile.insert(
+        version_key,
+        ron::Value::Number(i64::from(PROFILE_VERSION).into()),
+    );
+    ron::Value::Map(profile)
+        .into_rust()
+        .map_err(|err| err.to_string())
+}
+
+fn award_credits(
+    mut profile: ResMut<Profile>,
+    stats: Res<Stats>,
+    run_stats: Res<RunStats>,
+    config: Res<GameConfig>,
+) {
+    profile.credits += Profile::run_credits(&stats, &run_stats, &config);
+    profile.runs += 1;
+    profile.best_score = profile.best_score.max(stats.score);
+    profile.store();
+}
+
+// Buying an item equips it right away
+fn pick_unlocks(
+    mut ev_pick: EventReader<PickUnlock>,
+    mut profile: ResMut<Profile>,
+    ships: Ships,
+    config: Res<GameConfig>,
+) {
+    let Some(ship_list) = ships.list() else {
+        return;
+    };
+    let items = HangarItem::all(ship_list, &config);
+
+    for pick_ev in ev_pick.iter() {
+        let Some(item) = items.iter().find(|item| item.unlock == pick_ev.0) else {
+            continue;
+        };
+
+        if !profile.is_unlocked(&item.unlock) {
+            if profile.credits < item.price {
+                continue;
+            }
+
+            profile.credits -= item.price;
+            profile.unlock(&item.unlock);
+        }
+
+        profile.toggle_equipped(&item.unlock, ship_list);
+        profile.store();
+    }
+}
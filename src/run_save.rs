@@ -0,0 +1,380 @@
+use bevy::{
+    ecs::{query::Has, system::SystemParam},
+    prelude::*,
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    achievements::AchievementTracker,
+    background::StarfieldSeed,
+    common::{
+        AsteroidAssets, Collider, Damage, EntityType, Projectile, ProjectileBundle,
+        ProjectileSource,
+    },
+    consts,
+    enemy::{Enemy, EnemyHealth, GameplayStage},
+    movement::{AngularVelocity, PatternMovement, Velocity},
+    player::{
+        ChargedShot, Homing, Lives, PlayerAssetDimensions, PlayerCount, PlayerHandles, PlayerId,
+        RunUpgrades, SelectedShips, Spaceship, SpaceshipDash, SpaceshipHealth, SpaceshipShoot,
+    },
+    rng::GameRng,
+    run_stats::RunStats,
+    save, GameState, Stats,
+};
+
+pub struct RunSavePlugin;
+
+impl Plugin for RunSavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Gameplay), restore_run);
+    }
+}
+
+// ===
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+struct TransformSnapshot {
+    translation: [f32; 3],
+    rotation: [f32; 4],
+    scale: [f32; 3],
+}
+
+impl From<&Transform> for TransformSnapshot {
+    fn from(tf: &Transform) -> Self {
+        Self {
+            translation: tf.translation.to_array(),
+            rotation: tf.rotation.to_array(),
+            scale: tf.scale.to_array(),
+        }
+    }
+}
+
+impl From<TransformSnapshot> for Transform {
+    fn from(snapshot: TransformSnapshot) -> Self {
+        Transform {
+            translation: Vec3::from_array(snapshot.translation),
+            rotation: Quat::from_array(snapshot.rotation),
+            scale: Vec3::from_array(snapshot.scale),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct EnemySnapshot {
+    entity_type: EntityType,
+    transform: TransformSnapshot,
+    velocity: Velocity,
+    angular_velocity: AngularVelocity,
+    health: u32,
+    #[serde(default)]
+    pattern: Option<PatternMovement>,
+    // Index of the enemy the orbit goes around
+    #[serde(default)]
+    anchor: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct ProjectileSnapshot {
+    transform: TransformSnapshot,
+    velocity: Velocity,
+    source: ProjectileSource,
+    damage: u32,
+    color: [f32; 4],
+    homing: Option<Homing>,
+    #[serde(default)]
+    charged: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SpaceshipSnapshot {
+    pub player: PlayerId,
+    pub health: u32,
+    pub x: f32,
+    pub dash: SpaceshipDash,
+    pub shoot: SpaceshipShoot,
+}
+
+// Everything needed to continue a run after the game was closed,
+// present as a resource while the saved run is being restored. Beams, mines
+// and breaking asteroids only last for moments and are dropped
+#[derive(Resource, Serialize, Deserialize, Debug)]
+pub struct RunSnapshot {
+    pub player_count: usize,
+    ships: SelectedShips,
+    pub stage: GameplayStage,
+    enemies: Vec<EnemySnapshot>,
+    projectiles: Vec<ProjectileSnapshot>,
+    spaceships: Vec<SpaceshipSnapshot>,
+    stats: Stats,
+    run_stats: RunStats,
+    lives: Lives,
+    #[serde(default)]
+    upgrades: RunUpgrades,
+    #[serde(default)]
+    achievement_tracker: AchievementTracker,
+    rng: GameRng,
+    // Older saves keep the menu starfield
+    #[serde(default = "menu_starfield_seed")]
+    starfield_seed: StarfieldSeed,
+}
+
+impl RunSnapshot {
+    pub fn spaceship(&self, player: PlayerId) -> Option<&SpaceshipSnapshot> {
+        self.spaceships
+            .iter()
+            .find(|spaceship| spaceship.player == player)
+    }
+
+    pub fn enemy_types(&self) -> impl Iterator<Item = EntityType> + '_ {
+        self.enemies.iter().map(|enemy| enemy.entity_type)
+    }
+}
+
+fn menu_starfield_seed() -> StarfieldSeed {
+    StarfieldSeed(consts::MENU_STARFIELD_SEED)
+}
+
+// Entity brought back from the snapshot, the run stats already count it
+#[derive(Component, Debug)]
+pub struct Restored;
+
+// Resources of the run that go into the snapshot as they are
+#[derive(SystemParam)]
+pub struct RunResources<'w> {
+    stats: Res<'w, Stats>,
+    run_stats: Res<'w, RunStats>,
+    lives: Res<'w, Lives>,
+    upgrades: Res<'w, RunUpgrades>,
+    achievement_tracker: Res<'w, AchievementTracker>,
+    rng: Res<'w, GameRng>,
+    starfield_seed: Res<'w, StarfieldSeed>,
+    player_count: Res<'w, PlayerCount>,
+    selected_ships: Res<'w, SelectedShips>,
+}
+
+// ===
+
+pub fn has_saved_run() -> bool {
+    save::exists(consts::RUN_SAVE)
+}
+
+pub fn save_run(
+    stage_query: Query<&GameplayStage>,
+    enemy_query: Query<
+        (
+            Entity,
+            &EntityType,
+            &Transform,
+            &Velocity,
+            &AngularVelocity,
+            &EnemyHealth,
+            Option<&PatternMovement>,
+        ),
+        With<Enemy>,
+    >,
+    projectile_query: Query<
+        (
+            &Transform,
+            &Velocity,
+            &ProjectileSource,
+            &Damage,
+            &Sprite,
+            Option<&Homing>,
+            Has<ChargedShot>,
+        ),
+        With<Projectile>,
+    >,
+    spaceship_query: Query<
+        (
+            &PlayerId,
+            &SpaceshipHealth,
+            &Transform,
+            &SpaceshipDash,
+            &SpaceshipShoot,
+        ),
+        With<Spaceship>,
+    >,
+    resources: RunResources,
+) {
+    if let Ok(stage) = stage_query.get_single() {
+        let enemies: Vec<_> = enemy_query.iter().collect();
+        // Orbits point at their anchor by its index in the saved enemies
+        let anchor_idx = |anchor: Entity| enemies.iter().position(|(entity, ..)| *entity == anchor);
+
+        let snapshot = RunSnapshot {
+            player_count: resources.player_count.0,
+            ships: resources.selected_ships.clone(),
+            stage: stage.clone(),
+            enemies: enemies
+                .iter()
+                .map(
+                    |(_, entity_type, tf, velocity, angular_velocity, health, pattern)| {
+                        EnemySnapshot {
+                            entity_type: **entity_type,
+                            transform: (*tf).into(),
+                            velocity: **velocity,
+                            angular_velocity: **angular_velocity,
+                            health: health.0,
+                            pattern: pattern.cloned(),
+                            anchor: pattern
+                                .and_then(PatternMovement::anchor)
+                                .and_then(&anchor_idx),
+                        }
+                    },
+                )
+                .collect(),
+            projectiles: projectile_query
+                .iter()
+                .map(
+                    |(tf, velocity, source, damage, sprite, homing, charged)| ProjectileSnapshot {
+                        transform: tf.into(),
+                        velocity: *velocity,
+                        source: *source,
+                        damage: damage.0,
+                        color: sprite.color.as_rgba_f32(),
+                        homing: homing.copied(),
+                        charged,
+                    },
+                )
+                .collect(),
+            spaceships: spaceship_query
+                .iter()
+                .map(|(player, health, tf, dash, shoot)| SpaceshipSnapshot {
+                    player: *player,
+                    health: health.0,
+                    x: tf.translation.x,
+                    dash: dash.clone(),
+                    shoot: shoot.clone(),
+                })
+                .collect(),
+            stats: resources.stats.clone(),
+            run_stats: resources.run_stats.clone(),
+            lives: resources.lives.clone(),
+            upgrades: resources.upgrades.clone(),
+            achievement_tracker: resources.achievement_tracker.clone(),
+            rng: resources.rng.clone(),
+            starfield_seed: *resources.starfield_seed,
+        };
+
+        save::store(consts::RUN_SAVE, &snapshot);
+    }
+}
+
+// Loaded run is restored once the gameplay starts
+pub fn load_saved_run(
+    mut commands: Commands,
+    mut player_count: ResMut<PlayerCount>,
+    mut selected_ships: ResMut<SelectedShips>,
+) {
+    if let Some(snapshot) = save::load::<RunSnapshot>(consts::RUN_SAVE) {
+        player_count.0 = snapshot.player_count;
+        *selected_ships = snapshot.ships.clone();
+        commands.insert_resource(snapshot);
+    }
+}
+
+// Stage and spaceships are restored where they are spawned, this brings back
+// everything else and then removes the save, so the same run can't be continued twice
+fn restore_run(
+    mut commands: Commands,
+    snapshot: Option<Res<RunSnapshot>>,
+    mut rng: ResMut<GameRng>,
+    asteroid_assets: AsteroidAssets,
+    player_assets: Res<PlayerHandles>,
+    player_dims: Res<PlayerAssetDimensions>,
+) {
+    let snapshot = match snapshot {
+        Some(snapshot) => snapshot,
+        None => {
+            // Every new run gets its own seed, the starfield follows from it
+            *rng = GameRng::default();
+            commands.insert_resource(StarfieldSeed(rng.gen()));
+            return;
+        }
+    };
+
+    let enemies: Vec<Option<Entity>> = snapshot
+        .enemies
+        .iter()
+        .map(|enemy| {
+            let EntityType::Asteroid(asteroid) = enemy.entity_type else {
+                return None;
+            };
+            let asteroid_bundle = asteroid.construct_asteroid_bundle(
+                enemy.entity_type,
+                enemy.velocity,
+                Vec3::from_array(enemy.transform.translation),
+                &asteroid_assets,
+                &mut rng,
+            )?;
+
+            // Bundle already holds these, inserting them afterwards overrides the fresh ones
+            let asteroid = commands
+                .spawn(asteroid_bundle)
+                .insert((
+                    Transform::from(enemy.transform),
+                    enemy.angular_velocity,
+                    EnemyHealth(enemy.health),
+                ))
+                .id();
+            Some(asteroid)
+        })
+        .collect();
+
+    // Movement is added once every enemy is back, so orbits can find their anchors
+    for (enemy, entity) in snapshot.enemies.iter().zip(enemies.iter()) {
+        let (Some(entity), Some(pattern)) = (entity, &enemy.pattern) else {
+            continue;
+        };
+        let anchor = enemy
+            .anchor
+            .and_then(|idx| enemies.get(idx).copied().flatten());
+
+        commands.entity(*entity).insert(match anchor {
+            Some(anchor) => pattern.clone().with_anchor(anchor),
+            None => pattern.clone(),
+        });
+    }
+
+    for projectile in snapshot.projectiles.iter() {
+        let transform = Transform::from(projectile.transform);
+        let mut projectile_entity = commands.spawn((
+            ProjectileBundle::new(
+                EntityType::Projectile,
+                projectile.velocity,
+                transform.translation,
+                player_assets.projectile.clone(),
+                Collider::new(player_dims.projectile),
+                projectile.source,
+                Damage(projectile.damage),
+            )
+            .with_look(
+                Color::from(projectile.color),
+                transform.rotation,
+                transform.scale.x,
+            ),
+            Restored,
+        ));
+
+        if let Some(homing) = projectile.homing {
+            projectile_entity.insert(homing);
+        }
+        if projectile.charged {
+            projectile_entity.insert(ChargedShot);
+        }
+    }
+
+    // Inserted after the asteroids are built, so picking their sprites
+    // doesn't move the saved generator state
+    commands.insert_resource(snapshot.stats.clone());
+    commands.insert_resource(snapshot.run_stats.clone());
+    commands.insert_resource(snapshot.lives.clone());
+    commands.insert_resource(snapshot.upgrades.clone());
+    commands.insert_resource(snapshot.achievement_tracker.clone());
+    commands.insert_resource(snapshot.rng.clone());
+    commands.insert_resource(snapshot.starfield_seed);
+    commands.remove_resource::<RunSnapshot>();
+    save::remove(consts::RUN_SAVE);
+}
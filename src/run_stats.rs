@@ -0,0 +1,189 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::{prelude::*, utils::HashSet};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::{AsteroidType, EntityType, ProjectileSource},
+    consts,
+    events::{BreakAsteroid, EnemyIsHit, SpaceshipDamaged, WaveEnded},
+    is_playing,
+    player::{Beam, Mine, PlayerCount},
+    run_save::Restored,
+    score::Combo,
+    GameState, GameplayState, Stats,
+};
+
+pub struct RunStatsPlugin;
+
+impl Plugin for RunStatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunStats>()
+            .add_systems(OnEnter(GameState::Gameplay), reset_run_stats)
+            .add_systems(
+                Update,
+                (
+                    track_shots,
+                    track_destroyed_asteroids,
+                    track_max_combo,
+                    track_damage_taken,
+                    track_cleared_waves,
+                )
+                    .run_if(is_playing),
+            )
+            .add_systems(OnEnter(GameplayState::GameOver), write_run_log);
+    }
+}
+
+// ===
+
+// Statistics of the current run, shown on the game over screen
+// and written to the run log for balancing
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct RunStats {
+    // Projectiles, beams and mines fired, and how many of them hit at least one enemy
+    pub shots_fired: u32,
+    pub hits: u32,
+    pub charged_shots: u32,
+    pub asteroids_small: u32,
+    pub asteroids_medium: u32,
+    pub asteroids_large: u32,
+    pub dashes: u32,
+    pub damage_taken: u32,
+    pub max_combo: u32,
+    pub waves_cleared: u32,
+    // Shots in flight already counted as hits, a beam or mine can hit several enemies
+    #[serde(skip)]
+    hit_shots: HashSet<Entity>,
+}
+
+impl RunStats {
+    // Share of fired shots that hit something, in percent
+    pub fn accuracy(&self) -> f32 {
+        match self.shots_fired {
+            0 => 0.0,
+            shots_fired => self.hits as f32 / shots_fired as f32 * 100.0,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct RunLogEntry<'a> {
+    timestamp: u64,
+    players: usize,
+    score: u32,
+    time_secs: f32,
+    accuracy: f32,
+    stats: &'a RunStats,
+}
+
+// ===
+
+fn reset_run_stats(mut run_stats: ResMut<RunStats>) {
+    *run_stats = RunStats::default();
+}
+
+fn track_shots(
+    mut run_stats: ResMut<RunStats>,
+    mut ev_enemy_hit: EventReader<EnemyIsHit>,
+    projectile_query: Query<&ProjectileSource, (Added<ProjectileSource>, Without<Restored>)>,
+    weapon_query: Query<(), Or<(Added<Beam>, Added<Mine>)>>,
+    mut removed_projectiles: RemovedComponents<ProjectileSource>,
+    mut removed_beams: RemovedComponents<Beam>,
+    mut removed_mines: RemovedComponents<Mine>,
+) {
+    for projectile_source in projectile_query.iter() {
+        if matches!(projectile_source, ProjectileSource::FromSpaceship(_)) {
+            run_stats.shots_fired += 1;
+        }
+    }
+    run_stats.shots_fired += weapon_query.iter().count() as u32;
+
+    for hit_ev in ev_enemy_hit.iter() {
+        if run_stats.hit_shots.insert(hit_ev.source) {
+            run_stats.hits += 1;
+        }
+    }
+
+    for shot in removed_projectiles
+        .iter()
+        .chain(removed_beams.iter())
+        .chain(removed_mines.iter())
+    {
+        run_stats.hit_shots.remove(&shot);
+    }
+}
+
+fn track_destroyed_asteroids(
+    mut run_stats: ResMut<RunStats>,
+    mut ev_break_asteroid: EventReader<BreakAsteroid>,
+) {
+    let shot_down = ev_break_asteroid
+        .iter()
+        .filter(|break_ev| break_ev.is_shot_down());
+
+    for break_ev in BreakAsteroid::unique(shot_down) {
+        if let EntityType::Asteroid(asteroid) = break_ev.entity_type {
+            match asteroid.asteroid_type {
+                AsteroidType::Small => run_stats.asteroids_small += 1,
+                AsteroidType::Medium => run_stats.asteroids_medium += 1,
+                AsteroidType::Large => run_stats.asteroids_large += 1,
+            }
+        }
+    }
+}
+
+fn track_max_combo(mut run_stats: ResMut<RunStats>, combo: Res<Combo>) {
+    if combo.kills > run_stats.max_combo {
+        run_stats.max_combo = combo.kills;
+    }
+}
+
+fn track_damage_taken(
+    mut run_stats: ResMut<RunStats>,
+    mut ev_damaged: EventReader<SpaceshipDamaged>,
+) {
+    run_stats.damage_taken += ev_damaged.iter().count() as u32;
+}
+
+fn track_cleared_waves(mut run_stats: ResMut<RunStats>, mut ev_wave_ended: EventReader<WaveEnded>) {
+    run_stats.waves_cleared += ev_wave_ended.iter().count() as u32;
+}
+
+// Every run is appended as a single JSON line to the run log
+fn write_run_log(run_stats: Res<RunStats>, stats: Res<Stats>, player_count: Res<PlayerCount>) {
+    let entry = RunLogEntry {
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0),
+        players: player_count.0,
+        score: stats.score,
+        time_secs: stats.watch.elapsed_secs(),
+        accuracy: run_stats.accuracy(),
+        stats: &run_stats,
+    };
+
+    let result = serde_json::to_string(&entry)
+        .map_err(|err| err.to_string())
+        .and_then(|line| {
+            fs::create_dir_all(consts::SAVE_DIR).map_err(|err| err.to_string())?;
+
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(Path::new(consts::SAVE_DIR).join(consts::RUN_LOG_FILE))
+                .map_err(|err| err.to_string())?;
+
+            writeln!(file, "{}", line).map_err(|err| err.to_string())
+        });
+
+    if let Err(err) = result {
+        error!("Failed to write the run log: {}", err);
+    }
+}
@@ -0,0 +1,123 @@
+use std::{fs, path::PathBuf};
+
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::consts;
+
+// Local save files, stored as ron next to the run log
+
+fn save_path(file_name: &str) -> PathBuf {
+    PathBuf::from(consts::SAVE_DIR).join(file_name)
+}
+
+// Missing save file is not an error, it just hasn't been written yet
+pub fn load<T: DeserializeOwned>(file_name: &str) -> Option<T> {
+    let path = save_path(file_name);
+
+    if !path.exists() {
+        return None;
+    }
+
+    match fs::read_to_string(&path)
+        .map_err(|err| err.to_string())
+        .and_then(|content| ron::from_str(&content).map_err(|err| err.to_string()))
+    {
+        Ok(value) => Some(value),
+        Err(err) => {
+            error!("Failed to load {}: {}", path.display(), err);
+            None
+        }
+    }
+}
+
+pub fn store<T: Serialize>(file_name: &str, value: &T) {
+    let path = save_path(file_name);
+
+    let result = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+        .map_err(|err| err.to_string())
+        .and_then(|content| {
+            fs::create_dir_all(consts::SAVE_DIR).map_err(|err| err.to_string())?;
+            fs::write(&path, content).map_err(|err| err.to_string())
+        });
+
+    if let Err(err) = result {
+        error!("Failed to save {}: {}", path.display(), err);
+    }
+}
+
+pub fn exists(file_name: &str) -> bool {
+    save_path(file_name).exists()
+}
+
+pub fn remove(file_name: &str) {
+    let path = save_path(file_name);
+
+    if let Err(err) = fs::remove_file(&path) {
+        error!("Failed to remove {}: {}", path.display(), err);
+    }
+}
+
+// Bevy timers don't implement serde, so they are saved as plain seconds
+pub mod timer_serde {
+    use bevy::{prelude::*, utils::Duration};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct TimerData {
+        duration: f32,
+        elapsed: f32,
+        repeating: bool,
+    }
+
+    pub fn serialize<S: Serializer>(timer: &Timer, serializer: S) -> Result<S::Ok, S::Error> {
+        TimerData {
+            duration: timer.duration().as_secs_f32(),
+            elapsed: timer.elapsed_secs(),
+            repeating: matches!(timer.mode(), TimerMode::Repeating),
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Timer, D::Error> {
+        let data = TimerData::deserialize(deserializer)?;
+        let mode = match data.repeating {
+            true => TimerMode::Repeating,
+            false => TimerMode::Once,
+        };
+
+        let mut timer = Timer::from_seconds(data.duration, mode);
+        timer.set_elapsed(Duration::from_secs_f32(data.elapsed));
+        Ok(timer)
+    }
+}
+
+pub mod stopwatch_serde {
+    use bevy::{time::Stopwatch, utils::Duration};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct StopwatchData {
+        elapsed: f32,
+        paused: bool,
+    }
+
+    pub fn serialize<S: Serializer>(watch: &Stopwatch, serializer: S) -> Result<S::Ok, S::Error> {
+        StopwatchData {
+            elapsed: watch.elapsed_secs(),
+            paused: watch.paused(),
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Stopwatch, D::Error> {
+        let data = StopwatchData::deserialize(deserializer)?;
+
+        let mut watch = Stopwatch::new();
+        watch.set_elapsed(Duration::from_secs_f32(data.elapsed));
+        if data.paused {
+            watch.pause();
+        }
+        Ok(watch)
+    }
+}
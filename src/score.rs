@@ -0,0 +1,134 @@
+use bevy::prelude::*;
+
+use crate::{
+    config::ScoreConfig,
+    consts,
+    events::{EventSet, SpaceshipDamaged},
+    is_playing, FontHandle, GameState,
+};
+
+pub struct ScorePlugin;
+
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Combo>()
+            // Combo is gone before the kills of the same frame are scored
+            .add_systems(
+                PreUpdate,
+                break_combo_on_damage
+                    .after(EventSet::HandleHit)
+                    .before(EventSet::HandleScore)
+                    .run_if(is_playing),
+            )
+            .add_systems(
+                Update,
+                (update_combo_window, animate_score_popups).run_if(is_playing),
+            )
+            .add_systems(OnExit(GameState::Gameplay), reset_combo);
+    }
+}
+
+// ===
+
+// Consecutive kills, each one has to happen before the combo window runs out
+#[derive(Resource, Debug, Default)]
+pub struct Combo {
+    pub kills: u32,
+    window: Timer,
+}
+
+impl Combo {
+    pub fn add_kill(&mut self, config: &ScoreConfig) {
+        self.kills += 1;
+        self.window = Timer::from_seconds(config.combo_window, TimerMode::Once);
+    }
+
+    pub fn reset(&mut self) {
+        self.kills = 0;
+        self.window.reset();
+    }
+
+    pub fn multiplier(&self, config: &ScoreConfig) -> u32 {
+        (1 + self.kills / config.combo_step.max(1)).min(config.max_multiplier)
+    }
+
+    // Part of the combo window that is left, from 1.0 down to 0.0
+    pub fn window_left(&self) -> f32 {
+        match self.kills {
+            0 => 0.0,
+            _ => self.window.percent_left(),
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct ScorePopup {
+    timer: Timer,
+}
+
+impl ScorePopup {
+    pub fn spawn(commands: &mut Commands, font: &FontHandle, translation: Vec3, points: u32) {
+        commands.spawn((
+            ScorePopup {
+                timer: Timer::from_seconds(consts::SCORE_POPUP_TIME, TimerMode::Once),
+            },
+            Text2dBundle {
+                text: Text::from_section(
+                    format!("+{}", points),
+                    TextStyle {
+                        font: font.0.clone(),
+                        font_size: 20.0,
+                        color: Color::YELLOW,
+                    },
+                ),
+                transform: Transform::from_translation(
+                    translation.truncate().extend(consts::SCORE_POPUP_Z),
+                ),
+                ..default()
+            },
+        ));
+    }
+}
+
+// ===
+
+fn update_combo_window(mut combo: ResMut<Combo>, time: Res<Time>) {
+    if combo.kills > 0 {
+        combo.window.tick(time.delta());
+
+        if combo.window.finished() {
+            combo.reset();
+        }
+    }
+}
+
+fn break_combo_on_damage(mut combo: ResMut<Combo>, mut ev_damaged: EventReader<SpaceshipDamaged>) {
+    if ev_damaged.iter().count() > 0 {
+        combo.reset();
+    }
+}
+
+fn reset_combo(mut combo: ResMut<Combo>) {
+    combo.reset();
+}
+
+// Popups float upwards and fade out
+fn animate_score_popups(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut ScorePopup, &mut Transform, &mut Text)>,
+) {
+    for (entity, mut popup, mut tf, mut text) in query.iter_mut() {
+        popup.timer.tick(time.delta());
+
+        tf.translation.y += consts::SCORE_POPUP_SPEED * time.delta_seconds();
+        text.sections[0]
+            .style
+            .color
+            .set_a(popup.timer.percent_left());
+
+        if popup.timer.finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
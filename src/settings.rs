@@ -0,0 +1,65 @@
+use bevy::{
+    prelude::*,
+    window::{PrimaryWindow, WindowMode},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{consts, save};
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(save::load::<Settings>(consts::SETTINGS_SAVE).unwrap_or_default())
+            .add_systems(
+                Update,
+                apply_settings.run_if(resource_changed::<Settings>()),
+            );
+    }
+}
+
+// ===
+
+// Player preferences, stored as soon as they are changed
+#[derive(Resource, Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Settings {
+    // Id of the picked string table
+    pub language: String,
+    pub fullscreen: bool,
+    // Size of the menus and the HUD
+    pub ui_scale: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            language: consts::DEFAULT_LANGUAGE.to_string(),
+            fullscreen: false,
+            ui_scale: 1.0,
+        }
+    }
+}
+
+impl Settings {
+    pub fn store(&self) {
+        save::store(consts::SETTINGS_SAVE, self);
+    }
+}
+
+// ===
+
+fn apply_settings(
+    settings: Res<Settings>,
+    mut ui_scale: ResMut<UiScale>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    ui_scale.scale = settings.ui_scale as f64;
+
+    if let Ok(mut window) = window_query.get_single_mut() {
+        window.mode = match settings.fullscreen {
+            true => WindowMode::BorderlessFullscreen,
+            false => WindowMode::Windowed,
+        };
+    }
+}
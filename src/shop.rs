@@ -0,0 +1,116 @@
+use bevy::prelude::*;
+use rand::seq::IteratorRandom;
+
+use crate::{
+    config::{GameConfig, UpgradeConfig},
+    events::WaveEnded,
+    is_gameplay, is_playing,
+    player::{Downed, RunUpgrades, ShipDefinition, Spaceship, SpaceshipHealth},
+    rng::GameRng,
+    transition::ChangeState,
+    GameplayState,
+};
+
+pub struct ShopPlugin;
+
+impl Plugin for ShopPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShopOffers>()
+            .add_event::<BuyUpgrade>()
+            .add_systems(Update, open_shop.run_if(is_playing))
+            .add_systems(
+                Update,
+                buy_upgrades.run_if(in_state(GameplayState::Shop).and_then(is_gameplay)),
+            );
+    }
+}
+
+// ===
+
+#[derive(Debug)]
+pub struct ShopOffer {
+    pub upgrade: UpgradeConfig,
+    pub price: u32,
+    pub sold: bool,
+}
+
+// Upgrades offered in the current shop
+#[derive(Resource, Debug, Default)]
+pub struct ShopOffers(pub Vec<ShopOffer>);
+
+impl ShopOffers {
+    fn roll(config: &GameConfig, upgrades: &RunUpgrades, rng: &mut GameRng) -> Self {
+        let offers = config
+            .shop
+            .upgrades
+            .iter()
+            .choose_multiple(&mut **rng, config.shop.offers)
+            .into_iter()
+            .map(|upgrade| ShopOffer {
+                price: upgrade.price + upgrade.price_step * upgrades.times_bought(&upgrade.id),
+                upgrade: upgrade.clone(),
+                sold: false,
+            })
+            .collect();
+
+        Self(offers)
+    }
+}
+
+// Offer with the given index was picked in the shop
+#[derive(Event)]
+pub struct BuyUpgrade(pub usize);
+
+// ===
+
+// Shop opens once a wave is cleared, the next wave waits until it's closed
+fn open_shop(
+    mut commands: Commands,
+    mut ev_change: EventWriter<ChangeState>,
+    mut ev_wave_ended: EventReader<WaveEnded>,
+    mut rng: ResMut<GameRng>,
+    config: Res<GameConfig>,
+    upgrades: Res<RunUpgrades>,
+) {
+    if ev_wave_ended.iter().count() == 0 || config.shop.upgrades.is_empty() {
+        return;
+    }
+
+    commands.insert_resource(ShopOffers::roll(&config, &upgrades, &mut rng));
+    ev_change.send(ChangeState::to(GameplayState::Shop));
+}
+
+// Added max health comes with the health to fill it
+fn buy_upgrades(
+    mut ev_buy: EventReader<BuyUpgrade>,
+    mut offers: ResMut<ShopOffers>,
+    mut upgrades: ResMut<RunUpgrades>,
+    mut spaceship_query: Query<
+        (&ShipDefinition, &mut SpaceshipHealth),
+        (With<Spaceship>, Without<Downed>),
+    >,
+) {
+    for buy_ev in ev_buy.iter() {
+        let Some(offer) = offers.0.get_mut(buy_ev.0) else {
+            continue;
+        };
+
+        if offer.sold || upgrades.scrap < offer.price {
+            continue;
+        }
+
+        let max_healths: Vec<u32> = spaceship_query
+            .iter()
+            .map(|(ship, _)| upgrades.max_health(ship))
+            .collect();
+
+        upgrades.scrap -= offer.price;
+        upgrades.add(&offer.upgrade.id, &offer.upgrade.modifiers);
+        offer.sold = true;
+
+        for ((ship, mut health), old_max_health) in spaceship_query.iter_mut().zip(max_healths) {
+            let max_health = upgrades.max_health(ship);
+            health.0 = (health.0 + max_health.saturating_sub(old_max_health)).min(max_health);
+        }
+    }
+}
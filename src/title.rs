@@ -0,0 +1,413 @@
+use std::f32::consts::TAU;
+
+use bevy::{input::mouse::MouseMotion, prelude::*, utils::HashSet};
+use bevy_mod_aseprite::{Aseprite, AsepriteAnimation, AsepriteBundle};
+use rand::{thread_rng, Rng};
+
+use crate::{
+    assets::{AddAssetCollection, AssetCollection},
+    common::{aseprite_size, AsteroidAssets, AsteroidType},
+    consts, despawn_entities,
+    player::{PlayerHandles, Spaceship},
+    GameState, WinSize,
+};
+
+pub struct TitlePlugin;
+
+impl Plugin for TitlePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AttractMode>()
+            .add_asset_collection::<TitleHandles>(GameState::LoadingMenu)
+            .add_systems(
+                OnEnter(GameState::MainMenu),
+                (spawn_title_ship, reset_title_scene),
+            )
+            .add_systems(
+                Update,
+                (
+                    track_inactivity,
+                    spawn_title_asteroids,
+                    fly_title_ship,
+                    drift_title_objects,
+                    hit_title_asteroids,
+                    break_title_asteroids,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::MainMenu)),
+            )
+            .add_systems(OnExit(GameState::MainMenu), despawn_entities::<TitleScene>);
+    }
+}
+
+// ===
+
+// Time since the last input on the title screen,
+// the demo plays behind the menu once it's been long enough
+#[derive(Resource)]
+pub struct AttractMode {
+    idle: Timer,
+}
+
+impl Default for AttractMode {
+    fn default() -> Self {
+        Self {
+            idle: Timer::from_seconds(consts::TITLE_ATTRACT_DELAY, TimerMode::Once),
+        }
+    }
+}
+
+impl AttractMode {
+    pub fn is_playing(&self) -> bool {
+        self.idle.finished()
+    }
+}
+
+// Time until the next asteroid drifts across the title screen
+#[derive(Resource)]
+struct NextTitleAsteroid(Timer);
+
+// Sprites of the ship idling on the title screen, the ships picked for a run
+// are only loaded with the rest of the gameplay assets
+#[derive(Resource)]
+struct TitleHandles {
+    spaceship: Handle<Image>,
+    propulsion: Handle<Aseprite>,
+}
+
+impl AssetCollection for TitleHandles {
+    fn load(asset_server: &AssetServer) -> Self {
+        Self {
+            spaceship: asset_server.load(consts::TITLE_SPRITE_SPACESHIP),
+            propulsion: asset_server.load(consts::TITLE_ASEPRITE_PROPULSION),
+        }
+    }
+
+    fn handles(&self) -> Vec<HandleUntyped> {
+        vec![
+            self.spaceship.clone_weak_untyped(),
+            self.propulsion.clone_weak_untyped(),
+        ]
+    }
+}
+
+// Everything spawned for the title screen, none of it is part of a run
+#[derive(Component)]
+struct TitleScene;
+
+#[derive(Component)]
+struct TitleShip {
+    shoot_cooldown: Timer,
+}
+
+#[derive(Component)]
+struct TitleAsteroid {
+    size: Vec2,
+}
+
+#[derive(Component)]
+struct TitleProjectile;
+
+// Asteroid or projectile moving on its own, despawned once it leaves the screen
+#[derive(Component)]
+struct TitleDrift {
+    velocity: Vec2,
+    spin: f32,
+}
+
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+struct TitleAsteroidBreaking(Timer);
+
+// ===
+
+fn reset_title_scene(mut commands: Commands, mut attract: ResMut<AttractMode>) {
+    *attract = AttractMode::default();
+    commands.insert_resource(NextTitleAsteroid(Timer::from_seconds(0.0, TimerMode::Once)));
+}
+
+// Ship idles in the middle, its propulsion is a child so it follows the ship around
+fn spawn_title_ship(
+    mut commands: Commands,
+    title_handles: Res<TitleHandles>,
+    images: Res<Assets<Image>>,
+    aseprites: Res<Assets<Aseprite>>,
+    win_size: Res<WinSize>,
+) {
+    let (spaceship_size, propulsion_aseprite) = match (
+        images.get(&title_handles.spaceship),
+        aseprites.get(&title_handles.propulsion),
+    ) {
+        (Some(image), Some(aseprite)) => (image.size(), aseprite),
+        _ => {
+            error!("Sprites of the title screen ship are not loaded");
+            return;
+        }
+    };
+
+    let propulsion_animation = AsepriteAnimation::new(propulsion_aseprite.info(), "thrust");
+
+    commands
+        .spawn((
+            TitleScene,
+            TitleShip {
+                shoot_cooldown: Timer::from_seconds(
+                    consts::TITLE_SHIP_SHOOT_COOLDOWN,
+                    TimerMode::Once,
+                ),
+            },
+            SpriteBundle {
+                texture: title_handles.spaceship.clone(),
+                transform: Transform::from_xyz(
+                    0.0,
+                    Spaceship::player_position(win_size.h),
+                    consts::PLAYER_Z,
+                ),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(AsepriteBundle {
+                texture_atlas: propulsion_aseprite.atlas().clone_weak(),
+                sprite: TextureAtlasSprite::new(propulsion_animation.current_frame()),
+                aseprite: title_handles.propulsion.clone_weak(),
+                animation: propulsion_animation,
+                transform: Transform::from_xyz(
+                    0.0,
+                    Spaceship::propulsion_offset(spaceship_size.y),
+                    consts::PLAYER_PROPULSION_Z - consts::PLAYER_Z,
+                ),
+                ..default()
+            });
+        });
+}
+
+// Any key, button or mouse movement restarts the wait for the demo and stops it if it's playing
+fn track_inactivity(
+    mut attract: ResMut<AttractMode>,
+    mut ev_mouse_motion: EventReader<MouseMotion>,
+    keys: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    time: Res<Time>,
+) {
+    let has_input = ev_mouse_motion.iter().count() > 0
+        || keys.get_pressed().next().is_some()
+        || mouse_buttons.get_pressed().next().is_some()
+        || gamepad_buttons.get_pressed().next().is_some();
+
+    if has_input {
+        attract.idle.reset();
+    } else {
+        attract.idle.tick(time.delta());
+    }
+}
+
+fn spawn_title_asteroids(
+    mut commands: Commands,
+    mut next_asteroid: ResMut<NextTitleAsteroid>,
+    attract: Res<AttractMode>,
+    asteroid_assets: AsteroidAssets,
+    win_size: Res<WinSize>,
+    time: Res<Time>,
+) {
+    next_asteroid.0.tick(time.delta());
+    if !next_asteroid.0.finished() {
+        return;
+    }
+
+    let mut rng = thread_rng();
+    let interval = match attract.is_playing() {
+        true => consts::TITLE_DEMO_ASTEROID_INTERVAL,
+        false => consts::TITLE_ASTEROID_INTERVAL,
+    };
+    next_asteroid.0 = Timer::from_seconds(
+        rng.gen_range(interval * 0.5..interval * 1.5),
+        TimerMode::Once,
+    );
+
+    let asteroid_type = match rng.gen_range(0..3) {
+        0 => AsteroidType::Small,
+        1 => AsteroidType::Medium,
+        _ => AsteroidType::Large,
+    };
+    let variants = asteroid_assets.handles.variants(asteroid_type);
+    let aseprite_handle = &variants[rng.gen_range(0..variants.len())];
+    let Some(aseprite) = asteroid_assets.aseprites.get(aseprite_handle) else {
+        return;
+    };
+    let animation = AsepriteAnimation::new(aseprite.info(), "idle");
+
+    let size = aseprite_size(aseprite);
+    let x = rng.gen_range(-win_size.w / 2.0..win_size.w / 2.0);
+    let y = win_size.h / 2.0 + size.y;
+    let velocity = Vec2::new(
+        rng.gen_range(
+            -consts::TITLE_ASTEROID_SIDEWAYS_SPEED..consts::TITLE_ASTEROID_SIDEWAYS_SPEED,
+        ),
+        asteroid_assets
+            .config
+            .asteroids
+            .get(asteroid_type)
+            .initial_velocity()
+            .y
+            * consts::TITLE_ASTEROID_SPEED,
+    );
+
+    commands.spawn((
+        TitleScene,
+        TitleAsteroid { size },
+        TitleDrift {
+            velocity,
+            spin: rng.gen_range(-consts::ASTEROID_MAX_SPIN..consts::ASTEROID_MAX_SPIN),
+        },
+        AsepriteBundle {
+            texture_atlas: aseprite.atlas().clone_weak(),
+            sprite: TextureAtlasSprite::new(animation.current_frame()),
+            aseprite: aseprite_handle.clone_weak(),
+            animation,
+            transform: Transform::from_xyz(x, y, consts::ENEMY_Z)
+                .with_rotation(Quat::from_rotation_z(rng.gen_range(0.0..TAU))),
+            ..default()
+        },
+    ));
+}
+
+// In the demo an autopilot lines up below the lowest asteroid and shoots it,
+// otherwise the ship flies back to the middle and bobs in place
+fn fly_title_ship(
+    mut commands: Commands,
+    attract: Res<AttractMode>,
+    mut ship_query: Query<(&mut Transform, &mut TitleShip)>,
+    asteroid_query: Query<
+        &Transform,
+        (
+            With<TitleAsteroid>,
+            Without<TitleAsteroidBreaking>,
+            Without<TitleShip>,
+        ),
+    >,
+    player_handles: Res<PlayerHandles>,
+    win_size: Res<WinSize>,
+    time: Res<Time>,
+) {
+    let Ok((mut tf, mut ship)) = ship_query.get_single_mut() else {
+        return;
+    };
+
+    ship.shoot_cooldown.tick(time.delta());
+
+    let target = match attract.is_playing() {
+        true => asteroid_query
+            .iter()
+            .map(|asteroid_tf| asteroid_tf.translation)
+            .filter(|position| position.y > tf.translation.y && position.y < win_size.h / 2.0)
+            .min_by(|a, b| a.y.total_cmp(&b.y)),
+        false => None,
+    };
+    let target_x = match (attract.is_playing(), target) {
+        (true, Some(target)) => target.x,
+        (true, None) => tf.translation.x,
+        (false, _) => 0.0,
+    };
+
+    let distance = target_x - tf.translation.x;
+    let speed = (distance * consts::TITLE_SHIP_EASING)
+        .clamp(-consts::TITLE_SHIP_SPEED, consts::TITLE_SHIP_SPEED);
+    tf.translation.x += speed * time.delta_seconds();
+
+    let bob = (time.elapsed_seconds() * consts::TITLE_SHIP_BOB_SPEED * TAU).sin();
+    tf.translation.y = Spaceship::player_position(win_size.h) + bob * consts::TITLE_SHIP_BOB;
+
+    let is_aimed = target.is_some() && distance.abs() <= consts::TITLE_SHIP_AIM;
+    if is_aimed && ship.shoot_cooldown.finished() {
+        ship.shoot_cooldown.reset();
+        commands.spawn((
+            TitleScene,
+            TitleProjectile,
+            TitleDrift {
+                velocity: Vec2::new(0.0, consts::TITLE_PROJECTILE_SPEED),
+                spin: 0.0,
+            },
+            SpriteBundle {
+                texture: player_handles.projectile.clone(),
+                transform: Transform::from_xyz(
+                    tf.translation.x,
+                    tf.translation.y,
+                    consts::PLAYER_PROJECTILE_Z,
+                ),
+                ..default()
+            },
+        ));
+    }
+}
+
+fn drift_title_objects(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Transform, &TitleDrift)>,
+    win_size: Res<WinSize>,
+    time: Res<Time>,
+) {
+    let h_bound = win_size.h / 2.0 + consts::DESPAWN_MARGIN;
+    let w_bound = win_size.w / 2.0 + consts::DESPAWN_MARGIN;
+
+    for (entity, mut tf, drift) in query.iter_mut() {
+        tf.translation += drift.velocity.extend(0.0) * time.delta_seconds();
+        tf.rotate_z(drift.spin * time.delta_seconds());
+
+        if tf.translation.y.abs() > h_bound || tf.translation.x.abs() > w_bound {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+// Demo shots break asteroids like in a run, without any score or stats
+fn hit_title_asteroids(
+    mut commands: Commands,
+    projectile_query: Query<(Entity, &Transform), With<TitleProjectile>>,
+    asteroid_query: Query<
+        (Entity, &Transform, &TitleAsteroid, &Handle<Aseprite>),
+        Without<TitleAsteroidBreaking>,
+    >,
+    aseprites: Res<Assets<Aseprite>>,
+) {
+    let mut broken = HashSet::new();
+
+    for (projectile, projectile_tf) in projectile_query.iter() {
+        let hit = asteroid_query
+            .iter()
+            .find(|(entity, asteroid_tf, asteroid, _)| {
+                !broken.contains(entity)
+                    && Rect::from_center_size(asteroid_tf.translation.truncate(), asteroid.size)
+                        .contains(projectile_tf.translation.truncate())
+            });
+        let Some((entity, _, _, aseprite_handle)) = hit else {
+            continue;
+        };
+
+        broken.insert(entity);
+        commands.entity(projectile).despawn();
+
+        let mut asteroid = commands.entity(entity);
+        asteroid.insert(TitleAsteroidBreaking(Timer::from_seconds(
+            consts::ASTEROID_BREAK_ANIMATION_TIME,
+            TimerMode::Once,
+        )));
+        if let Some(aseprite) = aseprites.get(aseprite_handle) {
+            asteroid.insert(AsepriteAnimation::new(aseprite.info(), "break"));
+        }
+    }
+}
+
+fn break_title_asteroids(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut TitleAsteroidBreaking)>,
+) {
+    for (entity, mut breaking) in query.iter_mut() {
+        breaking.0.tick(time.delta());
+
+        if breaking.0.finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,346 @@
+use bevy::{prelude::*, ui::FocusPolicy};
+
+use crate::{background, consts, GameState, GameplayState};
+
+pub struct TransitionPlugin;
+
+impl Plugin for TransitionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ChangeState>()
+            .init_resource::<Transitions>()
+            .add_systems(Startup, create_iris_image)
+            .add_systems(Update, (start_transitions, play_transitions).chain());
+    }
+}
+
+// ===
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StateChange {
+    Game(GameState),
+    Gameplay(GameplayState),
+}
+
+impl From<GameState> for StateChange {
+    fn from(state: GameState) -> Self {
+        StateChange::Game(state)
+    }
+}
+
+impl From<GameplayState> for StateChange {
+    fn from(state: GameplayState) -> Self {
+        StateChange::Gameplay(state)
+    }
+}
+
+impl StateChange {
+    fn apply(self, commands: &mut Commands) {
+        match self {
+            StateChange::Game(state) => commands.insert_resource(NextState(Some(state))),
+            StateChange::Gameplay(state) => commands.insert_resource(NextState(Some(state))),
+        }
+    }
+}
+
+// Asks for a state change, which waits until the transition has covered the screen.
+// Changes asked for in the same frame share one transition
+#[derive(Event)]
+pub struct ChangeState(pub StateChange);
+
+impl ChangeState {
+    pub fn to(state: impl Into<StateChange>) -> Self {
+        ChangeState(state.into())
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum TransitionEffect {
+    // Screen goes dark and back
+    Fade,
+    // Screen is swept over from the left and uncovered to the right
+    Wipe,
+    // Circle closes on the center of the screen and opens up again
+    Iris,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Transition {
+    effect: TransitionEffect,
+    // Seconds of each half, covering and uncovering the screen
+    secs: f32,
+}
+
+// Transition played when going from one state to another, the change is instant without one
+fn transition_between(from: StateChange, to: StateChange) -> Option<Transition> {
+    use GameState as Game;
+    use GameplayState as Gameplay;
+    use StateChange as Change;
+    use TransitionEffect::*;
+
+    let (effect, secs) = match (from, to) {
+        (_, Change::Game(Game::LoadingError)) => return None,
+        (Change::Game(Game::LoadingMenu), Change::Game(Game::MainMenu)) => (Fade, 0.4),
+        (Change::Game(_), Change::Game(Game::LoadingGame)) => (Fade, 0.3),
+        (Change::Game(Game::LoadingGame), Change::Game(Game::Gameplay)) => (Iris, 0.5),
+        (Change::Game(Game::Gameplay), Change::Game(Game::MainMenu)) => (Fade, 0.4),
+        // Between menus
+        (Change::Game(_), Change::Game(_)) => (Wipe, 0.25),
+        (Change::Gameplay(Gameplay::Playing), Change::Gameplay(Gameplay::Paused))
+        | (Change::Gameplay(Gameplay::Paused), Change::Gameplay(Gameplay::Playing)) => (Fade, 0.1),
+        (Change::Gameplay(Gameplay::Playing), Change::Gameplay(Gameplay::Shop))
+        | (Change::Gameplay(Gameplay::Shop), Change::Gameplay(Gameplay::Playing)) => (Wipe, 0.25),
+        (Change::Gameplay(Gameplay::Playing), Change::Gameplay(Gameplay::Continue))
+        | (Change::Gameplay(Gameplay::Continue), Change::Gameplay(Gameplay::Playing)) => {
+            (Fade, 0.3)
+        }
+        (
+            Change::Gameplay(Gameplay::Playing | Gameplay::Continue),
+            Change::Gameplay(Gameplay::GameOver),
+        ) => (Iris, 0.6),
+        _ => return None,
+    };
+
+    Some(Transition { effect, secs })
+}
+
+#[derive(Debug)]
+struct ActiveTransition {
+    transition: Transition,
+    timer: Timer,
+    // Covering the screen, the state changes once it's fully covered
+    is_covering: bool,
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct Transitions {
+    // Changes applied once the screen is covered
+    pending: Vec<StateChange>,
+    // Changes asked for while a transition was playing
+    queued: Vec<StateChange>,
+    active: Option<ActiveTransition>,
+}
+
+pub fn is_idle(transitions: Res<Transitions>) -> bool {
+    transitions.active.is_none()
+}
+
+#[derive(Resource)]
+struct IrisImage(Handle<Image>);
+
+#[derive(Component)]
+struct TransitionOverlay;
+
+// Node that is resized or recolored as the transition plays
+#[derive(Component)]
+struct TransitionCover;
+
+// ===
+
+// Black square with a round hole, scaled to open and close the iris
+fn create_iris_image(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let image = background::new_image(consts::TRANSITION_IRIS_RESOLUTION, |u, v| {
+        match u * u + v * v > 1.0 {
+            true => [0, 0, 0, 255],
+            false => [0, 0, 0, 0],
+        }
+    });
+
+    commands.insert_resource(IrisImage(images.add(image)));
+}
+
+fn spawn_overlay(commands: &mut Commands, effect: TransitionEffect, iris_image: &IrisImage) {
+    let black = || NodeBundle {
+        style: Style {
+            flex_grow: 1.0,
+            ..default()
+        },
+        background_color: Color::BLACK.into(),
+        ..default()
+    };
+
+    let mut overlay = commands.spawn((
+        TransitionOverlay,
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::Column,
+                // Keeps the iris centered while it's larger than the screen
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            // Nothing below can be clicked while the screen changes
+            focus_policy: FocusPolicy::Block,
+            z_index: ZIndex::Global(50),
+            ..default()
+        },
+    ));
+
+    match effect {
+        TransitionEffect::Fade => {
+            overlay.insert((TransitionCover, BackgroundColor(Color::NONE)));
+        }
+        TransitionEffect::Wipe => {
+            overlay.with_children(|parent| {
+                parent.spawn((
+                    TransitionCover,
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Percent(0.0),
+                            height: Val::Percent(100.0),
+                            position_type: PositionType::Absolute,
+                            left: Val::Px(0.0),
+                            ..default()
+                        },
+                        background_color: Color::BLACK.into(),
+                        ..default()
+                    },
+                ));
+            });
+        }
+        // Black rows and columns fill the screen around the hole
+        TransitionEffect::Iris => {
+            overlay.with_children(|parent| {
+                parent.spawn(black());
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            flex_shrink: 0.0,
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(black());
+                        parent.spawn((
+                            TransitionCover,
+                            ImageBundle {
+                                style: Style {
+                                    width: Val::VMax(consts::TRANSITION_IRIS_SIZE),
+                                    height: Val::VMax(consts::TRANSITION_IRIS_SIZE),
+                                    flex_shrink: 0.0,
+                                    ..default()
+                                },
+                                image: UiImage::new(iris_image.0.clone()),
+                                ..default()
+                            },
+                        ));
+                        parent.spawn(black());
+                    });
+                parent.spawn(black());
+            });
+        }
+    }
+}
+
+fn start_transitions(
+    mut commands: Commands,
+    mut ev_change: EventReader<ChangeState>,
+    mut transitions: ResMut<Transitions>,
+    game_state: Res<State<GameState>>,
+    gameplay_state: Res<State<GameplayState>>,
+    iris_image: Res<IrisImage>,
+) {
+    // Changes keep being asked for every frame by some systems until the state is left
+    for ChangeState(change) in ev_change.iter() {
+        if !transitions.pending.contains(change) && !transitions.queued.contains(change) {
+            transitions.queued.push(*change);
+        }
+    }
+
+    if transitions.active.is_some() || transitions.queued.is_empty() {
+        return;
+    }
+
+    let current = |change: &StateChange| match change {
+        StateChange::Game(_) => StateChange::Game(*game_state.get()),
+        StateChange::Gameplay(_) => StateChange::Gameplay(*gameplay_state.get()),
+    };
+
+    let changes: Vec<StateChange> = transitions
+        .queued
+        .drain(..)
+        .filter(|change| current(change) != *change)
+        .collect();
+    let transition = changes
+        .iter()
+        .find_map(|change| transition_between(current(change), *change));
+
+    match transition {
+        Some(transition) => {
+            spawn_overlay(&mut commands, transition.effect, &iris_image);
+            transitions.pending = changes;
+            transitions.active = Some(ActiveTransition {
+                transition,
+                timer: Timer::from_seconds(transition.secs, TimerMode::Once),
+                is_covering: true,
+            });
+        }
+        None => {
+            for change in changes {
+                change.apply(&mut commands);
+            }
+        }
+    }
+}
+
+fn play_transitions(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut transitions: ResMut<Transitions>,
+    overlay_query: Query<Entity, With<TransitionOverlay>>,
+    mut cover_query: Query<(&mut Style, &mut BackgroundColor), With<TransitionCover>>,
+) {
+    let Some(active) = transitions.active.as_mut() else {
+        return;
+    };
+
+    active.timer.tick(time.delta());
+
+    // Share of the screen that is covered
+    let coverage = match active.is_covering {
+        true => active.timer.percent(),
+        false => active.timer.percent_left(),
+    };
+
+    for (mut style, mut background_color) in cover_query.iter_mut() {
+        match active.transition.effect {
+            TransitionEffect::Fade => {
+                background_color.0 = Color::rgba(0.0, 0.0, 0.0, coverage);
+            }
+            TransitionEffect::Wipe => {
+                style.width = Val::Percent(coverage * 100.0);
+                (style.left, style.right) = match active.is_covering {
+                    true => (Val::Px(0.0), Val::Auto),
+                    false => (Val::Auto, Val::Px(0.0)),
+                };
+            }
+            TransitionEffect::Iris => {
+                let size = Val::VMax(consts::TRANSITION_IRIS_SIZE * (1.0 - coverage));
+                style.width = size;
+                style.height = size;
+            }
+        }
+    }
+
+    if !active.timer.finished() {
+        return;
+    }
+
+    // The new state is entered behind the covered screen, then uncovered
+    if active.is_covering {
+        active.is_covering = false;
+        active.timer.reset();
+        for change in transitions.pending.iter() {
+            change.apply(&mut commands);
+        }
+    } else {
+        transitions.active = None;
+        transitions.pending.clear();
+        for entity in overlay_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
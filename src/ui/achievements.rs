@@ -0,0 +1,144 @@
+use bevy::prelude::*;
+
+use crate::{
+    achievements::{AchievementList, AchievementSave, AchievementUnlocked, AchievementsHandle},
+    consts,
+    locale::Localization,
+};
+
+use super::widgets::{ButtonBuilder, FocusList};
+
+#[derive(Component)]
+pub struct AchievementsUi;
+
+#[derive(Component)]
+pub struct AchievementsBackBtn;
+
+#[derive(Component)]
+pub struct AchievementToast {
+    timer: Timer,
+}
+
+impl AchievementToast {
+    fn unlocked_color() -> Color {
+        Color::rgb(1.0, 0.85, 0.2)
+    }
+
+    fn locked_color() -> Color {
+        Color::rgb(0.5, 0.5, 0.5)
+    }
+}
+
+// ===
+
+pub fn setup_achievements_ui(
+    mut commands: Commands,
+    localization: Localization,
+    achievement_save: Res<AchievementSave>,
+    achievements_handle: Res<AchievementsHandle>,
+    achievement_lists: Res<Assets<AchievementList>>,
+) {
+    commands
+        .spawn((
+            AchievementsUi,
+            FocusList,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(20.0)),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(localization.label("achievements-title", 60.0, Color::WHITE));
+        })
+        .with_children(|parent| {
+            if let Some(achievement_list) = achievement_lists.get(&achievements_handle.0) {
+                for achievement in achievement_list.achievements.iter() {
+                    let color = match achievement_save.is_unlocked(&achievement.id) {
+                        true => AchievementToast::unlocked_color(),
+                        false => AchievementToast::locked_color(),
+                    };
+
+                    parent.spawn(TextBundle::from_sections([
+                        TextSection::new(
+                            format!("{}\n", localization.text(&achievement.name)),
+                            localization.style(30.0, color),
+                        ),
+                        TextSection::new(
+                            localization.text(&achievement.description),
+                            localization.style(20.0, color),
+                        ),
+                    ]));
+                }
+            }
+        })
+        .with_children(|parent| {
+            ButtonBuilder::new(&localization, "back").spawn(parent, AchievementsBackBtn);
+        });
+}
+
+pub fn show_achievement_toasts(
+    mut commands: Commands,
+    mut ev_unlocked: EventReader<AchievementUnlocked>,
+    localization: Localization,
+    toast_query: Query<(), With<AchievementToast>>,
+) {
+    for (toast_count, unlocked_ev) in (toast_query.iter().count()..).zip(ev_unlocked.iter()) {
+        commands
+            .spawn((
+                AchievementToast {
+                    timer: Timer::from_seconds(consts::ACHIEVEMENT_TOAST_TIME, TimerMode::Once),
+                },
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        // Toasts stack above each other
+                        bottom: Val::Px(10.0 + toast_count as f32 * 60.0),
+                        right: Val::Px(10.0),
+                        padding: UiRect::all(Val::Px(5.0)),
+                        ..default()
+                    },
+                    background_color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+                    ..default()
+                },
+            ))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_sections([
+                    TextSection::new(
+                        format!(
+                            "{}\n",
+                            localization.format(
+                                "achievement-unlocked",
+                                &[("name", &localization.text(&unlocked_ev.name))]
+                            )
+                        ),
+                        localization.style(20.0, AchievementToast::unlocked_color()),
+                    ),
+                    TextSection::new(
+                        localization.text(&unlocked_ev.description),
+                        localization.style(15.0, Color::WHITE),
+                    ),
+                ]));
+            });
+    }
+}
+
+pub fn update_achievement_toasts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut AchievementToast)>,
+) {
+    for (entity, mut toast) in query.iter_mut() {
+        toast.timer.tick(time.delta());
+
+        if toast.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
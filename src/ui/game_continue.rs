@@ -0,0 +1,77 @@
+use bevy::prelude::*;
+
+use crate::{config::GameConfig, locale::Localization, transition::ChangeState, GameplayState};
+
+use super::widgets::{spawn_modal, ButtonBuilder};
+
+#[derive(Component)]
+pub struct MenuContinue;
+
+#[derive(Component)]
+pub struct MenuContinueCountdown;
+
+#[derive(Component)]
+pub struct MenuContinueBtn;
+
+#[derive(Component)]
+pub struct MenuGiveUpBtn;
+
+// Time left to decide before the run is over
+#[derive(Resource)]
+pub struct ContinueCountdown(Timer);
+
+impl ContinueCountdown {
+    fn secs_left(&self) -> u32 {
+        self.0.remaining_secs().ceil() as u32
+    }
+}
+
+// ===
+
+pub fn setup_continue_menu(
+    mut commands: Commands,
+    localization: Localization,
+    config: Res<GameConfig>,
+) {
+    let countdown = ContinueCountdown(Timer::from_seconds(
+        config.lives.continue_time,
+        TimerMode::Once,
+    ));
+
+    spawn_modal(&mut commands, MenuContinue).with_children(|parent| {
+        parent.spawn(localization.label("continue-title", 80.0, Color::WHITE));
+        parent.spawn((
+            MenuContinueCountdown,
+            TextBundle::from_section(
+                countdown.secs_left().to_string(),
+                localization.style(60.0, Color::WHITE),
+            ),
+        ));
+        parent.spawn(localization.label("continue-score-reset", 20.0, Color::WHITE));
+        ButtonBuilder::new(&localization, "continue-continue")
+            .height(Val::Px(50.0))
+            .spawn(parent, MenuContinueBtn);
+        ButtonBuilder::new(&localization, "continue-give-up")
+            .height(Val::Px(50.0))
+            .spawn(parent, MenuGiveUpBtn);
+    });
+
+    commands.insert_resource(countdown);
+}
+
+pub fn update_continue_countdown(
+    mut ev_change: EventWriter<ChangeState>,
+    time: Res<Time>,
+    mut countdown: ResMut<ContinueCountdown>,
+    mut ui_query: Query<&mut Text, With<MenuContinueCountdown>>,
+) {
+    countdown.0.tick(time.delta());
+
+    if let Ok(mut ui_element) = ui_query.get_single_mut() {
+        ui_element.sections[0].value = countdown.secs_left().to_string();
+    }
+
+    if countdown.0.finished() {
+        ev_change.send(ChangeState::to(GameplayState::GameOver));
+    }
+}
@@ -1,6 +1,11 @@
-use crate::{FontHandle, Stats};
+use crate::{
+    config::GameConfig, locale::Localization, player::PlayerCount, profile::Profile,
+    run_stats::RunStats, Stats,
+};
 use bevy::prelude::*;
 
+use super::widgets::{is_pressed, spawn_modal, Activated, ButtonBuilder, JustPressed};
+
 #[derive(Component)]
 pub struct MenuGameOver;
 
@@ -10,105 +15,175 @@ pub struct MenuPlayAgainBtn;
 #[derive(Component)]
 pub struct MenuQuitBtn;
 
+#[derive(Component)]
+pub struct MenuSummaryText;
+
+#[derive(Component)]
+pub struct MenuSummaryPageNumber;
+
+#[derive(Component)]
+pub struct MenuSummaryPrevBtn;
+
+#[derive(Component)]
+pub struct MenuSummaryNextBtn;
+
+// Page of the run summary that is currently shown
+#[derive(Resource, Default)]
+pub struct SummaryPage(usize);
+
+fn summary_pages(run_stats: &RunStats, localization: &Localization) -> Vec<Vec<String>> {
+    let line =
+        |key: &str, value: &dyn std::fmt::Display| localization.format(key, &[("value", value)]);
+
+    vec![
+        vec![
+            localization.text("summary-combat"),
+            line("summary-shots-fired", &run_stats.shots_fired),
+            line("summary-hits", &run_stats.hits),
+            line("summary-accuracy", &format!("{:.0}", run_stats.accuracy())),
+            line("summary-charged-shots", &run_stats.charged_shots),
+            line("summary-max-combo", &run_stats.max_combo),
+        ],
+        vec![
+            localization.text("summary-asteroids"),
+            line("summary-small", &run_stats.asteroids_small),
+            line("summary-medium", &run_stats.asteroids_medium),
+            line("summary-large", &run_stats.asteroids_large),
+        ],
+        vec![
+            localization.text("summary-flight"),
+            line("summary-dashes", &run_stats.dashes),
+            line("summary-damage-taken", &run_stats.damage_taken),
+            line("summary-waves-cleared", &run_stats.waves_cleared),
+        ],
+    ]
+}
+
+fn summary_page_number(page: usize, page_count: usize) -> String {
+    format!("{}/{}", page + 1, page_count)
+}
+
 // ===
 
-pub fn setup_game_over_menu(mut commands: Commands, font: Res<FontHandle>, stats: Res<Stats>) {
-    commands
-        .spawn((
-            MenuGameOver,
-            NodeBundle {
+pub fn setup_game_over_menu(
+    mut commands: Commands,
+    localization: Localization,
+    stats: Res<Stats>,
+    run_stats: Res<RunStats>,
+    player_count: Res<PlayerCount>,
+    config: Res<GameConfig>,
+) {
+    commands.insert_resource(SummaryPage::default());
+    let pages = summary_pages(&run_stats, &localization);
+
+    spawn_modal(&mut commands, MenuGameOver).with_children(|parent| {
+        parent.spawn(localization.label("game-over-title", 80.0, Color::WHITE));
+        parent.spawn(TextBundle::from_section(
+            localization.format("game-over-time", &[("time", &stats.get_watch_time())]),
+            localization.style(20.0, Color::WHITE),
+        ));
+        parent.spawn(TextBundle::from_section(
+            localization.format("score", &[("score", &stats.score)]),
+            localization.style(40.0, Color::WHITE),
+        ));
+
+        if player_count.is_coop() {
+            for (idx, score) in stats.player_scores[..player_count.0].iter().enumerate() {
+                parent.spawn(TextBundle::from_section(
+                    localization.format(
+                        "game-over-player-score",
+                        &[("player", &(idx + 1)), ("score", score)],
+                    ),
+                    localization.style(20.0, Color::WHITE),
+                ));
+            }
+        }
+
+        parent.spawn(TextBundle::from_section(
+            localization.format(
+                "game-over-credits",
+                &[(
+                    "credits",
+                    &Profile::run_credits(&stats, &run_stats, &config),
+                )],
+            ),
+            localization.style(20.0, Color::YELLOW),
+        ));
+        parent.spawn((
+            MenuSummaryText,
+            TextBundle::from_section(pages[0].join("\n"), localization.style(20.0, Color::WHITE))
+                .with_style(Style {
+                    margin: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                }),
+        ));
+        parent
+            .spawn(NodeBundle {
                 style: Style {
-                    width: Val::Percent(100.0),
-                    height: Val::Percent(100.0),
-                    position_type: PositionType::Absolute,
-                    justify_content: JustifyContent::Center,
-                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
                     ..default()
                 },
-                background_color: Color::rgba(0.0, 0.0, 0.0, 0.5).into(),
                 ..default()
-            },
-        ))
-        .with_children(|parent| {
-            parent.spawn(TextBundle::from_section(
-                "GAME OVER",
-                TextStyle {
-                    font: font.0.clone(),
-                    font_size: 80.0,
-                    color: Color::WHITE,
-                },
-            ));
-        })
-        .with_children(|parent| {
-            parent.spawn(TextBundle::from_section(
-                format!("ADVENTURE LASTED: {}", stats.get_watch_time()),
-                TextStyle {
-                    font: font.0.clone(),
-                    font_size: 20.0,
-                    color: Color::WHITE,
-                },
-            ));
-        })
-        .with_children(|parent| {
-            parent.spawn(TextBundle::from_section(
-                format!("SCORE: {}", stats.score),
-                TextStyle {
-                    font: font.0.clone(),
-                    font_size: 40.0,
-                    color: Color::WHITE,
-                },
-            ));
-        })
-        .with_children(|parent| {
-            parent
-                .spawn((
-                    MenuPlayAgainBtn,
-                    ButtonBundle {
-                        style: Style {
-                            padding: UiRect::all(Val::Px(5.0)),
-                            height: Val::Px(50.0),
-                            margin: UiRect::all(Val::Px(5.0)),
-                            ..default()
-                        },
-                        background_color: Color::WHITE.into(),
-                        ..default()
-                    },
-                ))
-                .with_children(|parent| {
-                    parent.spawn(TextBundle::from_section(
-                        "TRY AGAIN",
-                        TextStyle {
-                            font: font.0.clone(),
-                            font_size: 40.0,
-                            color: Color::BLACK,
-                        },
-                    ));
-                });
-        })
-        .with_children(|parent| {
-            parent
-                .spawn((
-                    MenuQuitBtn,
-                    ButtonBundle {
-                        style: Style {
-                            padding: UiRect::all(Val::Px(5.0)),
-                            height: Val::Px(50.0),
-                            margin: UiRect::all(Val::Px(5.0)),
-                            ..default()
-                        },
-                        background_color: Color::WHITE.into(),
-                        ..default()
-                    },
-                ))
-                .with_children(|parent| {
-                    parent.spawn(TextBundle::from_section(
-                        "QUIT",
-                        TextStyle {
-                            font: font.0.clone(),
-                            font_size: 40.0,
-                            color: Color::BLACK,
-                        },
-                    ));
-                });
-        });
+            })
+            .with_children(|parent| {
+                ButtonBuilder::text("<", localization.style(30.0, Color::BLACK))
+                    .size(Val::Px(40.0), Val::Px(40.0))
+                    .spawn(parent, MenuSummaryPrevBtn);
+                // Page number sits between the two arrows
+                parent.spawn((
+                    MenuSummaryPageNumber,
+                    TextBundle::from_section(
+                        summary_page_number(0, pages.len()),
+                        localization.style(20.0, Color::WHITE),
+                    ),
+                ));
+                ButtonBuilder::text(">", localization.style(30.0, Color::BLACK))
+                    .size(Val::Px(40.0), Val::Px(40.0))
+                    .spawn(parent, MenuSummaryNextBtn);
+            });
+        ButtonBuilder::new(&localization, "game-over-try-again")
+            .height(Val::Px(50.0))
+            .initial_focus()
+            .spawn(parent, MenuPlayAgainBtn);
+        ButtonBuilder::new(&localization, "game-over-quit")
+            .height(Val::Px(50.0))
+            .spawn(parent, MenuQuitBtn);
+    });
+}
+
+pub fn change_summary_page(
+    mut page: ResMut<SummaryPage>,
+    run_stats: Res<RunStats>,
+    localization: Localization,
+    prev_query: Query<(&Interaction, Option<&Activated>), (JustPressed, With<MenuSummaryPrevBtn>)>,
+    next_query: Query<(&Interaction, Option<&Activated>), (JustPressed, With<MenuSummaryNextBtn>)>,
+    mut text_query: Query<&mut Text, With<MenuSummaryText>>,
+    mut page_number_query: Query<
+        &mut Text,
+        (With<MenuSummaryPageNumber>, Without<MenuSummaryText>),
+    >,
+) {
+    let pages = summary_pages(&run_stats, &localization);
+
+    for (interaction, activated) in prev_query.iter() {
+        if is_pressed(interaction, activated) {
+            page.0 = (page.0 + pages.len() - 1) % pages.len();
+        }
+    }
+
+    for (interaction, activated) in next_query.iter() {
+        if is_pressed(interaction, activated) {
+            page.0 = (page.0 + 1) % pages.len();
+        }
+    }
+
+    if page.is_changed() {
+        if let Ok(mut ui_element) = text_query.get_single_mut() {
+            ui_element.sections[0].value = pages[page.0].join("\n");
+        }
+
+        if let Ok(mut ui_element) = page_number_query.get_single_mut() {
+            ui_element.sections[0].value = summary_page_number(page.0, pages.len());
+        }
+    }
 }
@@ -1,10 +1,26 @@
 use bevy::prelude::*;
 
-use crate::{consts, player::SpaceshipHealth, Stats};
+use crate::{
+    config::GameConfig,
+    consts,
+    enemy::GameplayStage,
+    locale::Localization,
+    player::{
+        Lives, PlayerCount, PlayerId, RunUpgrades, ShipDefinition, SpaceshipHealth, SpaceshipShoot,
+    },
+    score::Combo,
+    Stats,
+};
+
+use super::widgets::ButtonBuilder;
 
 #[derive(Component)]
 pub struct GameplayUi;
 
+// Row of health points of a player, grows with max health upgrades
+#[derive(Component)]
+pub struct HealthPoints(PlayerId);
+
 #[derive(Component)]
 pub struct HealthPoint;
 
@@ -18,12 +34,34 @@ impl HealthPoint {
     }
 }
 
+// Fills up while the secondary weapon of the player charges
+#[derive(Component)]
+pub struct GameplayChargeBar(PlayerId);
+
+// Lives left of the player
+#[derive(Component)]
+pub struct GameplayLives(PlayerId);
+
 #[derive(Component)]
 pub struct GameplayTime;
 
 #[derive(Component)]
 pub struct GameplayScore;
 
+#[derive(Component)]
+pub struct GameplayCombo;
+
+// Shrinks as the combo window runs out
+#[derive(Component)]
+pub struct GameplayComboBar;
+
+#[derive(Component)]
+pub struct GameplayWave;
+
+// Fills up as the enemies of the current wave are spawned
+#[derive(Component)]
+pub struct GameplayWaveBar;
+
 #[derive(Component)]
 pub struct GameplayPauseBtn;
 
@@ -37,9 +75,18 @@ pub fn reset_gameplay_stats(mut stats: ResMut<Stats>) {
     stats.watch.pause();
     stats.watch.reset();
     stats.score = 0;
+    stats.player_scores = [0; consts::MAX_PLAYERS];
 }
 
-pub fn setup_gameplay_ui(mut commands: Commands, stats: Res<Stats>) {
+pub fn setup_gameplay_ui(
+    mut commands: Commands,
+    stats: Res<Stats>,
+    player_count: Res<PlayerCount>,
+    localization: Localization,
+) {
+    // HUD keeps the built-in font unless the language needs its own
+    let font = localization.locale_font().unwrap_or_default();
+
     commands
         .spawn((
             GameplayUi,
@@ -67,27 +114,78 @@ pub fn setup_gameplay_ui(mut commands: Commands, stats: Res<Stats>) {
                 })
                 .with_children(|parent| {
                     parent
-                        .spawn(NodeBundle { ..default() })
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Column,
+                                ..default()
+                            },
+                            ..default()
+                        })
                         .with_children(|parent| {
-                            for _ in 1..=consts::PLAYER_MAX_HEALTH {
-                                parent.spawn((
-                                    HealthPoint,
-                                    NodeBundle {
+                            // One row of health points for every spaceship
+                            for player in (0..player_count.0).map(PlayerId) {
+                                parent
+                                    .spawn(NodeBundle {
                                         style: Style {
-                                            width: Val::Px(30.0),
-                                            height: Val::Px(30.0),
-                                            margin: UiRect::all(Val::Px(5.0)),
+                                            align_items: AlignItems::Center,
                                             ..default()
                                         },
-                                        border_color: Color::BLUE.into(),
-                                        background_color: {
-                                            BackgroundColor::from(
-                                                HealthPoint::empty_health_point_color(),
-                                            )
-                                        },
                                         ..default()
-                                    },
-                                ));
+                                    })
+                                    .with_children(|parent| {
+                                        if player_count.is_coop() {
+                                            parent.spawn(TextBundle::from_section(
+                                                localization.format(
+                                                    "player",
+                                                    &[("player", &(player.0 + 1))],
+                                                ),
+                                                TextStyle {
+                                                    font: font.clone(),
+                                                    font_size: 30.0,
+                                                    color: Color::WHITE,
+                                                },
+                                            ));
+                                        }
+
+                                        parent.spawn((HealthPoints(player), NodeBundle::default()));
+
+                                        parent
+                                            .spawn(NodeBundle {
+                                                style: Style {
+                                                    width: Val::Px(60.0),
+                                                    height: Val::Px(10.0),
+                                                    margin: UiRect::all(Val::Px(5.0)),
+                                                    ..default()
+                                                },
+                                                background_color: Color::rgb(0.3, 0.3, 0.3).into(),
+                                                ..default()
+                                            })
+                                            .with_children(|parent| {
+                                                parent.spawn((
+                                                    GameplayChargeBar(player),
+                                                    NodeBundle {
+                                                        style: Style {
+                                                            width: Val::Percent(0.0),
+                                                            height: Val::Percent(100.0),
+                                                            ..default()
+                                                        },
+                                                        ..default()
+                                                    },
+                                                ));
+                                            });
+
+                                        parent.spawn((
+                                            GameplayLives(player),
+                                            TextBundle::from_section(
+                                                "",
+                                                TextStyle {
+                                                    font: font.clone(),
+                                                    font_size: 20.0,
+                                                    color: Color::WHITE,
+                                                },
+                                            ),
+                                        ));
+                                    });
                             }
                         });
                 })
@@ -106,9 +204,9 @@ pub fn setup_gameplay_ui(mut commands: Commands, stats: Res<Stats>) {
                                 TextBundle::from_section(
                                     stats.score.to_string(),
                                     TextStyle {
+                                        font: font.clone(),
                                         font_size: 30.0,
                                         color: Color::WHITE,
-                                        ..default()
                                     },
                                 ),
                             ));
@@ -119,47 +217,184 @@ pub fn setup_gameplay_ui(mut commands: Commands, stats: Res<Stats>) {
                                 TextBundle::from_section(
                                     stats.get_watch_time(),
                                     TextStyle {
+                                        font: font.clone(),
                                         font_size: 30.0,
                                         color: Color::WHITE,
+                                    },
+                                ),
+                            ));
+                        })
+                        .with_children(|parent| {
+                            parent.spawn((
+                                GameplayCombo,
+                                TextBundle::from_section(
+                                    "",
+                                    TextStyle {
+                                        font: font.clone(),
+                                        font_size: 20.0,
+                                        color: Color::YELLOW,
+                                    },
+                                ),
+                            ));
+                        })
+                        .with_children(|parent| {
+                            parent
+                                .spawn(NodeBundle {
+                                    style: Style {
+                                        width: Val::Px(100.0),
+                                        height: Val::Px(5.0),
                                         ..default()
                                     },
+                                    background_color: Color::rgb(0.3, 0.3, 0.3).into(),
+                                    ..default()
+                                })
+                                .with_children(|parent| {
+                                    parent.spawn((
+                                        GameplayComboBar,
+                                        NodeBundle {
+                                            style: Style {
+                                                width: Val::Percent(0.0),
+                                                height: Val::Percent(100.0),
+                                                ..default()
+                                            },
+                                            background_color: Color::YELLOW.into(),
+                                            ..default()
+                                        },
+                                    ));
+                                });
+                        })
+                        .with_children(|parent| {
+                            parent.spawn((
+                                GameplayWave,
+                                TextBundle::from_section(
+                                    "",
+                                    TextStyle {
+                                        font: font.clone(),
+                                        font_size: 20.0,
+                                        color: Color::WHITE,
+                                    },
                                 ),
                             ));
+                        })
+                        .with_children(|parent| {
+                            parent
+                                .spawn(NodeBundle {
+                                    style: Style {
+                                        width: Val::Px(100.0),
+                                        height: Val::Px(5.0),
+                                        ..default()
+                                    },
+                                    background_color: Color::rgb(0.3, 0.3, 0.3).into(),
+                                    ..default()
+                                })
+                                .with_children(|parent| {
+                                    parent.spawn((
+                                        GameplayWaveBar,
+                                        NodeBundle {
+                                            style: Style {
+                                                width: Val::Percent(0.0),
+                                                height: Val::Percent(100.0),
+                                                ..default()
+                                            },
+                                            background_color: Color::CYAN.into(),
+                                            ..default()
+                                        },
+                                    ));
+                                });
                         });
                 })
                 .with_children(|parent| {
+                    ButtonBuilder::empty()
+                        .size(Val::Px(50.0), Val::Px(50.0))
+                        .spawn(parent, GameplayPauseBtn);
+                });
+        });
+}
+
+// Missing health points are added whenever max health grows
+pub fn spaceship_health_update(
+    mut commands: Commands,
+    spaceship_query: Query<(&SpaceshipHealth, &PlayerId, &ShipDefinition)>,
+    row_query: Query<(Entity, &HealthPoints, Option<&Children>)>,
+    mut ui_query: Query<&mut BackgroundColor, With<HealthPoint>>,
+    upgrades: Res<RunUpgrades>,
+) {
+    for (row, health_points, children) in row_query.iter() {
+        let spaceship = spaceship_query
+            .iter()
+            .find(|(_, player, _)| **player == health_points.0);
+
+        let Some((health, _, ship)) = spaceship else {
+            continue;
+        };
+
+        let point_count = children.map_or(0, |children| children.len());
+        let max_health = upgrades.max_health(ship) as usize;
+
+        if point_count < max_health {
+            commands.entity(row).with_children(|parent| {
+                for _ in point_count..max_health {
                     parent.spawn((
-                        GameplayPauseBtn,
-                        ButtonBundle {
+                        HealthPoint,
+                        NodeBundle {
                             style: Style {
-                                width: Val::Px(50.0),
-                                height: Val::Px(50.0),
+                                width: Val::Px(30.0),
+                                height: Val::Px(30.0),
                                 margin: UiRect::all(Val::Px(5.0)),
                                 ..default()
                             },
-                            background_color: Color::WHITE.into(),
+                            border_color: Color::BLUE.into(),
+                            background_color: HealthPoint::empty_health_point_color().into(),
                             ..default()
                         },
                     ));
-                });
-        });
+                }
+            });
+        }
+
+        let points = children.map_or(&[][..], |children| &children[..]);
+        for (idx, &point) in points.iter().enumerate() {
+            if let Ok(mut ui_element) = ui_query.get_mut(point) {
+                if idx >= health.0 as usize {
+                    ui_element.0 = HealthPoint::empty_health_point_color();
+                } else {
+                    ui_element.0 = HealthPoint::full_health_point_color();
+                }
+            }
+        }
+    }
 }
 
-pub fn spaceship_health_update(
-    spaceship_query: Query<&SpaceshipHealth>,
-    mut ui_query: Query<&mut BackgroundColor, With<HealthPoint>>,
+pub fn update_gameplay_charge(
+    spaceship_query: Query<(&PlayerId, &SpaceshipShoot, &ShipDefinition)>,
+    mut ui_query: Query<(&mut Style, &mut BackgroundColor, &GameplayChargeBar)>,
 ) {
-    if let Ok(health) = spaceship_query.get_single() {
-        for (idx, mut ui_element) in ui_query.iter_mut().enumerate() {
-            if idx >= health.0 as usize {
-                ui_element.0 = HealthPoint::empty_health_point_color();
-            } else {
-                ui_element.0 = HealthPoint::full_health_point_color();
-            }
+    for (mut style, mut background_color, charge_bar) in ui_query.iter_mut() {
+        let spaceship = spaceship_query
+            .iter()
+            .find(|(player, _, _)| **player == charge_bar.0);
+
+        if let Some((_, spaceship_shoot, ship)) = spaceship {
+            let charge = &ship.secondary.charge;
+            let charge_secs = spaceship_shoot.charge_secs().unwrap_or(0.0);
+
+            style.width = Val::Percent(charge.progress(charge_secs) * 100.0);
+            background_color.0 = charge.color(charge_secs);
         }
     }
 }
 
+pub fn update_gameplay_lives(
+    lives: Res<Lives>,
+    localization: Localization,
+    mut ui_query: Query<(&GameplayLives, &mut Text)>,
+) {
+    for (GameplayLives(player), mut ui_element) in ui_query.iter_mut() {
+        ui_element.sections[0].value =
+            localization.format("gameplay-lives", &[("lives", &lives.remaining[player.0])]);
+    }
+}
+
 pub fn update_gameplay_watch(
     mut stats: ResMut<Stats>,
     mut ui_query: Query<&mut Text, With<GameplayTime>>,
@@ -180,3 +415,49 @@ pub fn update_gameplay_score(
         ui_element.sections[0].value = stats.score.to_string();
     }
 }
+
+pub fn update_gameplay_combo(
+    combo: Res<Combo>,
+    config: Res<GameConfig>,
+    localization: Localization,
+    mut text_query: Query<&mut Text, With<GameplayCombo>>,
+    mut bar_query: Query<&mut Style, With<GameplayComboBar>>,
+) {
+    if let Ok(mut ui_element) = text_query.get_single_mut() {
+        ui_element.sections[0].value = match combo.kills {
+            0 => String::new(),
+            kills => localization.format(
+                "gameplay-combo",
+                &[
+                    ("kills", &kills),
+                    ("multiplier", &combo.multiplier(&config.score)),
+                ],
+            ),
+        };
+    }
+
+    if let Ok(mut style) = bar_query.get_single_mut() {
+        style.width = Val::Percent(combo.window_left() * 100.0);
+    }
+}
+
+pub fn update_gameplay_wave(
+    stage_query: Query<&GameplayStage>,
+    localization: Localization,
+    mut text_query: Query<&mut Text, With<GameplayWave>>,
+    mut bar_query: Query<&mut Style, With<GameplayWaveBar>>,
+) {
+    if let Ok(stage) = stage_query.get_single() {
+        if let Ok(mut ui_element) = text_query.get_single_mut() {
+            ui_element.sections[0].value = match stage.wave() {
+                0 => String::new(),
+                wave => localization.format("wave", &[("wave", &wave)]),
+            };
+        }
+
+        // Bar stays full while the last enemies of the wave are cleared
+        if let Ok(mut style) = bar_query.get_single_mut() {
+            style.width = Val::Percent(stage.spawn_progress().unwrap_or(0.0) * 100.0);
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use bevy::prelude::*;
+
+use crate::{
+    config::GameConfig,
+    locale::Localization,
+    player::{Ships, WeaponSlot},
+    profile::{HangarItem, ItemDescription, PickUnlock, Profile},
+};
+
+use super::widgets::{
+    is_pressed, set_disabled, Activated, ButtonBuilder, ButtonColors, Disabled, FocusList,
+    JustPressed,
+};
+
+#[derive(Component)]
+pub struct HangarUi;
+
+#[derive(Component)]
+pub struct HangarCredits;
+
+#[derive(Component)]
+pub struct HangarItemBtn(HangarItem);
+
+// Price, or whether the item is owned and equipped
+#[derive(Component)]
+pub struct HangarItemStatus(HangarItem);
+
+#[derive(Component)]
+pub struct HangarBackBtn;
+
+impl HangarItemBtn {
+    fn equipped_colors() -> ButtonColors {
+        ButtonColors {
+            normal: Color::rgb(1.0, 0.85, 0.2),
+            hovered: Color::rgb(0.85, 0.7, 0.15),
+            pressed: Color::rgb(0.7, 0.55, 0.1),
+            ..default()
+        }
+    }
+}
+
+fn item_description(item: &HangarItem, localization: &Localization) -> String {
+    match &item.description {
+        ItemDescription::Ship(description) => localization.text(description),
+        ItemDescription::Weapon(WeaponSlot::Primary) => localization.text("hangar-primary-weapon"),
+        ItemDescription::Weapon(WeaponSlot::Secondary) => {
+            localization.text("hangar-charged-weapon")
+        }
+        ItemDescription::Palette => localization.text("hangar-palette"),
+        ItemDescription::StartingUpgrade(description) => localization.format(
+            "hangar-starting-upgrade",
+            &[("description", &localization.text(description))],
+        ),
+    }
+}
+
+// ===
+
+pub fn setup_hangar_ui(
+    mut commands: Commands,
+    localization: Localization,
+    ships: Ships,
+    config: Res<GameConfig>,
+) {
+    let items = ships
+        .list()
+        .map(|ship_list| HangarItem::all(ship_list, &config))
+        .unwrap_or_default();
+
+    commands
+        .spawn((
+            HangarUi,
+            FocusList,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(localization.label("hangar-title", 60.0, Color::WHITE));
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                HangarCredits,
+                TextBundle::from_section("", localization.style(30.0, Color::YELLOW)),
+            ));
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Percent(90.0),
+                        margin: UiRect::vertical(Val::Px(20.0)),
+                        flex_wrap: FlexWrap::Wrap,
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for item in items {
+                        ButtonBuilder::empty()
+                            .style(Style {
+                                width: Val::Px(220.0),
+                                padding: UiRect::all(Val::Px(10.0)),
+                                margin: UiRect::all(Val::Px(5.0)),
+                                flex_direction: FlexDirection::Column,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            })
+                            .spawn(parent, HangarItemBtn(item.clone()))
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(
+                                    localization.text(&item.name),
+                                    localization.style(25.0, Color::BLACK),
+                                ));
+                                parent.spawn(
+                                    TextBundle::from_section(
+                                        item_description(&item, &localization),
+                                        localization.style(15.0, Color::BLACK),
+                                    )
+                                    .with_text_alignment(TextAlignment::Center),
+                                );
+                                parent.spawn((
+                                    HangarItemStatus(item),
+                                    TextBundle::from_section(
+                                        "",
+                                        localization.style(20.0, Color::BLACK),
+                                    ),
+                                ));
+                            });
+                    }
+                });
+        })
+        .with_children(|parent| {
+            ButtonBuilder::new(&localization, "back").spawn(parent, HangarBackBtn);
+        });
+}
+
+pub fn hangar_pick(
+    mut ev_pick: EventWriter<PickUnlock>,
+    query: Query<(&Interaction, Option<&Activated>, &HangarItemBtn), JustPressed>,
+) {
+    for (interaction, activated, item_btn) in query.iter() {
+        if is_pressed(interaction, activated) {
+            ev_pick.send(PickUnlock(item_btn.0.unlock.clone()));
+        }
+    }
+}
+
+// Items the profile can't afford yet are disabled, equipped ones are highlighted
+pub fn update_hangar(
+    mut commands: Commands,
+    profile: Res<Profile>,
+    localization: Localization,
+    mut credits_query: Query<&mut Text, (With<HangarCredits>, Without<HangarItemStatus>)>,
+    mut status_query: Query<(&mut Text, &HangarItemStatus)>,
+    mut btn_query: Query<(Entity, &mut ButtonColors, &HangarItemBtn, Option<&Disabled>)>,
+) {
+    if let Ok(mut ui_element) = credits_query.get_single_mut() {
+        ui_element.sections[0].value =
+            localization.format("hangar-credits", &[("credits", &profile.credits)]);
+    }
+
+    for (mut ui_element, HangarItemStatus(item)) in status_query.iter_mut() {
+        ui_element.sections[0].value = match (
+            profile.is_unlocked(&item.unlock),
+            profile.is_equipped(&item.unlock),
+        ) {
+            (true, true) => localization.text("hangar-equipped"),
+            (true, false) => localization.text("hangar-owned"),
+            (false, _) => localization.format("hangar-price", &[("price", &item.price)]),
+        };
+    }
+
+    for (entity, mut colors, HangarItemBtn(item), disabled) in btn_query.iter_mut() {
+        let is_unlocked = profile.is_unlocked(&item.unlock);
+        let item_colors = match profile.is_equipped(&item.unlock) {
+            true => HangarItemBtn::equipped_colors(),
+            false => ButtonColors::default(),
+        };
+
+        if *colors != item_colors {
+            *colors = item_colors;
+        }
+
+        set_disabled(
+            &mut commands,
+            entity,
+            !is_unlocked && profile.credits < item.price,
+            disabled,
+        );
+    }
+}
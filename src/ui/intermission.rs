@@ -0,0 +1,113 @@
+use bevy::prelude::*;
+
+use crate::{consts, enemy::GameplayStage, locale::Localization, Stats};
+
+// Wave banner at the start of a wave, and the tally with the countdown between waves
+#[derive(Component)]
+pub struct IntermissionUi;
+
+#[derive(Component)]
+pub struct IntermissionText;
+
+impl IntermissionText {
+    const TITLE: usize = 0;
+    const TALLY: usize = 1;
+    const COUNTDOWN: usize = 2;
+}
+
+// ===
+
+pub fn setup_intermission_ui(mut commands: Commands, localization: Localization) {
+    commands
+        .spawn((
+            IntermissionUi,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                IntermissionText,
+                TextBundle::from_sections([
+                    TextSection::new("", localization.style(50.0, Color::WHITE)),
+                    TextSection::new("", localization.style(20.0, Color::YELLOW)),
+                    TextSection::new("", localization.style(30.0, Color::WHITE)),
+                ])
+                .with_text_alignment(TextAlignment::Center),
+            ));
+        });
+}
+
+pub fn update_intermission(
+    stage_query: Query<&GameplayStage>,
+    stats: Res<Stats>,
+    localization: Localization,
+    mut text_query: Query<&mut Text, With<IntermissionText>>,
+) {
+    let (Ok(stage), Ok(mut text)) = (stage_query.get_single(), text_query.get_single_mut()) else {
+        return;
+    };
+
+    let (title, alpha, tally, countdown) = match stage.cooldown_left() {
+        Some(cooldown_left) => {
+            let (title, tally) = match stage.tally() {
+                Some(tally) => (
+                    localization.format("wave-clear", &[("wave", &stage.wave())]),
+                    format!(
+                        "\n{}\n",
+                        localization.format(
+                            "wave-tally",
+                            &[
+                                ("time", &format!("{:.1}", tally.time_secs)),
+                                ("time_bonus", &tally.time_bonus),
+                                ("accuracy", &format!("{:.0}", tally.accuracy)),
+                                ("accuracy_bonus", &tally.accuracy_bonus),
+                                ("no_damage_bonus", &tally.no_damage_bonus),
+                                ("bonus", &tally.total()),
+                            ],
+                        )
+                    ),
+                ),
+                None => (localization.text("wave-get-ready"), "\n".to_string()),
+            };
+            let countdown = format!(
+                "\n{}",
+                localization.format(
+                    "wave-countdown",
+                    &[("secs", &(cooldown_left.ceil() as u32))]
+                )
+            );
+
+            (title, 1.0, tally, countdown)
+        }
+        None => {
+            // Banner fades out shortly after the wave starts
+            let wave_time = stage.wave_time(&stats);
+            match wave_time < consts::WAVE_BANNER_TIME {
+                true => (
+                    localization.format("wave", &[("wave", &stage.wave())]),
+                    1.0 - wave_time / consts::WAVE_BANNER_TIME,
+                    String::new(),
+                    String::new(),
+                ),
+                false => (String::new(), 0.0, String::new(), String::new()),
+            }
+        }
+    };
+
+    text.sections[IntermissionText::TITLE].value = title;
+    text.sections[IntermissionText::TITLE]
+        .style
+        .color
+        .set_a(alpha);
+    text.sections[IntermissionText::TALLY].value = tally;
+    text.sections[IntermissionText::COUNTDOWN].value = countdown;
+}
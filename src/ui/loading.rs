@@ -0,0 +1,147 @@
+use bevy::prelude::*;
+
+use crate::{
+    assets::{FailedAssets, LoadingAssets},
+    locale::{Localization, LocalizedText},
+};
+
+use super::widgets::{ButtonBuilder, FocusList};
+
+#[derive(Component)]
+pub struct LoadingUi;
+
+#[derive(Component)]
+pub struct LoadingProgressBar;
+
+#[derive(Component)]
+pub struct LoadingErrorUi;
+
+#[derive(Component)]
+pub struct LoadingErrorExitBtn;
+
+// ===
+
+// String tables may still be loading, the title is filled in once they are
+pub fn setup_loading_ui(mut commands: Commands) {
+    commands
+        .spawn((
+            LoadingUi,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                LocalizedText("loading-title"),
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 30.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+            ));
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Percent(50.0),
+                        height: Val::Px(20.0),
+                        margin: UiRect::all(Val::Px(10.0)),
+                        border: UiRect::all(Val::Px(2.0)),
+                        ..default()
+                    },
+                    border_color: Color::WHITE.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        LoadingProgressBar,
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Percent(0.0),
+                                height: Val::Percent(100.0),
+                                ..default()
+                            },
+                            background_color: Color::WHITE.into(),
+                            ..default()
+                        },
+                    ));
+                });
+        });
+}
+
+pub fn update_loading_progress_bar(
+    loading_assets: Res<LoadingAssets>,
+    mut ui_query: Query<&mut Style, With<LoadingProgressBar>>,
+) {
+    if let Ok(mut style) = ui_query.get_single_mut() {
+        style.width = Val::Percent(loading_assets.progress() * 100.0);
+    }
+}
+
+// Font could be one of the failed assets, so the default font is used here
+pub fn setup_loading_error_ui(
+    mut commands: Commands,
+    failed_assets: Res<FailedAssets>,
+    localization: Localization,
+) {
+    commands
+        .spawn((
+            LoadingErrorUi,
+            FocusList,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                localization.text("loading-failed"),
+                TextStyle {
+                    font_size: 40.0,
+                    color: Color::RED,
+                    ..default()
+                },
+            ));
+
+            for path in failed_assets.0.iter() {
+                parent.spawn(TextBundle::from_section(
+                    path.clone(),
+                    TextStyle {
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ));
+            }
+        })
+        .with_children(|parent| {
+            ButtonBuilder::text(
+                localization.text("loading-exit"),
+                TextStyle {
+                    font_size: 40.0,
+                    color: Color::BLACK,
+                    ..default()
+                },
+            )
+            .spawn(parent, LoadingErrorExitBtn);
+        });
+}
@@ -1,81 +1,161 @@
+use std::f32::consts::TAU;
+
 use bevy::prelude::*;
 
-use crate::FontHandle;
+use crate::{consts, locale::Localization, player::PlayerCount, run_save, title::AttractMode};
+
+use super::widgets::{ButtonBuilder, FocusList};
 
 #[derive(Component)]
 pub struct MainMenuUi;
 
+// Game name, bobs up and down
+#[derive(Component)]
+pub struct MainMenuLogo;
+
+// Shown while the demo plays behind the menu
+#[derive(Component)]
+pub struct MainMenuDemoLabel;
+
+#[derive(Component)]
+pub struct MainMenuContinueBtn;
+
 #[derive(Component)]
 pub struct MainMenuPlayBtn;
 
+#[derive(Component)]
+pub struct MainMenuCoopBtn;
+
+#[derive(Component)]
+pub struct MainMenuAchievementsBtn;
+
+#[derive(Component)]
+pub struct MainMenuHangarBtn;
+
+#[derive(Component)]
+pub struct MainMenuSettingsBtn;
+
 #[derive(Component)]
 pub struct MainMenuExitBtn;
 
 // ===
 
-pub fn setup_main_menu_ui(mut commands: Commands, font: Res<FontHandle>) {
+pub fn set_single_player(mut player_count: ResMut<PlayerCount>) {
+    player_count.0 = 1;
+}
+
+pub fn set_coop(mut player_count: ResMut<PlayerCount>) {
+    player_count.0 = 2;
+}
+
+pub fn setup_main_menu_ui(mut commands: Commands, localization: Localization) {
     commands
         .spawn((
             MainMenuUi,
+            FocusList,
             NodeBundle {
                 style: Style {
                     width: Val::Percent(100.0),
                     height: Val::Percent(100.0),
-                    justify_content: JustifyContent::Center,
-                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::SpaceEvenly,
+                    align_items: AlignItems::Center,
                     ..default()
                 },
                 ..default()
             },
         ))
         .with_children(|parent| {
+            // Game name stays the same in every language
             parent
                 .spawn((
-                    MainMenuPlayBtn,
-                    ButtonBundle {
+                    MainMenuLogo,
+                    NodeBundle {
                         style: Style {
-                            padding: UiRect::all(Val::Px(5.0)),
-                            margin: UiRect::all(Val::Px(5.0)),
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
                             ..default()
                         },
-                        background_color: Color::WHITE.into(),
                         ..default()
                     },
                 ))
                 .with_children(|parent| {
                     parent.spawn(TextBundle::from_section(
-                        "Play",
-                        TextStyle {
-                            font: font.0.clone(),
-                            font_size: 40.0,
-                            color: Color::BLACK,
-                        },
+                        "SPACE RANGER",
+                        localization.style(48.0, Color::WHITE),
+                    ));
+                    parent.spawn(TextBundle::from_section(
+                        "CINNAMON",
+                        localization.style(72.0, Color::rgb(0.82, 0.45, 0.2)),
                     ));
                 });
-        })
-        .with_children(|parent| {
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(320.0),
+                        flex_direction: FlexDirection::Column,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    // Greyed out unless there is a saved run to resume
+                    ButtonBuilder::new(&localization, "menu-continue")
+                        .disabled(!run_save::has_saved_run())
+                        .spawn(parent, MainMenuContinueBtn);
+                    ButtonBuilder::new(&localization, "menu-play").spawn(parent, MainMenuPlayBtn);
+                    ButtonBuilder::new(&localization, "menu-coop").spawn(parent, MainMenuCoopBtn);
+                    ButtonBuilder::new(&localization, "menu-hangar")
+                        .spawn(parent, MainMenuHangarBtn);
+                    ButtonBuilder::new(&localization, "menu-achievements")
+                        .spawn(parent, MainMenuAchievementsBtn);
+                    ButtonBuilder::new(&localization, "menu-settings")
+                        .spawn(parent, MainMenuSettingsBtn);
+                    ButtonBuilder::new(&localization, "menu-exit").spawn(parent, MainMenuExitBtn);
+                });
+
             parent
                 .spawn((
-                    MainMenuExitBtn,
-                    ButtonBundle {
+                    MainMenuDemoLabel,
+                    NodeBundle {
                         style: Style {
-                            padding: UiRect::all(Val::Px(5.0)),
-                            margin: UiRect::all(Val::Px(5.0)),
+                            position_type: PositionType::Absolute,
+                            left: Val::Px(0.0),
+                            right: Val::Px(0.0),
+                            bottom: Val::Px(20.0),
+                            justify_content: JustifyContent::Center,
                             ..default()
                         },
-                        background_color: Color::WHITE.into(),
+                        visibility: Visibility::Hidden,
                         ..default()
                     },
                 ))
                 .with_children(|parent| {
-                    parent.spawn(TextBundle::from_section(
-                        "Exit",
-                        TextStyle {
-                            font: font.0.clone(),
-                            font_size: 40.0,
-                            color: Color::BLACK,
-                        },
-                    ));
+                    parent.spawn(localization.label("menu-demo", 30.0, Color::WHITE));
                 });
         });
 }
+
+pub fn bob_main_menu_logo(time: Res<Time>, mut logo_query: Query<&mut Style, With<MainMenuLogo>>) {
+    let bob = (time.elapsed_seconds() * consts::TITLE_LOGO_BOB_SPEED * TAU).sin();
+
+    for mut style in logo_query.iter_mut() {
+        style.top = Val::Px(bob * consts::TITLE_LOGO_BOB);
+    }
+}
+
+pub fn show_demo_label(
+    attract: Res<AttractMode>,
+    mut label_query: Query<&mut Visibility, With<MainMenuDemoLabel>>,
+) {
+    let visibility = match attract.is_playing() {
+        true => Visibility::Inherited,
+        false => Visibility::Hidden,
+    };
+
+    for mut label_visibility in label_query.iter_mut() {
+        if *label_visibility != visibility {
+            *label_visibility = visibility;
+        }
+    }
+}
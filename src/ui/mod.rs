@@ -1,47 +1,184 @@
-use bevy::{app::AppExit, prelude::*};
+use bevy::{app::AppExit, input::InputSystem, prelude::*};
 
 use crate::{
-    common::EntityType,
+    common::{AsteroidBreaking, EntityType},
     despawn_entities,
     enemy::Gameplay,
-    is_gameplay, is_playing,
-    player::{load_player_asset_dimensions, load_player_assets, PlayerAssetDimensions},
+    is_gameplay, is_playing, player, run_save,
+    score::ScorePopup,
+    transition::{self, ChangeState},
     GameState, GameplayState,
 };
 
+mod achievements;
+mod game_continue;
 mod game_over;
 mod gameplay;
+mod hangar;
+mod intermission;
+mod loading;
 mod mainmenu;
 mod pause;
+mod settings;
+mod ship_select;
+mod shop;
+mod widgets;
 
 pub struct UiPlugin;
 
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
         app
+            // === Widgets ===
+            .add_systems(
+                PreUpdate,
+                (
+                    widgets::clear_activated,
+                    widgets::activate_focused.run_if(transition::is_idle),
+                    widgets::release_focus_lists,
+                )
+                    .chain()
+                    .after(InputSystem),
+            )
+            .add_systems(
+                Update,
+                (
+                    (
+                        widgets::focus_new_lists,
+                        widgets::focus_hovered,
+                        widgets::navigate_focus,
+                    )
+                        .chain(),
+                    widgets::update_button_colors,
+                    (
+                        widgets::drag_sliders,
+                        widgets::step_focused_sliders,
+                        widgets::update_slider_fills,
+                    )
+                        .chain(),
+                    (widgets::flip_toggles, widgets::update_toggle_checks).chain(),
+                ),
+            )
             // === Menu ===
             .add_systems(OnEnter(GameState::MainMenu), mainmenu::setup_main_menu_ui)
             .add_systems(
                 Update,
                 (
-                    (game_to_loading_assets, gameplay_playing)
+                    (
+                        run_save::load_saved_run,
+                        game_to_loading_assets,
+                        gameplay_playing,
+                    )
+                        .run_if(clicked_btn::<mainmenu::MainMenuContinueBtn>),
+                    (mainmenu::set_single_player, game_to_ship_select)
                         .run_if(clicked_btn::<mainmenu::MainMenuPlayBtn>),
+                    (mainmenu::set_coop, game_to_ship_select)
+                        .run_if(clicked_btn::<mainmenu::MainMenuCoopBtn>),
+                    game_to_achievements.run_if(clicked_btn::<mainmenu::MainMenuAchievementsBtn>),
+                    game_to_hangar.run_if(clicked_btn::<mainmenu::MainMenuHangarBtn>),
+                    game_to_settings.run_if(clicked_btn::<mainmenu::MainMenuSettingsBtn>),
                     exit_app.run_if(clicked_btn::<mainmenu::MainMenuExitBtn>),
+                    (mainmenu::bob_main_menu_logo, mainmenu::show_demo_label)
+                        .run_if(in_state(GameState::MainMenu)),
                 ),
             )
             .add_systems(
                 OnExit(GameState::MainMenu),
                 despawn_entities::<mainmenu::MainMenuUi>,
             )
-            // === Loading ===
-            .add_systems(OnEnter(GameState::LoadingGame), load_player_assets)
+            // === Ship Select ===
             .add_systems(
-                Update,
+                OnEnter(GameState::ShipSelect),
                 (
-                    load_player_asset_dimensions,
-                    game_to_gameplay.run_if(resource_exists::<PlayerAssetDimensions>()),
+                    ship_select::skip_locked_ships,
+                    ship_select::setup_ship_select_ui,
                 )
-                    .run_if(in_state(GameState::LoadingGame)),
+                    .chain(),
+            )
+            .add_systems(
+                Update,
+                (
+                    ship_select::change_selected_ship.run_if(in_state(GameState::ShipSelect)),
+                    (game_to_loading_assets, gameplay_playing)
+                        .run_if(clicked_btn::<ship_select::ShipSelectStartBtn>),
+                    game_to_main_menu.run_if(clicked_btn::<ship_select::ShipSelectBackBtn>),
+                ),
+            )
+            .add_systems(
+                OnExit(GameState::ShipSelect),
+                despawn_entities::<ship_select::ShipSelectUi>,
+            )
+            // === Achievements ===
+            .add_systems(
+                OnEnter(GameState::Achievements),
+                achievements::setup_achievements_ui,
+            )
+            .add_systems(
+                Update,
+                game_to_main_menu.run_if(clicked_btn::<achievements::AchievementsBackBtn>),
+            )
+            .add_systems(
+                OnExit(GameState::Achievements),
+                despawn_entities::<achievements::AchievementsUi>,
+            )
+            // === Hangar ===
+            .add_systems(OnEnter(GameState::Hangar), hangar::setup_hangar_ui)
+            .add_systems(
+                Update,
+                (
+                    (hangar::hangar_pick, hangar::update_hangar)
+                        .run_if(in_state(GameState::Hangar)),
+                    game_to_main_menu.run_if(clicked_btn::<hangar::HangarBackBtn>),
+                ),
+            )
+            .add_systems(
+                OnExit(GameState::Hangar),
+                despawn_entities::<hangar::HangarUi>,
+            )
+            // === Settings ===
+            .add_systems(OnEnter(GameState::Settings), settings::setup_settings_ui)
+            .add_systems(
+                Update,
+                (
+                    (
+                        settings::change_language,
+                        settings::update_language_name,
+                        settings::change_fullscreen,
+                        settings::change_ui_scale,
+                    )
+                        .run_if(in_state(GameState::Settings)),
+                    game_to_main_menu.run_if(clicked_btn::<settings::SettingsBackBtn>),
+                ),
+            )
+            .add_systems(
+                OnExit(GameState::Settings),
+                despawn_entities::<settings::SettingsUi>,
+            )
+            // === Loading ===
+            .add_systems(OnEnter(GameState::LoadingMenu), loading::setup_loading_ui)
+            .add_systems(OnEnter(GameState::LoadingGame), loading::setup_loading_ui)
+            .add_systems(
+                Update,
+                loading::update_loading_progress_bar.run_if(
+                    in_state(GameState::LoadingMenu).or_else(in_state(GameState::LoadingGame)),
+                ),
+            )
+            .add_systems(
+                OnExit(GameState::LoadingMenu),
+                despawn_entities::<loading::LoadingUi>,
+            )
+            .add_systems(
+                OnExit(GameState::LoadingGame),
+                despawn_entities::<loading::LoadingUi>,
+            )
+            // === Loading Error ===
+            .add_systems(
+                OnEnter(GameState::LoadingError),
+                loading::setup_loading_error_ui,
+            )
+            .add_systems(
+                Update,
+                exit_app.run_if(clicked_btn::<loading::LoadingErrorExitBtn>),
             )
             // === Gameplay ===
             .add_systems(
@@ -49,19 +186,30 @@ impl Plugin for UiPlugin {
                 (
                     gameplay::setup_gameplay_ui,
                     gameplay::unpause_gameplay_watch,
+                    intermission::setup_intermission_ui,
                 ),
             )
             .add_systems(
                 Update,
-                gameplay::spaceship_health_update.run_if(is_gameplay),
+                (
+                    gameplay::spaceship_health_update,
+                    gameplay::update_gameplay_charge,
+                    gameplay::update_gameplay_lives,
+                    gameplay::update_gameplay_wave,
+                    intermission::update_intermission,
+                    achievements::show_achievement_toasts,
+                )
+                    .run_if(is_gameplay),
             )
             .add_systems(
                 Update,
                 (
                     gameplay::update_gameplay_watch,
                     gameplay::update_gameplay_score,
+                    gameplay::update_gameplay_combo,
+                    achievements::update_achievement_toasts,
                     gameplay_pause
-                        .run_if(clicked_btn::<gameplay::GameplayPauseBtn>.or_else(pressed_esc)),
+                        .run_if(clicked_btn::<gameplay::GameplayPauseBtn>.or_else(pressed_pause)),
                 )
                     .run_if(is_playing),
             )
@@ -69,9 +217,17 @@ impl Plugin for UiPlugin {
                 OnExit(GameState::Gameplay),
                 (
                     despawn_entities::<gameplay::GameplayUi>,
+                    despawn_entities::<intermission::IntermissionUi>,
                     despawn_entities::<pause::MenuPause>,
+                    despawn_entities::<shop::MenuShop>,
+                    despawn_entities::<game_continue::MenuContinue>,
                     despawn_entities::<game_over::MenuGameOver>,
                     despawn_entities::<EntityType>,
+                    despawn_entities::<AsteroidBreaking>,
+                    despawn_entities::<player::Beam>,
+                    despawn_entities::<player::Mine>,
+                    despawn_entities::<ScorePopup>,
+                    despawn_entities::<achievements::AchievementToast>,
                     despawn_entities::<Gameplay>,
                     gameplay::reset_gameplay_stats,
                 ),
@@ -81,15 +237,51 @@ impl Plugin for UiPlugin {
             .add_systems(
                 Update,
                 (
-                    gameplay_playing
-                        .run_if(clicked_btn::<pause::MenuCloseBtn>.or_else(pressed_esc)),
+                    gameplay_playing.run_if(
+                        clicked_btn::<pause::MenuCloseBtn>
+                            .or_else(pressed_pause.and_then(in_state(GameplayState::Paused))),
+                    ),
                     game_to_main_menu.run_if(clicked_btn::<pause::MenuExitBtn>),
+                    (run_save::save_run, game_to_main_menu)
+                        .run_if(clicked_btn::<pause::MenuSaveQuitBtn>),
                 ),
             )
             .add_systems(
                 OnExit(GameplayState::Paused),
                 despawn_entities::<pause::MenuPause>,
             )
+            // === Shop ===
+            .add_systems(OnEnter(GameplayState::Shop), shop::setup_shop_menu)
+            .add_systems(
+                Update,
+                (
+                    (shop::shop_buy, shop::update_shop_menu)
+                        .run_if(in_state(GameplayState::Shop).and_then(is_gameplay)),
+                    gameplay_playing.run_if(clicked_btn::<shop::ShopContinueBtn>),
+                ),
+            )
+            .add_systems(
+                OnExit(GameplayState::Shop),
+                despawn_entities::<shop::MenuShop>,
+            )
+            // === Continue ===
+            .add_systems(
+                OnEnter(GameplayState::Continue),
+                game_continue::setup_continue_menu,
+            )
+            .add_systems(
+                Update,
+                (
+                    game_continue::update_continue_countdown
+                        .run_if(in_state(GameplayState::Continue).and_then(is_gameplay)),
+                    player::continue_run.run_if(clicked_btn::<game_continue::MenuContinueBtn>),
+                    gameplay_game_over.run_if(clicked_btn::<game_continue::MenuGiveUpBtn>),
+                ),
+            )
+            .add_systems(
+                OnExit(GameplayState::Continue),
+                despawn_entities::<game_continue::MenuContinue>,
+            )
             // === Game Over ===
             .add_systems(
                 OnEnter(GameplayState::GameOver),
@@ -99,6 +291,8 @@ impl Plugin for UiPlugin {
                 Update,
                 (
                     game_to_main_menu.run_if(clicked_btn::<game_over::MenuQuitBtn>),
+                    game_over::change_summary_page
+                        .run_if(in_state(GameplayState::GameOver).and_then(is_gameplay)),
                     (
                         gameplay::reset_gameplay_stats,
                         gameplay_playing,
@@ -110,37 +304,58 @@ impl Plugin for UiPlugin {
     }
 }
 
-fn gameplay_pause(mut commands: Commands) {
-    commands.insert_resource(NextState(Some(GameplayState::Paused)));
+fn gameplay_pause(mut ev_change: EventWriter<ChangeState>) {
+    ev_change.send(ChangeState::to(GameplayState::Paused));
+}
+
+fn gameplay_playing(mut ev_change: EventWriter<ChangeState>) {
+    ev_change.send(ChangeState::to(GameplayState::Playing));
+}
+
+fn gameplay_game_over(mut ev_change: EventWriter<ChangeState>) {
+    ev_change.send(ChangeState::to(GameplayState::GameOver));
+}
+
+fn game_to_ship_select(mut ev_change: EventWriter<ChangeState>) {
+    ev_change.send(ChangeState::to(GameState::ShipSelect));
+}
+
+fn game_to_achievements(mut ev_change: EventWriter<ChangeState>) {
+    ev_change.send(ChangeState::to(GameState::Achievements));
 }
 
-fn gameplay_playing(mut commands: Commands) {
-    commands.insert_resource(NextState(Some(GameplayState::Playing)));
+fn game_to_hangar(mut ev_change: EventWriter<ChangeState>) {
+    ev_change.send(ChangeState::to(GameState::Hangar));
 }
 
-fn game_to_main_menu(mut commands: Commands) {
-    commands.insert_resource(NextState(Some(GameState::MainMenu)));
+fn game_to_settings(mut ev_change: EventWriter<ChangeState>) {
+    ev_change.send(ChangeState::to(GameState::Settings));
 }
 
-fn game_to_loading_assets(mut commands: Commands) {
-    commands.insert_resource(NextState(Some(GameState::LoadingGame)));
+fn game_to_main_menu(mut ev_change: EventWriter<ChangeState>) {
+    ev_change.send(ChangeState::to(GameState::MainMenu));
 }
 
-fn game_to_gameplay(mut commands: Commands) {
-    commands.insert_resource(NextState(Some(GameState::Gameplay)));
+fn game_to_loading_assets(mut ev_change: EventWriter<ChangeState>) {
+    ev_change.send(ChangeState::to(GameState::LoadingGame));
 }
 
-pub fn clicked_btn<T: Component>(query: Query<&Interaction, With<T>>) -> bool {
-    for interaction in query.iter() {
-        if let Interaction::Pressed = interaction {
+pub fn clicked_btn<T: Component>(
+    query: Query<
+        (&Interaction, Option<&widgets::Activated>),
+        (With<T>, Without<widgets::Disabled>),
+    >,
+) -> bool {
+    for (interaction, activated) in query.iter() {
+        if widgets::is_pressed(interaction, activated) {
             return true;
         }
     }
     false
 }
 
-pub fn pressed_esc(kdb: Res<Input<KeyCode>>) -> bool {
-    kdb.just_pressed(KeyCode::Escape)
+pub fn pressed_pause(input: widgets::MenuInput) -> bool {
+    input.pause()
 }
 
 pub fn exit_app(mut ev_exit: EventWriter<AppExit>) {
@@ -0,0 +1,168 @@
+use bevy::prelude::*;
+
+use crate::{
+    consts,
+    locale::{CycleLanguage, Localization},
+    settings::Settings,
+};
+
+use super::widgets::{
+    is_pressed, Activated, ButtonBuilder, FocusList, JustPressed, Slider, Toggle,
+};
+
+#[derive(Component)]
+pub struct SettingsUi;
+
+// Switches to the previous or the next language
+#[derive(Component)]
+pub struct SettingsLanguageArrowBtn {
+    is_next: bool,
+}
+
+#[derive(Component)]
+pub struct SettingsLanguageName;
+
+#[derive(Component)]
+pub struct SettingsFullscreenToggle;
+
+#[derive(Component)]
+pub struct SettingsUiScaleSlider;
+
+#[derive(Component)]
+pub struct SettingsBackBtn;
+
+// Name of the setting followed by the widgets that change it
+fn spawn_setting_row(
+    parent: &mut ChildBuilder,
+    localization: &Localization,
+    key: &'static str,
+    spawn_widgets: impl FnOnce(&mut ChildBuilder),
+) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                margin: UiRect::all(Val::Px(10.0)),
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(localization.label(key, 30.0, Color::WHITE));
+            spawn_widgets(parent);
+        });
+}
+
+// ===
+
+pub fn setup_settings_ui(
+    mut commands: Commands,
+    localization: Localization,
+    settings: Res<Settings>,
+) {
+    commands
+        .spawn((
+            SettingsUi,
+            FocusList,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(localization.label("settings-title", 60.0, Color::WHITE));
+
+            spawn_setting_row(parent, &localization, "settings-language", |parent| {
+                ButtonBuilder::text("<", localization.style(30.0, Color::BLACK))
+                    .size(Val::Px(40.0), Val::Px(40.0))
+                    .spawn(parent, SettingsLanguageArrowBtn { is_next: false });
+                // Language name sits between the arrows
+                parent.spawn((
+                    SettingsLanguageName,
+                    TextBundle::from_section(
+                        localization.language_name(),
+                        localization.style(30.0, Color::WHITE),
+                    ),
+                ));
+                ButtonBuilder::text(">", localization.style(30.0, Color::BLACK))
+                    .size(Val::Px(40.0), Val::Px(40.0))
+                    .spawn(parent, SettingsLanguageArrowBtn { is_next: true });
+            });
+
+            spawn_setting_row(parent, &localization, "settings-fullscreen", |parent| {
+                Toggle {
+                    on: settings.fullscreen,
+                }
+                .spawn(parent, SettingsFullscreenToggle);
+            });
+
+            spawn_setting_row(parent, &localization, "settings-ui-scale", |parent| {
+                Slider::new(
+                    settings.ui_scale,
+                    consts::UI_SCALE_MIN,
+                    consts::UI_SCALE_MAX,
+                    consts::UI_SCALE_STEP,
+                )
+                .spawn(parent, SettingsUiScaleSlider);
+            });
+
+            ButtonBuilder::new(&localization, "back").spawn(parent, SettingsBackBtn);
+        });
+}
+
+pub fn change_language(
+    mut ev_cycle: EventWriter<CycleLanguage>,
+    arrow_query: Query<(&Interaction, Option<&Activated>, &SettingsLanguageArrowBtn), JustPressed>,
+) {
+    for (interaction, activated, arrow) in arrow_query.iter() {
+        if is_pressed(interaction, activated) {
+            ev_cycle.send(CycleLanguage {
+                is_next: arrow.is_next,
+            });
+        }
+    }
+}
+
+pub fn update_language_name(
+    localization: Localization,
+    mut text_query: Query<&mut Text, With<SettingsLanguageName>>,
+) {
+    if !localization.is_changed() {
+        return;
+    }
+
+    if let Ok(mut ui_element) = text_query.get_single_mut() {
+        ui_element.sections[0].value = localization.language_name();
+    }
+}
+
+pub fn change_fullscreen(
+    mut settings: ResMut<Settings>,
+    toggle_query: Query<&Toggle, (Changed<Toggle>, With<SettingsFullscreenToggle>)>,
+) {
+    if let Ok(toggle) = toggle_query.get_single() {
+        if settings.fullscreen != toggle.on {
+            settings.fullscreen = toggle.on;
+            settings.store();
+        }
+    }
+}
+
+pub fn change_ui_scale(
+    mut settings: ResMut<Settings>,
+    slider_query: Query<&Slider, (Changed<Slider>, With<SettingsUiScaleSlider>)>,
+) {
+    if let Ok(slider) = slider_query.get_single() {
+        if settings.ui_scale != slider.value {
+            settings.ui_scale = slider.value;
+            settings.store();
+        }
+    }
+}
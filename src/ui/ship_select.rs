@@ -0,0 +1,216 @@
+use bevy::prelude::*;
+
+use crate::{
+    locale::Localization,
+    player::{PlayerCount, PlayerId, SelectedShips, ShipDefinition, ShipList, Ships, ShipsHandle},
+    profile::Profile,
+};
+
+use super::widgets::{is_pressed, Activated, ButtonBuilder, FocusList, JustPressed};
+
+#[derive(Component)]
+pub struct ShipSelectUi;
+
+// Switches the ship of a player to the previous or the next one
+#[derive(Component)]
+pub struct ShipSelectArrowBtn {
+    player: PlayerId,
+    is_next: bool,
+}
+
+#[derive(Component)]
+pub struct ShipSelectInfo(PlayerId);
+
+#[derive(Component)]
+pub struct ShipSelectStartBtn;
+
+#[derive(Component)]
+pub struct ShipSelectBackBtn;
+
+fn ship_info(ship: &ShipDefinition, localization: &Localization) -> String {
+    localization.format(
+        "ship-select-info",
+        &[
+            ("name", &localization.text(&ship.name)),
+            ("description", &localization.text(&ship.description)),
+            ("health", &ship.max_health),
+            ("speed", &ship.movement_speed),
+            ("dash", &ship.dash.speed),
+            ("primary", &localization.text(&ship.primary.name)),
+            ("charged", &localization.text(&ship.secondary.name)),
+        ],
+    )
+}
+
+// Index of the first ship unlocked in the profile, stepping from the given
+// one by the step which wraps around the ship list
+fn find_unlocked_ship(
+    ships: &[ShipDefinition],
+    profile: &Profile,
+    from: usize,
+    step: usize,
+) -> usize {
+    (0..ships.len())
+        .map(|offset| (from + offset * step) % ships.len())
+        .find(|&idx| profile.is_ship_unlocked(&ships[idx]))
+        .unwrap_or(from)
+}
+
+// ===
+
+// Ships locked in the hangar can't be picked
+pub fn skip_locked_ships(
+    mut selected_ships: ResMut<SelectedShips>,
+    ships_handle: Res<ShipsHandle>,
+    ship_lists: Res<Assets<ShipList>>,
+    profile: Res<Profile>,
+) {
+    let ships = match ship_lists.get(&ships_handle.0) {
+        Some(ship_list) if !ship_list.ships.is_empty() => &ship_list.ships,
+        _ => return,
+    };
+
+    for selected in selected_ships.0.iter_mut() {
+        *selected = find_unlocked_ship(ships, &profile, *selected, 1);
+    }
+}
+
+pub fn setup_ship_select_ui(
+    mut commands: Commands,
+    localization: Localization,
+    player_count: Res<PlayerCount>,
+    ships: Ships,
+    profile: Res<Profile>,
+) {
+    commands
+        .spawn((
+            ShipSelectUi,
+            FocusList,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(localization.label("ship-select-title", 60.0, Color::WHITE));
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        margin: UiRect::all(Val::Px(20.0)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    // One column for every player
+                    for player in (0..player_count.0).map(PlayerId) {
+                        parent
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    width: Val::Px(300.0),
+                                    flex_direction: FlexDirection::Column,
+                                    align_items: AlignItems::Center,
+                                    margin: UiRect::all(Val::Px(10.0)),
+                                    ..default()
+                                },
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                if player_count.is_coop() {
+                                    parent.spawn(TextBundle::from_section(
+                                        localization
+                                            .format("player", &[("player", &(player.0 + 1))]),
+                                        localization.style(30.0, Color::WHITE),
+                                    ));
+                                }
+                            })
+                            .with_children(|parent| {
+                                parent
+                                    .spawn(NodeBundle {
+                                        style: Style {
+                                            align_items: AlignItems::Center,
+                                            ..default()
+                                        },
+                                        ..default()
+                                    })
+                                    .with_children(|parent| {
+                                        for (arrow, is_next) in [("<", false), (">", true)] {
+                                            ButtonBuilder::text(
+                                                arrow,
+                                                localization.style(30.0, Color::BLACK),
+                                            )
+                                            .size(Val::Px(40.0), Val::Px(40.0))
+                                            .spawn(parent, ShipSelectArrowBtn { player, is_next });
+                                        }
+                                    });
+                            })
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    ShipSelectInfo(player),
+                                    TextBundle::from_section(
+                                        ships
+                                            .selected(player)
+                                            .zip(ships.list())
+                                            .map(|(ship, ship_list)| {
+                                                ship_info(
+                                                    &profile.outfit(ship, ship_list),
+                                                    &localization,
+                                                )
+                                            })
+                                            .unwrap_or_default(),
+                                        localization.style(20.0, Color::WHITE),
+                                    ),
+                                ));
+                            });
+                    }
+                });
+        })
+        .with_children(|parent| {
+            ButtonBuilder::new(&localization, "ship-select-start")
+                .initial_focus()
+                .spawn(parent, ShipSelectStartBtn);
+            ButtonBuilder::new(&localization, "back").spawn(parent, ShipSelectBackBtn);
+        });
+}
+
+pub fn change_selected_ship(
+    mut selected_ships: ResMut<SelectedShips>,
+    ships_handle: Res<ShipsHandle>,
+    ship_lists: Res<Assets<ShipList>>,
+    profile: Res<Profile>,
+    localization: Localization,
+    arrow_query: Query<(&Interaction, Option<&Activated>, &ShipSelectArrowBtn), JustPressed>,
+    mut info_query: Query<(&mut Text, &ShipSelectInfo)>,
+) {
+    let (ship_list, ships) = match ship_lists.get(&ships_handle.0) {
+        Some(ship_list) if !ship_list.ships.is_empty() => (ship_list, &ship_list.ships),
+        _ => return,
+    };
+
+    for (interaction, activated, arrow) in arrow_query.iter() {
+        if is_pressed(interaction, activated) {
+            let selected = &mut selected_ships.0[arrow.player.0];
+            let step = match arrow.is_next {
+                true => 1,
+                false => ships.len() - 1,
+            };
+            *selected = find_unlocked_ship(ships, &profile, *selected + step, step);
+        }
+    }
+
+    if selected_ships.is_changed() {
+        for (mut text, &ShipSelectInfo(player)) in info_query.iter_mut() {
+            let ship = ships.get(selected_ships.0[player.0]).unwrap_or(&ships[0]);
+            text.sections[0].value = ship_info(&profile.outfit(ship, ship_list), &localization);
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use bevy::prelude::*;
+
+use crate::{
+    locale::Localization,
+    player::RunUpgrades,
+    shop::{BuyUpgrade, ShopOffers},
+};
+
+use super::widgets::{
+    is_pressed, set_disabled, spawn_modal, Activated, ButtonBuilder, Disabled, JustPressed,
+};
+
+#[derive(Component)]
+pub struct MenuShop;
+
+#[derive(Component)]
+pub struct ShopScrap;
+
+// Index of the offer in `ShopOffers`
+#[derive(Component)]
+pub struct ShopOfferBtn(usize);
+
+#[derive(Component)]
+pub struct ShopOfferPrice(usize);
+
+#[derive(Component)]
+pub struct ShopContinueBtn;
+
+// ===
+
+pub fn setup_shop_menu(
+    mut commands: Commands,
+    localization: Localization,
+    offers: Res<ShopOffers>,
+) {
+    spawn_modal(&mut commands, MenuShop).with_children(|parent| {
+        parent.spawn(localization.label("shop-title", 60.0, Color::WHITE));
+        parent.spawn((
+            ShopScrap,
+            TextBundle::from_section("", localization.style(30.0, Color::YELLOW)),
+        ));
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    margin: UiRect::vertical(Val::Px(20.0)),
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|parent| {
+                for (idx, offer) in offers.0.iter().enumerate() {
+                    ButtonBuilder::empty()
+                        .style(Style {
+                            width: Val::Px(220.0),
+                            padding: UiRect::all(Val::Px(10.0)),
+                            margin: UiRect::all(Val::Px(5.0)),
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        })
+                        .spawn(parent, ShopOfferBtn(idx))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(
+                                localization.text(&offer.upgrade.name),
+                                localization.style(30.0, Color::BLACK),
+                            ));
+                            parent.spawn(
+                                TextBundle::from_section(
+                                    localization.text(&offer.upgrade.description),
+                                    localization.style(20.0, Color::BLACK),
+                                )
+                                .with_text_alignment(TextAlignment::Center),
+                            );
+                            parent.spawn((
+                                ShopOfferPrice(idx),
+                                TextBundle::from_section(
+                                    "",
+                                    localization.style(25.0, Color::BLACK),
+                                ),
+                            ));
+                        });
+                }
+            });
+        ButtonBuilder::new(&localization, "shop-continue")
+            .height(Val::Px(50.0))
+            .spawn(parent, ShopContinueBtn);
+    });
+}
+
+pub fn shop_buy(
+    mut ev_buy: EventWriter<BuyUpgrade>,
+    query: Query<(&Interaction, Option<&Activated>, &ShopOfferBtn), JustPressed>,
+) {
+    for (interaction, activated, offer_btn) in query.iter() {
+        if is_pressed(interaction, activated) {
+            ev_buy.send(BuyUpgrade(offer_btn.0));
+        }
+    }
+}
+
+// Sold offers and the ones the players can't afford are disabled
+pub fn update_shop_menu(
+    mut commands: Commands,
+    offers: Res<ShopOffers>,
+    upgrades: Res<RunUpgrades>,
+    localization: Localization,
+    mut scrap_query: Query<&mut Text, (With<ShopScrap>, Without<ShopOfferPrice>)>,
+    mut price_query: Query<(&mut Text, &ShopOfferPrice)>,
+    btn_query: Query<(Entity, &ShopOfferBtn, Option<&Disabled>)>,
+) {
+    if let Ok(mut ui_element) = scrap_query.get_single_mut() {
+        ui_element.sections[0].value =
+            localization.format("shop-scrap", &[("scrap", &upgrades.scrap)]);
+    }
+
+    for (mut ui_element, offer_price) in price_query.iter_mut() {
+        if let Some(offer) = offers.0.get(offer_price.0) {
+            ui_element.sections[0].value = match offer.sold {
+                true => localization.text("shop-sold"),
+                false => localization.format("shop-price", &[("price", &offer.price)]),
+            };
+        }
+    }
+
+    for (entity, offer_btn, disabled) in btn_query.iter() {
+        if let Some(offer) = offers.0.get(offer_btn.0) {
+            let is_available = !offer.sold && upgrades.scrap >= offer.price;
+            set_disabled(&mut commands, entity, !is_available, disabled);
+        }
+    }
+}
@@ -0,0 +1,814 @@
+use bevy::{
+    ecs::system::{EntityCommands, SystemParam},
+    prelude::*,
+    ui::{FocusPolicy, RelativeCursorPosition},
+};
+
+use crate::{
+    consts,
+    locale::{Localization, LocalizedText},
+};
+
+// Colors of a button in each of its states
+#[derive(Component, Clone, Copy, PartialEq)]
+pub struct ButtonColors {
+    pub normal: Color,
+    // Also used while the button is focused
+    pub hovered: Color,
+    pub pressed: Color,
+    pub disabled: Color,
+}
+
+impl Default for ButtonColors {
+    fn default() -> Self {
+        Self {
+            normal: Color::WHITE,
+            hovered: Color::rgb(0.8, 0.8, 0.8),
+            pressed: Color::rgb(0.6, 0.6, 0.6),
+            disabled: Color::rgb(0.4, 0.4, 0.4),
+        }
+    }
+}
+
+impl ButtonColors {
+    fn track() -> Self {
+        Self {
+            normal: Color::rgb(0.3, 0.3, 0.3),
+            hovered: Color::rgb(0.45, 0.45, 0.45),
+            pressed: Color::rgb(0.45, 0.45, 0.45),
+            disabled: Color::rgb(0.15, 0.15, 0.15),
+        }
+    }
+}
+
+// Greyed out widget that can't be pressed or focused
+#[derive(Component)]
+pub struct Disabled;
+
+// Widget that can be reached with the keyboard or a gamepad
+#[derive(Component)]
+pub struct Focusable;
+
+#[derive(Component)]
+pub struct Focused;
+
+impl Focused {
+    fn border_color() -> Color {
+        Color::rgb(0.3, 0.8, 1.0)
+    }
+}
+
+// Widget focused when its menu opens
+#[derive(Component)]
+pub struct InitialFocus;
+
+// Set for the frame in which the focused widget is pressed with the keyboard or a gamepad
+#[derive(Component)]
+pub struct Activated;
+
+// Container whose focusable widgets are navigated between with the keyboard or a gamepad
+#[derive(Component)]
+pub struct FocusList;
+
+// List that was just focused, its widgets can't be activated until confirm is
+// released, so only a press that starts after the list opened counts
+#[derive(Component)]
+pub struct AwaitingRelease;
+
+// Widgets that were just clicked or activated
+pub type JustPressed = (
+    Or<(Changed<Interaction>, Added<Activated>)>,
+    Without<Disabled>,
+);
+
+pub fn is_pressed(interaction: &Interaction, activated: Option<&Activated>) -> bool {
+    matches!(interaction, Interaction::Pressed) || activated.is_some()
+}
+
+// Enables or disables a widget, only touching it when that changes
+pub fn set_disabled(
+    commands: &mut Commands,
+    entity: Entity,
+    is_disabled: bool,
+    disabled: Option<&Disabled>,
+) {
+    match (is_disabled, disabled.is_some()) {
+        (true, false) => {
+            commands.entity(entity).insert(Disabled);
+        }
+        (false, true) => {
+            commands.entity(entity).remove::<Disabled>();
+        }
+        _ => {}
+    }
+}
+
+// Border every focusable widget keeps room for, colored while it's focused
+fn focus_border() -> UiRect {
+    UiRect::all(Val::Px(consts::MENU_FOCUS_BORDER))
+}
+
+// Menu input from the keyboard or any connected gamepad
+#[derive(SystemParam)]
+pub struct MenuInput<'w> {
+    keyboard: Res<'w, Input<KeyCode>>,
+    gamepads: Res<'w, Gamepads>,
+    gamepad_buttons: Res<'w, Input<GamepadButton>>,
+}
+
+impl<'w> MenuInput<'w> {
+    fn just_pressed(&self, keys: &[KeyCode], button_type: GamepadButtonType) -> bool {
+        self.keyboard.any_just_pressed(keys.iter().copied())
+            || self.gamepads.iter().any(|gamepad| {
+                self.gamepad_buttons
+                    .just_pressed(GamepadButton::new(gamepad, button_type))
+            })
+    }
+
+    fn up(&self) -> bool {
+        self.just_pressed(&[KeyCode::Up, KeyCode::W], GamepadButtonType::DPadUp)
+    }
+
+    fn down(&self) -> bool {
+        self.just_pressed(&[KeyCode::Down, KeyCode::S], GamepadButtonType::DPadDown)
+    }
+
+    fn left(&self) -> bool {
+        self.just_pressed(&[KeyCode::Left, KeyCode::A], GamepadButtonType::DPadLeft)
+    }
+
+    fn right(&self) -> bool {
+        self.just_pressed(&[KeyCode::Right, KeyCode::D], GamepadButtonType::DPadRight)
+    }
+
+    pub fn pause(&self) -> bool {
+        self.just_pressed(&[KeyCode::Escape], GamepadButtonType::Start)
+    }
+
+    fn pressed(&self, keys: &[KeyCode], button_type: GamepadButtonType) -> bool {
+        self.keyboard.any_pressed(keys.iter().copied())
+            || self.gamepads.iter().any(|gamepad| {
+                self.gamepad_buttons
+                    .pressed(GamepadButton::new(gamepad, button_type))
+            })
+    }
+
+    // Space is left out as it shoots, the gamepad south button shoots too but
+    // is the only one a gamepad can confirm with
+    fn confirm(&self) -> bool {
+        self.just_pressed(
+            &[KeyCode::Return, KeyCode::NumpadEnter],
+            GamepadButtonType::South,
+        )
+    }
+
+    fn confirm_held(&self) -> bool {
+        self.pressed(
+            &[KeyCode::Return, KeyCode::NumpadEnter],
+            GamepadButtonType::South,
+        )
+    }
+}
+
+// === Button ===
+
+pub struct ButtonBuilder {
+    // Text of the label and its key in the string tables
+    label: Option<(TextBundle, Option<&'static str>)>,
+    style: Style,
+    disabled: bool,
+    initial_focus: bool,
+}
+
+impl ButtonBuilder {
+    // Button labeled with a string from the string tables
+    pub fn new(localization: &Localization, key: &'static str) -> Self {
+        Self {
+            label: Some((
+                TextBundle::from_section(
+                    localization.text(key),
+                    localization.style(40.0, Color::BLACK),
+                ),
+                Some(key),
+            )),
+            ..Self::empty()
+        }
+    }
+
+    // Button labeled with a text that isn't translated
+    pub fn text(text: impl Into<String>, style: TextStyle) -> Self {
+        Self {
+            label: Some((TextBundle::from_section(text, style), None)),
+            ..Self::empty()
+        }
+    }
+
+    // Button without a label, its content is spawned by the caller
+    pub fn empty() -> Self {
+        Self {
+            label: None,
+            style: Style {
+                padding: UiRect::all(Val::Px(5.0)),
+                margin: UiRect::all(Val::Px(5.0)),
+                ..default()
+            },
+            disabled: false,
+            initial_focus: false,
+        }
+    }
+
+    pub fn height(mut self, height: Val) -> Self {
+        self.style.height = height;
+        self
+    }
+
+    // Fixed size button with its label centered
+    pub fn size(mut self, width: Val, height: Val) -> Self {
+        self.style.width = width;
+        self.style.height = height;
+        self.style.padding = UiRect::default();
+        self.style.justify_content = JustifyContent::Center;
+        self.style.align_items = AlignItems::Center;
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
+
+    pub fn initial_focus(mut self) -> Self {
+        self.initial_focus = true;
+        self
+    }
+
+    pub fn spawn<'w, 's, 'a>(
+        self,
+        parent: &'a mut ChildBuilder<'w, 's, '_>,
+        marker: impl Bundle,
+    ) -> EntityCommands<'w, 's, 'a> {
+        let mut button = parent.spawn((
+            marker,
+            Focusable,
+            ButtonColors::default(),
+            ButtonBundle {
+                style: Style {
+                    border: focus_border(),
+                    ..self.style
+                },
+                background_color: ButtonColors::default().normal.into(),
+                border_color: BorderColor(Color::NONE),
+                ..default()
+            },
+        ));
+
+        if self.disabled {
+            button.insert(Disabled);
+        }
+
+        if self.initial_focus {
+            button.insert(InitialFocus);
+        }
+
+        if let Some((text, key)) = self.label {
+            button.with_children(|parent| {
+                let mut label = parent.spawn(text);
+                if let Some(key) = key {
+                    label.insert(LocalizedText(key));
+                }
+            });
+        }
+
+        button
+    }
+}
+
+// === Slider ===
+
+// Dragged with the mouse, or stepped with left and right while focused
+#[derive(Component)]
+pub struct Slider {
+    pub value: f32,
+    min: f32,
+    max: f32,
+    step: f32,
+}
+
+#[derive(Component)]
+pub struct SliderFill;
+
+impl Slider {
+    pub fn new(value: f32, min: f32, max: f32, step: f32) -> Self {
+        Self {
+            value,
+            min,
+            max,
+            step,
+        }
+    }
+
+    fn fraction(&self) -> f32 {
+        (self.value - self.min) / (self.max - self.min)
+    }
+
+    // The value snapped to the closest step within the range
+    fn stepped(&self, value: f32) -> f32 {
+        let steps = ((value - self.min) / self.step).round();
+        (self.min + steps * self.step).clamp(self.min, self.max)
+    }
+
+    pub fn spawn(self, parent: &mut ChildBuilder, marker: impl Bundle) {
+        let fraction = self.fraction();
+
+        parent
+            .spawn((
+                marker,
+                self,
+                Focusable,
+                ButtonColors::track(),
+                RelativeCursorPosition::default(),
+                ButtonBundle {
+                    style: Style {
+                        width: Val::Px(200.0),
+                        height: Val::Px(26.0),
+                        margin: UiRect::all(Val::Px(10.0)),
+                        border: focus_border(),
+                        ..default()
+                    },
+                    background_color: ButtonColors::track().normal.into(),
+                    border_color: BorderColor(Color::NONE),
+                    ..default()
+                },
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    SliderFill,
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Percent(fraction * 100.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        background_color: Color::WHITE.into(),
+                        ..default()
+                    },
+                ));
+            });
+    }
+}
+
+// === Toggle ===
+
+// Switch that flips whenever it is pressed
+#[derive(Component)]
+pub struct Toggle {
+    pub on: bool,
+}
+
+#[derive(Component)]
+pub struct ToggleCheck;
+
+impl Toggle {
+    fn check_color(&self) -> Color {
+        match self.on {
+            true => Color::BLACK,
+            false => Color::NONE,
+        }
+    }
+
+    pub fn spawn(self, parent: &mut ChildBuilder, marker: impl Bundle) {
+        let check_color = self.check_color();
+
+        parent
+            .spawn((
+                marker,
+                self,
+                Focusable,
+                ButtonColors::default(),
+                ButtonBundle {
+                    style: Style {
+                        width: Val::Px(40.0),
+                        height: Val::Px(40.0),
+                        margin: UiRect::all(Val::Px(5.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        border: focus_border(),
+                        ..default()
+                    },
+                    background_color: Color::WHITE.into(),
+                    border_color: BorderColor(Color::NONE),
+                    ..default()
+                },
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    ToggleCheck,
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Px(24.0),
+                            height: Val::Px(24.0),
+                            ..default()
+                        },
+                        background_color: check_color.into(),
+                        ..default()
+                    },
+                ));
+            });
+    }
+}
+
+// === Modal ===
+
+// Panel over a dimmed backdrop that keeps clicks from reaching the screen below,
+// the content is spawned as children of the returned panel
+pub fn spawn_modal<'w, 's, 'a>(
+    commands: &'a mut Commands<'w, 's>,
+    marker: impl Bundle,
+) -> EntityCommands<'w, 's, 'a> {
+    let panel = commands
+        .spawn((
+            FocusList,
+            NodeBundle {
+                style: Style {
+                    padding: UiRect::all(Val::Px(20.0)),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0.05, 0.05, 0.1, 0.9).into(),
+                ..default()
+            },
+        ))
+        .id();
+
+    commands
+        .spawn((
+            marker,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.5).into(),
+                focus_policy: FocusPolicy::Block,
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+        ))
+        .add_child(panel);
+
+    commands.entity(panel)
+}
+
+// ===
+
+// Widgets of focus lists that can take the focus
+#[derive(SystemParam)]
+pub struct Focusables<'w, 's> {
+    children_query: Query<'w, 's, &'static Children>,
+    focusable_query: Query<'w, 's, (), (With<Focusable>, Without<Disabled>)>,
+}
+
+impl Focusables<'_, '_> {
+    // Focusable descendants of the entity, in the order they are laid out
+    fn of(&self, entity: Entity) -> Vec<Entity> {
+        let mut focusables = Vec::new();
+        self.collect(entity, &mut focusables);
+        focusables
+    }
+
+    fn collect(&self, entity: Entity, focusables: &mut Vec<Entity>) {
+        if let Ok(children) = self.children_query.get(entity) {
+            for &child in children.iter() {
+                if self.focusable_query.contains(child) {
+                    focusables.push(child);
+                }
+                self.collect(child, focusables);
+            }
+        }
+    }
+}
+
+// Whether any ancestor of the entity has the marker
+fn has_ancestor<T: Component>(
+    entity: Entity,
+    parent_query: &Query<&Parent>,
+    marker_query: &Query<(), With<T>>,
+) -> bool {
+    let mut current = entity;
+    while let Ok(parent) = parent_query.get(current) {
+        if marker_query.contains(parent.get()) {
+            return true;
+        }
+        current = parent.get();
+    }
+    false
+}
+
+fn move_focus(commands: &mut Commands, focused_query: &Query<Entity, With<Focused>>, to: Entity) {
+    for entity in focused_query.iter() {
+        if entity != to {
+            commands.entity(entity).remove::<Focused>();
+        }
+    }
+
+    if !focused_query.contains(to) {
+        commands.entity(to).insert(Focused);
+    }
+}
+
+// Closest widget in the direction, widgets off to the side count as further away
+fn find_in_direction(
+    from: Vec2,
+    direction: Vec2,
+    candidates: impl Iterator<Item = (Entity, Vec2)>,
+) -> Option<Entity> {
+    candidates
+        .filter_map(|(entity, position)| {
+            let offset = position - from;
+            let distance = offset.dot(direction);
+            (distance > 1.0).then(|| (entity, distance + offset.perp_dot(direction).abs() * 2.0))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(entity, _)| entity)
+}
+
+// Menus open with their initial widget focused, or their first one, and wait
+// for a fresh confirm press
+pub fn focus_new_lists(
+    mut commands: Commands,
+    list_query: Query<Entity, Added<FocusList>>,
+    widgets: Focusables,
+    initial_query: Query<(), With<InitialFocus>>,
+    focused_query: Query<Entity, With<Focused>>,
+) {
+    for list in list_query.iter() {
+        let focusables = widgets.of(list);
+
+        let initial = focusables
+            .iter()
+            .find(|entity| initial_query.contains(**entity))
+            .or(focusables.first());
+
+        if let Some(&entity) = initial {
+            move_focus(&mut commands, &focused_query, entity);
+        }
+
+        commands.entity(list).insert(AwaitingRelease);
+    }
+}
+
+// The mouse takes the focus along, so only one widget is ever highlighted
+pub fn focus_hovered(
+    mut commands: Commands,
+    hovered_query: Query<
+        (Entity, &Interaction),
+        (Changed<Interaction>, With<Focusable>, Without<Disabled>),
+    >,
+    parent_query: Query<&Parent>,
+    list_query: Query<(), With<FocusList>>,
+    focused_query: Query<Entity, With<Focused>>,
+) {
+    for (entity, interaction) in hovered_query.iter() {
+        if matches!(interaction, Interaction::Hovered)
+            && has_ancestor(entity, &parent_query, &list_query)
+        {
+            move_focus(&mut commands, &focused_query, entity);
+        }
+    }
+}
+
+pub fn navigate_focus(
+    mut commands: Commands,
+    input: MenuInput,
+    list_query: Query<Entity, With<FocusList>>,
+    widgets: Focusables,
+    transform_query: Query<&GlobalTransform>,
+    slider_query: Query<(), With<Slider>>,
+    focused_query: Query<Entity, With<Focused>>,
+) {
+    // UI positions grow downwards
+    let direction = if input.up() {
+        Vec2::NEG_Y
+    } else if input.down() {
+        Vec2::Y
+    } else if input.left() {
+        Vec2::NEG_X
+    } else if input.right() {
+        Vec2::X
+    } else {
+        return;
+    };
+
+    let position = |entity: Entity| {
+        transform_query
+            .get(entity)
+            .map(|transform| transform.translation().truncate())
+            .unwrap_or_default()
+    };
+
+    for list in list_query.iter() {
+        let focusables = widgets.of(list);
+
+        let Some(&first) = focusables.first() else {
+            continue;
+        };
+
+        // A list that lost its focus, like to a widget that got disabled, starts over
+        let Some(idx) = focusables
+            .iter()
+            .position(|entity| focused_query.contains(*entity))
+        else {
+            move_focus(&mut commands, &focused_query, first);
+            continue;
+        };
+
+        // Left and right step the focused slider instead
+        if direction.x != 0.0 && slider_query.contains(focusables[idx]) {
+            continue;
+        }
+
+        let count = focusables.len();
+        let next = find_in_direction(
+            position(focusables[idx]),
+            direction,
+            focusables.iter().map(|&entity| (entity, position(entity))),
+        )
+        // Up and down wrap around the list
+        .or_else(|| match direction.y {
+            y if y > 0.0 => Some(focusables[(idx + 1) % count]),
+            y if y < 0.0 => Some(focusables[(idx + count - 1) % count]),
+            _ => None,
+        });
+
+        if let Some(next) = next {
+            move_focus(&mut commands, &focused_query, next);
+        }
+    }
+}
+
+pub fn clear_activated(mut commands: Commands, query: Query<Entity, With<Activated>>) {
+    for entity in query.iter() {
+        commands.entity(entity).remove::<Activated>();
+    }
+}
+
+pub fn activate_focused(
+    mut commands: Commands,
+    input: MenuInput,
+    query: Query<Entity, (With<Focused>, Without<Disabled>)>,
+    parent_query: Query<&Parent>,
+    awaiting_query: Query<(), With<AwaitingRelease>>,
+) {
+    if !input.confirm() {
+        return;
+    }
+
+    for entity in query.iter() {
+        if !has_ancestor(entity, &parent_query, &awaiting_query) {
+            commands.entity(entity).insert(Activated);
+        }
+    }
+}
+
+// Runs after activation, so the press following the release is the first one counted
+pub fn release_focus_lists(
+    mut commands: Commands,
+    input: MenuInput,
+    query: Query<Entity, With<AwaitingRelease>>,
+) {
+    if input.confirm_held() {
+        return;
+    }
+
+    for entity in query.iter() {
+        commands.entity(entity).remove::<AwaitingRelease>();
+    }
+}
+
+pub fn update_button_colors(
+    mut query: Query<(
+        &Interaction,
+        &ButtonColors,
+        &mut BackgroundColor,
+        &mut BorderColor,
+        Option<&Focused>,
+        Option<&Activated>,
+        Option<&Disabled>,
+    )>,
+) {
+    for (
+        interaction,
+        colors,
+        mut background_color,
+        mut border_color,
+        focused,
+        activated,
+        disabled,
+    ) in query.iter_mut()
+    {
+        let color = if disabled.is_some() {
+            colors.disabled
+        } else if is_pressed(interaction, activated) {
+            colors.pressed
+        } else if matches!(interaction, Interaction::Hovered) || focused.is_some() {
+            colors.hovered
+        } else {
+            colors.normal
+        };
+
+        if background_color.0 != color {
+            background_color.0 = color;
+        }
+
+        let border = match focused {
+            Some(_) => Focused::border_color(),
+            None => Color::NONE,
+        };
+
+        if border_color.0 != border {
+            border_color.0 = border;
+        }
+    }
+}
+
+pub fn drag_sliders(
+    mut query: Query<(&Interaction, &RelativeCursorPosition, &mut Slider), Without<Disabled>>,
+) {
+    for (interaction, cursor, mut slider) in query.iter_mut() {
+        let Some(position) = cursor.normalized else {
+            continue;
+        };
+
+        if let Interaction::Pressed = interaction {
+            let value =
+                slider.stepped(slider.min + position.x.clamp(0.0, 1.0) * (slider.max - slider.min));
+            if slider.value != value {
+                slider.value = value;
+            }
+        }
+    }
+}
+
+pub fn step_focused_sliders(
+    input: MenuInput,
+    mut query: Query<&mut Slider, (With<Focused>, Without<Disabled>)>,
+) {
+    let steps = match (input.left(), input.right()) {
+        (true, false) => -1.0,
+        (false, true) => 1.0,
+        _ => return,
+    };
+
+    for mut slider in query.iter_mut() {
+        let value = slider.stepped(slider.value + steps * slider.step);
+        if slider.value != value {
+            slider.value = value;
+        }
+    }
+}
+
+pub fn update_slider_fills(
+    slider_query: Query<(&Slider, &Children), Changed<Slider>>,
+    mut fill_query: Query<&mut Style, With<SliderFill>>,
+) {
+    for (slider, children) in slider_query.iter() {
+        for &child in children.iter() {
+            if let Ok(mut style) = fill_query.get_mut(child) {
+                style.width = Val::Percent(slider.fraction() * 100.0);
+            }
+        }
+    }
+}
+
+pub fn flip_toggles(
+    mut query: Query<(&Interaction, Option<&Activated>, &mut Toggle), JustPressed>,
+) {
+    for (interaction, activated, mut toggle) in query.iter_mut() {
+        if is_pressed(interaction, activated) {
+            toggle.on = !toggle.on;
+        }
+    }
+}
+
+pub fn update_toggle_checks(
+    toggle_query: Query<(&Toggle, &Children), Changed<Toggle>>,
+    mut check_query: Query<&mut BackgroundColor, With<ToggleCheck>>,
+) {
+    for (toggle, children) in toggle_query.iter() {
+        for &child in children.iter() {
+            if let Ok(mut background_color) = check_query.get_mut(child) {
+                background_color.0 = toggle.check_color();
+            }
+        }
+    }
+}